@@ -19,9 +19,22 @@
 /// ///////////////////////////////////////////////////////////////////////////////////////////////
 
 use crate::error::CryptfolioError;
-use crate::database::{Database, script::DatabaseScript, entry::DatabaseEntry};
+use crate::database::{
+    Database,
+    script::DatabaseScript,
+    entry::{AllocationTarget, CoinCategory, CoinSnapshot, DatabaseEntry, FxRate, Setting, TransferLink},
+};
 use crate::platform::SyncClient;
-use crate::recording::CoinRecord;
+use crate::pricing::{CashEquivalent, CurrencyConverter, FxRateSource, PriceSource};
+use crate::recording::{
+    BenchmarkRecord, CashLedger, CoinRecord, ContributionRecord, CostBasisMethod, DiagnosticRecord,
+    DiagnosticType, HistoryRecord, Ledger, Period, PortfolioRecord, RebalanceRecord, ReconciliationRecord,
+    RecurringBuyRecord, ReturnRecord, RiskRecord, TransactionRecord, TransferMatch, TransferMatcher,
+    TransferRecord,
+};
+use crate::util::Util;
+use chrono::{NaiveDate, NaiveDateTime};
+use sqlite3::Connection;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
@@ -29,6 +42,8 @@ use std::rc::Rc;
 pub struct CryptfolioApp {
     database: Database,
     connected_platforms: RefCell<HashMap<String, Rc<Box<dyn SyncClient>>>>,
+    fx_source: RefCell<Option<Rc<dyn FxRateSource>>>,
+    price_source: RefCell<Option<Rc<dyn PriceSource>>>,
 }
 
 impl CryptfolioApp {
@@ -43,6 +58,10 @@ impl CryptfolioApp {
         // Load existing connections from database
         let mut platforms = HashMap::<String, Rc<Box<dyn SyncClient>>>::new();
         for connection in DatabaseScript::fetch_connections(db.get_dbh()) {
+            // Connections whose keys no longer authenticate are kept, but not loaded.
+            if connection.needs_new_keys() {
+                continue;
+            }
             let key = format!("{}:{}", connection.platform, connection.nickname);
             platforms.insert(key.to_string(), connection.to_concrete_type());
         }
@@ -51,6 +70,8 @@ impl CryptfolioApp {
             CryptfolioApp {
                 database: db,
                 connected_platforms: RefCell::new(platforms),
+                fx_source: RefCell::new(None),
+                price_source: RefCell::new(None),
             }
         )
     }
@@ -78,6 +99,18 @@ impl CryptfolioApp {
                     }
                 }
                 DatabaseScript::update_default_values(self.database.get_dbh());
+
+                // Clients which keep track of what they have synced save it with their connection.
+                let prefix = format!("{}:", platform.get_name());
+                let nickname = self.connected_platforms
+                    .borrow()
+                    .iter()
+                    .find(|x| Rc::ptr_eq(x.1, &platform))
+                    .and_then(|x| x.0.strip_prefix(prefix.as_str()).map(|x| x.to_string()));
+                if let Some(nickname) = nickname {
+                    DatabaseScript::update_connection(self.database.get_dbh(), &platform.get_connection(&nickname))?;
+                }
+                self.match_transfers(&TransferMatcher::default())?;
                 Ok(())
             },
             Err(e) => {
@@ -93,8 +126,506 @@ impl CryptfolioApp {
         Ok(())
     }
 
-    pub fn get_coin_record(&self, coin: String) -> CoinRecord {
-        DatabaseScript::fetch_coin_record(self.database.get_dbh(), coin)
+    pub fn get_reporting_currency(&self) -> String {
+        DatabaseScript::fetch_setting(self.database.get_dbh(), "reporting_currency").unwrap_or("USD".to_string())
+    }
+
+    pub fn set_reporting_currency(&self, currency: &str) -> Result<(), CryptfolioError> {
+        Setting::new("reporting_currency".to_string(), currency.to_uppercase()).write(self.database.get_dbh())
+    }
+
+    pub fn get_cost_basis_method(&self) -> CostBasisMethod {
+        DatabaseScript::fetch_setting(self.database.get_dbh(), "cost_basis_method")
+            .and_then(|x| x.parse::<CostBasisMethod>().ok())
+            .unwrap_or(CostBasisMethod::Fifo)
+    }
+
+    pub fn set_cost_basis_method(&self, method: CostBasisMethod) -> Result<(), CryptfolioError> {
+        Setting::new("cost_basis_method".to_string(), method.to_string()).write(self.database.get_dbh())
+    }
+
+    pub fn set_fx_source(&self, source: Rc<dyn FxRateSource>) {
+        *self.fx_source.borrow_mut() = Some(source);
+    }
+
+    /// Prices the coins which orders are quoted in (e.g. BTC on DOT-BTC), in the reporting currency.
+    pub fn set_price_source(&self, source: Rc<dyn PriceSource>) {
+        *self.price_source.borrow_mut() = Some(source);
+    }
+
+    pub fn get_cash_equivalents(&self) -> Vec<CashEquivalent> {
+        DatabaseScript::fetch_settings(self.database.get_dbh(), "cash_equivalent:")
+            .iter()
+            .filter_map(|(key, value)| CashEquivalent::from_setting(key.as_str(), value.as_str()))
+            .collect()
+    }
+
+    pub fn set_cash_equivalent(&self, cash_equivalent: CashEquivalent) -> Result<(), CryptfolioError> {
+        Setting::new(cash_equivalent.get_key(), cash_equivalent.get_value()).write(self.database.get_dbh())
+    }
+
+    fn get_converter(&self) -> CurrencyConverter {
+        let reporting_currency = self.get_reporting_currency();
+        let mut converter = CurrencyConverter::new(reporting_currency.as_str(), self.fx_source.borrow().clone());
+        converter.set_price_source(self.price_source.borrow().clone());
+        for cash_equivalent in self.get_cash_equivalents() {
+            converter.add_cash_equivalent(&cash_equivalent);
+        }
+        for (currency, date, rate) in DatabaseScript::fetch_fx_rates(self.database.get_dbh(), reporting_currency.as_str()) {
+            converter.add_cached_rate(currency.as_str(), date, rate);
+        }
+        converter
+    }
+
+    fn save_converter(&self, converter: &CurrencyConverter) {
+        // Rates are only a cache, so failing to store one is not worth failing the read over.
+        for (currency, date, rate) in converter.get_new_rates() {
+            FxRate::new(
+                date.format("%Y-%m-%d").to_string(),
+                currency,
+                converter.get_reporting_currency().to_string(),
+                rate
+            ).write(self.database.get_dbh()).ok();
+        }
+    }
+
+    pub fn get_coin_record(&self, coin: String, as_of: Option<NaiveDateTime>) -> CoinRecord {
+        let converter = self.get_converter();
+        let record = DatabaseScript::fetch_coin_record(self.database.get_dbh(), coin, as_of, &converter, self.get_cost_basis_method());
+        self.save_converter(&converter);
+        record
+    }
+
+    pub fn get_lots(&self, coin: String, as_of: Option<NaiveDateTime>) -> Vec<TransactionRecord> {
+        self.get_coin_record(coin, as_of).get_lots().iter().map(|x| x.clone()).collect()
+    }
+
+    pub fn get_balances(&self, platform: Option<&str>, as_of: Option<NaiveDateTime>) -> HashMap<String, f64> {
+        let as_of = as_of.unwrap_or(chrono::Utc::now().naive_utc());
+        DatabaseScript::fetch_ledger(self.database.get_dbh(), Some(as_of), &self.get_converter()).get_balances(as_of, platform)
+    }
+
+    pub fn get_portfolio(&self, prices: &dyn PriceSource, as_of: Option<NaiveDateTime>) -> PortfolioRecord {
+        let date = as_of.map(|x| x.date()).unwrap_or(chrono::Utc::now().naive_utc().date());
+        let mut portfolio = PortfolioRecord::new();
+        let converter = self.get_converter();
+        for coin in DatabaseScript::fetch_coins(self.database.get_dbh(), as_of, &converter) {
+            let price = prices.get_price(coin.as_str(), date);
+            portfolio.add_coin(coin.to_string(), self.get_coin_record(coin, as_of), price);
+        }
+
+        let cash = DatabaseScript::fetch_cash_ledger(self.database.get_dbh(), as_of, &converter);
+        for platform in cash.get_platforms() {
+            for (currency, balance) in cash.get_balances(as_of.unwrap_or(date.and_hms_opt(23, 59, 59).unwrap()), Some(platform.as_str())) {
+                if balance.abs() < 1e-9 {
+                    continue;
+                }
+                let value = converter.convert(balance, currency.as_str(), date);
+                portfolio.add_cash(platform.to_string(), currency, balance, value);
+            }
+        }
+        self.save_converter(&converter);
+        portfolio.update();
+        portfolio
+    }
+
+    pub fn get_cash_ledger(&self, as_of: Option<NaiveDateTime>) -> CashLedger {
+        let converter = self.get_converter();
+        DatabaseScript::fetch_cash_ledger(self.database.get_dbh(), as_of, &converter)
+    }
+
+    pub fn rebuild_history(&self, prices: &dyn PriceSource) -> Result<(), CryptfolioError> {
+        let dbh = self.database.get_dbh();
+        let converter = self.get_converter();
+        let ledger = DatabaseScript::fetch_ledger(dbh, None, &converter);
+        let cash = DatabaseScript::fetch_cash_ledger(dbh, None, &converter);
+        let start = match ledger.get_start_date() {
+            Some(date) => { date },
+            None => { return DatabaseScript::clear_snapshots(dbh); }
+        };
+        let end = chrono::Utc::now().naive_utc().date();
+
+        // Portfolio-wide snapshots are stored with an empty platform, followed by each platform.
+        let mut platforms = vec![String::new()];
+        platforms.append(&mut ledger.get_platforms());
+        for platform in cash.get_platforms() {
+            if !platforms.contains(&platform) {
+                platforms.push(platform);
+            }
+        }
+
+        if let Err(e) = dbh.execute("BEGIN TRANSACTION") {
+            return Err(CryptfolioError::DatabaseWriteError(e.to_string()));
+        }
+
+        // The previous snapshots are only replaced once every new one has been written.
+        if let Err(e) = Self::write_snapshots(dbh, &ledger, &cash, platforms, start, end, prices, &converter) {
+            dbh.execute("ROLLBACK").ok();
+            return Err(e);
+        }
+        if let Err(e) = dbh.execute("COMMIT") {
+            dbh.execute("ROLLBACK").ok();
+            return Err(CryptfolioError::DatabaseWriteError(e.to_string()));
+        }
+        self.save_converter(&converter);
+        Ok(())
+    }
+
+    fn write_snapshots(dbh: &Connection, ledger: &Ledger, cash: &CashLedger, platforms: Vec<String>, start: NaiveDate, end: NaiveDate, prices: &dyn PriceSource, converter: &CurrencyConverter) -> Result<(), CryptfolioError> {
+        DatabaseScript::clear_snapshots(dbh)?;
+        for platform in platforms {
+            let filter = if platform.is_empty() { None } else { Some(platform.as_str()) };
+            for (date, balances) in ledger.get_daily_balances(start, end, filter) {
+                for (coin, shares) in balances {
+                    // Skip coins which are no longer held (allowing for floating point dust).
+                    if shares.abs() < 1e-12 {
+                        continue;
+                    }
+                    let price = prices.get_price(coin.as_str(), date).unwrap_or(0.0);
+                    CoinSnapshot::new(
+                        date.format("%Y-%m-%d").to_string(),
+                        coin,
+                        platform.to_string(),
+                        shares,
+                        price,
+                        shares * price,
+                        converter.get_reporting_currency().to_string()
+                    ).write(dbh)?;
+                }
+            }
+
+            // Uninvested cash is stored as a coin of its own, priced by the exchange rate.
+            for (date, balances) in cash.get_daily_balances(start, end, filter) {
+                for (currency, balance) in balances {
+                    if balance.abs() < 1e-9 {
+                        continue;
+                    }
+                    let rate = converter.get_rate(currency.as_str(), date).unwrap_or(0.0);
+                    CoinSnapshot::new(
+                        date.format("%Y-%m-%d").to_string(),
+                        currency,
+                        platform.to_string(),
+                        balance,
+                        rate,
+                        balance * rate,
+                        converter.get_reporting_currency().to_string()
+                    ).write(dbh)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Prices rewards which were synced without a price (e.g. Solana staking rewards) at the
+    /// price of their coin on the day, in the reporting currency. Returns how many were priced.
+    pub fn fill_reward_prices(&self, prices: &dyn PriceSource) -> Result<usize, CryptfolioError> {
+        let dbh = self.database.get_dbh();
+        let currency = self.get_reporting_currency();
+        let mut count = 0;
+        for (table, id, coin, date) in DatabaseScript::fetch_missing_prices(dbh) {
+            if table != "reward" {
+                continue;
+            }
+            let date = match Util::parse_date(date.as_str()) {
+                Ok(date) => { date.date() },
+                Err(_) => { continue; }
+            };
+            if let Some(price) = prices.get_price(coin.as_str(), date) {
+                DatabaseScript::update_reward_price(dbh, id.as_str(), price, currency.as_str())?;
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    pub fn get_coin_history(&self, coin: &str, start: NaiveDate, end: NaiveDate) -> HistoryRecord {
+        DatabaseScript::fetch_history(self.database.get_dbh(), Some(coin), None, start, end)
+    }
+
+    pub fn get_portfolio_history(&self, start: NaiveDate, end: NaiveDate) -> HistoryRecord {
+        DatabaseScript::fetch_history(self.database.get_dbh(), None, None, start, end)
+    }
+
+    pub fn get_portfolio_returns(&self, period: Period) -> ReturnRecord {
+        let (start, end) = self.get_period_range(&period);
+        let history = DatabaseScript::fetch_history(self.database.get_dbh(), None, None, start, end);
+        let converter = self.get_converter();
+        let flows = DatabaseScript::fetch_fiat_flows(self.database.get_dbh(), None, &converter);
+        self.save_converter(&converter);
+        ReturnRecord::new(&history.get_values(), &flows)
+    }
+
+    pub fn get_coin_returns(&self, coin: &str, period: Period) -> ReturnRecord {
+        let (start, end) = self.get_period_range(&period);
+        let history = DatabaseScript::fetch_history(self.database.get_dbh(), Some(coin), None, start, end);
+
+        // Fiat is not transferred to a particular coin, so its buys and sells are the cash flows.
+        let converter = self.get_converter();
+        let flows = DatabaseScript::fetch_ledger(self.database.get_dbh(), None, &converter).get_coin_flows(coin);
+        self.save_converter(&converter);
+        ReturnRecord::new(&history.get_values(), &flows)
+    }
+
+    pub fn get_platform_returns(&self, platform: &str, period: Period) -> ReturnRecord {
+        let (start, end) = self.get_period_range(&period);
+        let history = DatabaseScript::fetch_history(self.database.get_dbh(), None, Some(platform), start, end);
+        let converter = self.get_converter();
+        let mut flows = DatabaseScript::fetch_fiat_flows(self.database.get_dbh(), Some(platform), &converter);
+
+        // Coins moved in or out of the platform are flows as well, valued as the snapshots are.
+        let prices = DatabaseScript::fetch_snapshot_prices(self.database.get_dbh());
+        let ledger = DatabaseScript::fetch_ledger(self.database.get_dbh(), None, &converter);
+        flows.append(&mut ledger.get_transfer_flows(platform, &prices));
+        flows.sort_by_key(|x| x.0);
+        self.save_converter(&converter);
+        ReturnRecord::new(&history.get_values(), &flows)
+    }
+
+    pub fn get_allocation_targets(&self) -> Vec<(String, f64)> {
+        DatabaseScript::fetch_allocation_targets(self.database.get_dbh())
+    }
+
+    pub fn set_allocation_target(&self, target: &str, weight: f64) -> Result<(), CryptfolioError> {
+        AllocationTarget::new(target.to_string(), weight).write(self.database.get_dbh())
+    }
+
+    pub fn remove_allocation_target(&self, target: &str) -> Result<(), CryptfolioError> {
+        DatabaseScript::delete_allocation_target(self.database.get_dbh(), target)
+    }
+
+    pub fn set_coin_category(&self, coin: &str, category: &str) -> Result<(), CryptfolioError> {
+        CoinCategory::new(coin.to_uppercase(), category.to_string()).write(self.database.get_dbh())
+    }
+
+    pub fn get_rebalance_plan(&self, prices: &dyn PriceSource) -> RebalanceRecord {
+        let dbh = self.database.get_dbh();
+        RebalanceRecord::new(
+            &self.get_portfolio(prices, None),
+            &DatabaseScript::fetch_allocation_targets(dbh),
+            &DatabaseScript::fetch_coin_categories(dbh),
+            chrono::Utc::now().naive_utc().format("%Y-%m-%dT%H:%M:%SZ").to_string()
+        )
+    }
+
+    pub fn get_recurring_buys(&self, prices: &dyn PriceSource) -> RecurringBuyRecord {
+        let converter = self.get_converter();
+        let ledger = DatabaseScript::fetch_ledger(self.database.get_dbh(), None, &converter);
+        self.save_converter(&converter);
+        RecurringBuyRecord::new(ledger.get_entries(), prices)
+    }
+
+    pub fn get_benchmark(&self, benchmark: &str, prices: &dyn PriceSource, period: Period) -> BenchmarkRecord {
+        let (start, end) = self.get_period_range(&period);
+        let history = DatabaseScript::fetch_history(self.database.get_dbh(), None, None, start, end);
+        let converter = self.get_converter();
+        let flows = DatabaseScript::fetch_fiat_flows(self.database.get_dbh(), None, &converter);
+        self.save_converter(&converter);
+        BenchmarkRecord::new(benchmark, &history.get_values(), &flows, prices)
+    }
+
+    pub fn get_risk(&self, period: Period, risk_free_rate: f64) -> RiskRecord {
+        let dbh = self.database.get_dbh();
+        let (start, end) = self.get_period_range(&period);
+        let history = DatabaseScript::fetch_history(dbh, None, None, start, end);
+        let converter = self.get_converter();
+        let flows = DatabaseScript::fetch_fiat_flows(dbh, None, &converter);
+        let mut risk = RiskRecord::new(&history.get_values(), &flows, risk_free_rate);
+        for coin in DatabaseScript::fetch_coins(dbh, None, &converter) {
+            let prices = DatabaseScript::fetch_history(dbh, Some(coin.as_str()), None, start, end).get_prices();
+            if prices.len() > 1 {
+                risk.add_coin(coin, &prices);
+            }
+        }
+        self.save_converter(&converter);
+        risk
+    }
+
+    fn get_period_range(&self, period: &Period) -> (NaiveDate, NaiveDate) {
+        let today = chrono::Utc::now().naive_utc().date();
+        let inception = DatabaseScript::fetch_ledger(self.database.get_dbh(), None, &self.get_converter())
+            .get_start_date()
+            .unwrap_or(today);
+        period.to_range(inception, today)
+    }
+
+    pub fn get_reconciliation(&self, tolerance: f64) -> ReconciliationRecord {
+        let dbh = self.database.get_dbh();
+        let converter = self.get_converter();
+        let ledger = DatabaseScript::fetch_ledger(dbh, None, &converter);
+        let mut reconciliation = ReconciliationRecord::new(tolerance);
+        let cash = DatabaseScript::fetch_cash_ledger(dbh, None, &converter);
+        for (platform, coin, balance, date) in DatabaseScript::fetch_reported_balances(dbh) {
+            let as_of = match Util::parse_date(date.as_str()) {
+                Ok(as_of) => { as_of },
+                Err(_) => { chrono::Utc::now().naive_utc() }
+            };
+
+            // Fiat balances are not a part of the coin ledger, but of the cash ledger.
+            let ledger_balance = if converter.is_cash(coin.as_str()) {
+                cash.get_balances(as_of, Some(platform.as_str()))
+            } else {
+                ledger.get_balances(as_of, Some(platform.as_str()))
+            };
+            let ledger_balance = ledger_balance
+                .get(&coin)
+                .map(|x| *x)
+                .unwrap_or(0.0);
+            reconciliation.add_balance(platform, coin, date, balance, ledger_balance);
+        }
+        reconciliation
+    }
+
+    pub fn get_contributions(&self) -> ContributionRecord {
+        let dbh = self.database.get_dbh();
+        let converter = self.get_converter();
+        let mut contributions = ContributionRecord::new(DatabaseScript::fetch_fiat_transfers(dbh, &converter));
+        let today = chrono::Utc::now().naive_utc().date();
+        for (platform, currency, balance, _date) in DatabaseScript::fetch_reported_balances(dbh) {
+            if !converter.is_cash(currency.as_str()) {
+                continue;
+            }
+            let value = converter.convert(balance, currency.as_str(), today);
+            contributions.add_cash_balance(platform, currency, balance, value);
+        }
+        self.save_converter(&converter);
+        contributions
+    }
+
+    pub fn match_transfers(&self, matcher: &TransferMatcher) -> Result<Vec<TransferMatch>, CryptfolioError> {
+        let dbh = self.database.get_dbh();
+        let transfers = DatabaseScript::fetch_transfers(dbh);
+        let linked = DatabaseScript::fetch_linked_transfers(dbh);
+        let (matches, _unmatched) = matcher.match_transfers(&transfers, &linked);
+        for transfer in &matches {
+            TransferLink::new(transfer.outgoing.id.to_string(), transfer.incoming.id.to_string()).write(dbh)?;
+        }
+        Ok(matches)
+    }
+
+    pub fn link_transfers(&self, outgoing: &str, incoming: &str) -> Result<(), CryptfolioError> {
+        TransferLink::new(outgoing.to_string(), incoming.to_string()).write(self.database.get_dbh())
+    }
+
+    pub fn get_transfer_review_queue(&self) -> Vec<TransferRecord> {
+        let dbh = self.database.get_dbh();
+        let linked = DatabaseScript::fetch_linked_transfers(dbh);
+        DatabaseScript::fetch_transfers(dbh)
+            .into_iter()
+            .filter(|x| !linked.contains(&x.id))
+            .collect()
+    }
+
+    pub fn get_diagnostics(&self) -> DiagnosticRecord {
+        let dbh = self.database.get_dbh();
+        let mut diagnostics = DiagnosticRecord::new();
+
+        // Negative balances, reported once per coin at the first date the balance dips below zero.
+        let converter = self.get_converter();
+        let ledger = DatabaseScript::fetch_ledger(dbh, None, &converter);
+        let mut balances = HashMap::<String, f64>::new();
+        for entry in ledger.get_entries() {
+            let balance = balances.entry(entry.coin.to_string()).or_insert(0.0);
+            let was_negative = *balance < -1e-9;
+            *balance += entry.get_share_change(None);
+            if *balance < -1e-9 && !was_negative {
+                diagnostics.add_diagnostic(
+                    DiagnosticType::NegativeBalance,
+                    entry.id.to_string(),
+                    entry.coin.to_string(),
+                    entry.date.to_string(),
+                    format!("Balance of {} falls to {}", entry.coin, balance)
+                );
+            }
+        }
+
+        for coin in DatabaseScript::fetch_coins(dbh, None, &converter) {
+            for sell in self.get_coin_record(coin.to_string(), None).get_unmatched_sells() {
+                diagnostics.add_diagnostic(
+                    DiagnosticType::SellWithoutBasis,
+                    String::new(),
+                    coin.to_string(),
+                    sell.date.to_string(),
+                    format!("{} {} sold without any remaining buys", sell.shares, coin)
+                );
+            }
+        }
+
+        // Amounts in a currency (or coin) which could not be converted to the reporting currency.
+        for (currency, date) in converter.get_missing_rates() {
+            diagnostics.add_diagnostic(
+                DiagnosticType::MissingPrice,
+                String::new(),
+                currency.to_string(),
+                date.to_string(),
+                format!("No rate from {} to {} on {}", currency, converter.get_reporting_currency(), date)
+            );
+        }
+
+        for (table, id, coin, date) in DatabaseScript::fetch_missing_prices(dbh) {
+            diagnostics.add_diagnostic(
+                DiagnosticType::MissingPrice,
+                id,
+                coin,
+                date,
+                format!("The {} has a price of NaN or zero", table)
+            );
+        }
+
+        for (id, coin, date) in DatabaseScript::fetch_missing_fees(dbh) {
+            diagnostics.add_diagnostic(
+                DiagnosticType::MissingFee,
+                id,
+                coin,
+                date,
+                "The transfer has a fee of NaN".to_string()
+            );
+        }
+
+        // Transfers between two known accounts are complete on their own.
+        for transfer in self.get_transfer_review_queue() {
+            if transfer.is_outgoing() && transfer.is_incoming() {
+                continue;
+            }
+            diagnostics.add_diagnostic(
+                DiagnosticType::OrphanTransfer,
+                transfer.id.to_string(),
+                transfer.coin.to_string(),
+                transfer.date.to_string(),
+                format!("{} {} from '{}' to '{}' has no matching transfer", transfer.unit_size, transfer.coin, transfer.origin, transfer.destination)
+            );
+        }
+
+        for (id, date, coin, r#type, platform) in DatabaseScript::fetch_unknown_transactions(dbh) {
+            diagnostics.add_diagnostic(
+                DiagnosticType::UnknownTransaction,
+                id,
+                coin,
+                date,
+                format!("{} transaction of type '{}' could not be processed", platform, r#type)
+            );
+        }
+
+        for (id, table, count) in DatabaseScript::fetch_duplicate_ids(dbh) {
+            diagnostics.add_diagnostic(
+                DiagnosticType::DuplicateId,
+                id,
+                String::new(),
+                String::new(),
+                format!("The id occurs {} times in {}", count, table)
+            );
+        }
+
+        diagnostics
+    }
+
+    /// The nickname and platform of every connection which has to be added again with new keys
+    /// (e.g. Coinbase Pro connections, to be added as Coinbase Advanced).
+    pub fn get_connections_needing_keys(&self) -> Vec<(String, String)> {
+        DatabaseScript::fetch_connections(self.database.get_dbh())
+            .into_iter()
+            .filter(|x| x.needs_new_keys())
+            .map(|x| (x.nickname, x.platform))
+            .collect()
     }
 
     pub fn get_connections(&self) -> Vec<(String, Rc<Box<dyn SyncClient>>)> {
@@ -0,0 +1,54 @@
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// account_balance.rs
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Description:
+///     Database entry for the balance of an account as reported by its platform during a sync.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Usage:
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Notes:
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+
+use sqlite3::Connection;
+use crate::database::entry::DatabaseEntry;
+use crate::error::CryptfolioError;
+
+#[derive(Default)]
+pub struct AccountBalance {
+    account: String,
+    date: String,
+    coin: String,
+    platform: String,
+    balance: f64,
+}
+
+impl AccountBalance {
+    pub fn new(account: String, date: String, coin: String, platform: String, balance: f64) -> AccountBalance {
+        AccountBalance {
+            account: account, date: date, coin: coin, platform: platform, balance: balance
+        }
+    }
+}
+
+impl DatabaseEntry for AccountBalance {
+    fn write(&self, dbh: &Connection) -> Result<(), CryptfolioError> {
+        let mut statement = dbh.prepare(
+            "INSERT INTO balances (account, date, coin, platform, balance) VALUES (?, ?, ?, ?, ?)"
+        ).unwrap();
+        statement.bind(1, self.account.as_str()).unwrap();
+        statement.bind(2, self.date.as_str()).unwrap();
+        statement.bind(3, self.coin.as_str()).unwrap();
+        statement.bind(4, self.platform.as_str()).unwrap();
+        statement.bind(5, self.balance).unwrap();
+        statement.next().unwrap();
+        Ok(())
+    }
+}
\ No newline at end of file
@@ -0,0 +1,50 @@
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// allocation_target.rs
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Description:
+///     Database entry for the target weight of a coin or category within the portfolio.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Usage:
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Notes:
+///     The weight is a percentage of the total portfolio value. Writing a target replaces any
+///     existing weight for the same coin or category.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+
+use sqlite3::Connection;
+use crate::database::entry::DatabaseEntry;
+use crate::error::CryptfolioError;
+
+#[derive(Default)]
+pub struct AllocationTarget {
+    target: String,
+    weight: f64,
+}
+
+impl AllocationTarget {
+    pub fn new(target: String, weight: f64) -> AllocationTarget {
+        AllocationTarget {
+            target: target, weight: weight
+        }
+    }
+}
+
+impl DatabaseEntry for AllocationTarget {
+    fn write(&self, dbh: &Connection) -> Result<(), CryptfolioError> {
+        let mut statement = dbh.prepare(
+            "INSERT OR REPLACE INTO allocation_targets (target, weight) VALUES (?, ?)"
+        ).unwrap();
+        statement.bind(1, self.target.as_str()).unwrap();
+        statement.bind(2, self.weight).unwrap();
+        statement.next().unwrap();
+        Ok(())
+    }
+}
\ No newline at end of file
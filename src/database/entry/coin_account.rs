@@ -39,7 +39,7 @@ impl CoinAccount {
 impl DatabaseEntry for CoinAccount {
     fn write(&self, dbh: &Connection) -> Result<(), CryptfolioError> {
         let mut statement = dbh.prepare(
-            "INSERT INTO accounts (id, coin, platform) VALUES(?, ?, ?)"
+            "INSERT OR IGNORE INTO accounts (id, coin, platform) VALUES(?, ?, ?)"
         ).unwrap();
         statement.bind(1, self.id.as_str()).unwrap();
         statement.bind(2, self.coin.as_str()).unwrap();
@@ -0,0 +1,49 @@
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// coin_category.rs
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Description:
+///     Database entry for the category (e.g. 'Layer 1', 'DeFi') a coin belongs to.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Usage:
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Notes:
+///     A coin belongs to a single category. Writing a category replaces any existing one.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+
+use sqlite3::Connection;
+use crate::database::entry::DatabaseEntry;
+use crate::error::CryptfolioError;
+
+#[derive(Default)]
+pub struct CoinCategory {
+    coin: String,
+    category: String,
+}
+
+impl CoinCategory {
+    pub fn new(coin: String, category: String) -> CoinCategory {
+        CoinCategory {
+            coin: coin, category: category
+        }
+    }
+}
+
+impl DatabaseEntry for CoinCategory {
+    fn write(&self, dbh: &Connection) -> Result<(), CryptfolioError> {
+        let mut statement = dbh.prepare(
+            "INSERT OR REPLACE INTO coin_categories (coin, category) VALUES (?, ?)"
+        ).unwrap();
+        statement.bind(1, self.coin.as_str()).unwrap();
+        statement.bind(2, self.category.as_str()).unwrap();
+        statement.next().unwrap();
+        Ok(())
+    }
+}
\ No newline at end of file
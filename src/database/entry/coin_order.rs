@@ -20,6 +20,7 @@ use sqlite3::Connection;
 
 use crate::database::entry::DatabaseEntry;
 use crate::error::CryptfolioError;
+use crate::util::Util;
 
 #[derive(Default)]
 pub struct CoinOrder {
@@ -31,21 +32,35 @@ pub struct CoinOrder {
     fee: f64,
     side: String,
     platform: String,
+    description: String,
+    order_id: String,
 }
 
 impl CoinOrder {
     pub fn new(id: String, date: String, pair: String, unit_price: f64, unit_size: f64, fee: f64, side: String, platform: String) -> CoinOrder {
         CoinOrder {
-            id: id, date: date, pair: pair, unit_price: unit_price, unit_size: unit_size, fee: fee, side: side, platform: platform
+            id: id, date: date, pair: pair, unit_price: unit_price, unit_size: unit_size, fee: fee, side: side, platform: platform,
+            description: String::new(),
+            order_id: String::new()
         }
     }
+
+    /// Describes where an order came from when the pair alone does not (e.g. the DEX of a swap).
+    pub fn set_description(&mut self, description: String) {
+        self.description = description;
+    }
+
+    /// The exchange order a fill belongs to, when the id of the order is not the id of the fill.
+    pub fn set_order_id(&mut self, order_id: String) {
+        self.order_id = order_id;
+    }
 }
 
 impl DatabaseEntry for CoinOrder {
     fn write(&self, dbh: &Connection) -> Result<(), CryptfolioError> {
         let mut statement = dbh
             .prepare(
-            "INSERT INTO orders (id, date, pair, unit_price, unit_size, fee, side, platform) VALUES (?, ?, ?, ?, ?, ?, ?, ?)"
+            "INSERT INTO orders (id, date, pair, unit_price, unit_size, fee, side, platform, currency, description, order_id) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
         ).unwrap();
         statement.bind(1, self.id.as_str()).unwrap();
         statement.bind(2, self.date.as_str()).unwrap();
@@ -55,6 +70,10 @@ impl DatabaseEntry for CoinOrder {
         statement.bind(6, self.fee).unwrap();
         statement.bind(7, self.side.as_str()).unwrap();
         statement.bind(8, self.platform.as_str()).unwrap();
+        // Price and fee are always denominated in the quote currency of the pair.
+        statement.bind(9, Util::split_pair(self.pair.as_str()).1.as_str()).unwrap();
+        statement.bind(10, self.description.as_str()).unwrap();
+        statement.bind(11, self.order_id.as_str()).unwrap();
         statement.next().unwrap();
         Ok(())
     }
@@ -30,12 +30,14 @@ pub struct CoinReward {
     unit_size: f64,
     r#type: String,
     description: String,
+    platform: String,
+    currency: String,
 }
 
 impl CoinReward {
-    pub fn new(id: String, date: String, coin: String, unit_price: f64, unit_size: f64, r#type: String, description: String) -> CoinReward {
+    pub fn new(id: String, date: String, coin: String, unit_price: f64, unit_size: f64, r#type: String, description: String, platform: String, currency: String) -> CoinReward {
         CoinReward {
-             id: id, date: date, coin: coin, unit_price: unit_price, unit_size: unit_size, r#type: r#type, description: description 
+             id: id, date: date, coin: coin, unit_price: unit_price, unit_size: unit_size, r#type: r#type, description: description, platform: platform, currency: currency
         }
     }
 }
@@ -43,7 +45,7 @@ impl CoinReward {
 impl DatabaseEntry for CoinReward {
     fn write(&self, dbh: &Connection) -> Result<(), CryptfolioError> {
         let mut statement = dbh.prepare(
-            "INSERT INTO rewards (id, date, coin, unit_price, unit_size, type, description) VALUES (?, ?, ?, ?, ?, ?, ?)"
+            "INSERT INTO rewards (id, date, coin, unit_price, unit_size, type, description, platform, currency) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)"
         ).unwrap();
         statement.bind(1, self.id.as_str()).unwrap();
         statement.bind(2, self.date.as_str()).unwrap();
@@ -52,6 +54,8 @@ impl DatabaseEntry for CoinReward {
         statement.bind(5, self.unit_size).unwrap();
         statement.bind(6, self.r#type.as_str()).unwrap();
         statement.bind(7, self.description.as_str()).unwrap();
+        statement.bind(8, self.platform.as_str()).unwrap();
+        statement.bind(9, self.currency.as_str()).unwrap();
         statement.next().unwrap();
         Ok(())
     }
@@ -0,0 +1,59 @@
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// coin_snapshot.rs
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Description:
+///     Database entry for the holdings and value of a coin at the end of a single day.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Usage:
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Notes:
+///     An empty platform denotes the portfolio-wide snapshot of the coin.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+
+use sqlite3::Connection;
+use crate::database::entry::DatabaseEntry;
+use crate::error::CryptfolioError;
+
+#[derive(Default)]
+pub struct CoinSnapshot {
+    date: String,
+    coin: String,
+    platform: String,
+    shares: f64,
+    unit_price: f64,
+    value: f64,
+    currency: String,
+}
+
+impl CoinSnapshot {
+    pub fn new(date: String, coin: String, platform: String, shares: f64, unit_price: f64, value: f64, currency: String) -> CoinSnapshot {
+        CoinSnapshot {
+            date: date, coin: coin, platform: platform, shares: shares, unit_price: unit_price, value: value, currency: currency
+        }
+    }
+}
+
+impl DatabaseEntry for CoinSnapshot {
+    fn write(&self, dbh: &Connection) -> Result<(), CryptfolioError> {
+        let mut statement = dbh.prepare(
+            "INSERT OR REPLACE INTO snapshots (date, coin, platform, shares, unit_price, value, currency) VALUES (?, ?, ?, ?, ?, ?, ?)"
+        ).unwrap();
+        statement.bind(1, self.date.as_str()).unwrap();
+        statement.bind(2, self.coin.as_str()).unwrap();
+        statement.bind(3, self.platform.as_str()).unwrap();
+        statement.bind(4, self.shares).unwrap();
+        statement.bind(5, self.unit_price).unwrap();
+        statement.bind(6, self.value).unwrap();
+        statement.bind(7, self.currency.as_str()).unwrap();
+        statement.next().unwrap();
+        Ok(())
+    }
+}
\ No newline at end of file
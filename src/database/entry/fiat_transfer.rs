@@ -28,12 +28,13 @@ pub struct FiatTransfer {
     origin: String,
     destination: String,
     amount: f64,
+    currency: String,
 }
 
 impl FiatTransfer {
-    pub fn new(id: String, date: String, origin: String, destination: String, amount: f64) -> FiatTransfer {
+    pub fn new(id: String, date: String, origin: String, destination: String, amount: f64, currency: String) -> FiatTransfer {
         FiatTransfer { 
-            id: id, date: date, origin: origin, destination: destination, amount: amount 
+            id: id, date: date, origin: origin, destination: destination, amount: amount, currency: currency
         }
     }
 }
@@ -41,13 +42,14 @@ impl FiatTransfer {
 impl DatabaseEntry for FiatTransfer {
     fn write(&self, dbh: &Connection) -> Result<(), CryptfolioError> {
         let mut statement = dbh.prepare(
-            "INSERT INTO fiat_transfers (id, date, origin, destination, amount) VALUES(?, ?, ?, ?, ?)"
+            "INSERT INTO fiat_transfers (id, date, origin, destination, amount, currency) VALUES(?, ?, ?, ?, ?, ?)"
         ).unwrap();
         statement.bind(1, self.id.as_str()).unwrap();
         statement.bind(2, self.date.as_str()).unwrap();
         statement.bind(3, self.origin.as_str()).unwrap();
         statement.bind(4, self.destination.as_str()).unwrap();
         statement.bind(5, self.amount).unwrap();
+        statement.bind(6, self.currency.as_str()).unwrap();
         statement.next().unwrap();
         Ok(())
     }
@@ -0,0 +1,52 @@
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// fx_rate.rs
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Description:
+///     Database entry for a cached exchange rate between two fiat currencies on a single day.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Usage:
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Notes:
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+
+use sqlite3::Connection;
+use crate::database::entry::DatabaseEntry;
+use crate::error::CryptfolioError;
+
+#[derive(Default)]
+pub struct FxRate {
+    date: String,
+    base: String,
+    quote: String,
+    rate: f64,
+}
+
+impl FxRate {
+    pub fn new(date: String, base: String, quote: String, rate: f64) -> FxRate {
+        FxRate {
+            date: date, base: base, quote: quote, rate: rate
+        }
+    }
+}
+
+impl DatabaseEntry for FxRate {
+    fn write(&self, dbh: &Connection) -> Result<(), CryptfolioError> {
+        let mut statement = dbh.prepare(
+            "INSERT OR REPLACE INTO fx_rates (date, base, quote, rate) VALUES (?, ?, ?, ?)"
+        ).unwrap();
+        statement.bind(1, self.date.as_str()).unwrap();
+        statement.bind(2, self.base.as_str()).unwrap();
+        statement.bind(3, self.quote.as_str()).unwrap();
+        statement.bind(4, self.rate).unwrap();
+        statement.next().unwrap();
+        Ok(())
+    }
+}
\ No newline at end of file
@@ -1,16 +1,32 @@
+pub mod account_balance;
+pub mod allocation_target;
 pub mod coin_account;
+pub mod coin_category;
 pub mod coin_order;
 pub mod coin_reward;
+pub mod coin_snapshot;
 pub mod coin_transfer;
 pub mod fiat_transfer;
+pub mod fx_rate;
 pub mod platform_connection;
+pub mod setting;
+pub mod transfer_link;
+pub mod unknown_transaction;
+pub use account_balance::AccountBalance;
+pub use allocation_target::AllocationTarget;
 pub use coin_account::CoinAccount;
+pub use coin_category::CoinCategory;
 pub use coin_order::CoinOrder;
 pub use coin_reward::CoinReward;
+pub use coin_snapshot::CoinSnapshot;
 pub use coin_transfer::CoinTransfer;
 pub use fiat_transfer::FiatTransfer;
+pub use fx_rate::FxRate;
 pub use platform_connection::PlatformConnection;
 pub use platform_connection::PlatformConnectionData;
+pub use setting::Setting;
+pub use transfer_link::TransferLink;
+pub use unknown_transaction::UnknownTransaction;
 
 use sqlite3::Connection;
 use crate::error::CryptfolioError;
@@ -17,15 +17,20 @@
 /// 
 /// ///////////////////////////////////////////////////////////////////////////////////////////////
 
+use chrono::NaiveDate;
 use serde::{Serialize, Deserialize};
 use sqlite3::Connection;
 use std::rc::Rc;
 use crate::database::entry::DatabaseEntry;
 use crate::error::CryptfolioError;
 use crate::platform::SyncClient;
-use crate::platform::blockchain::Solana;
+use crate::platform::blockchain::{Bitcoin, BitcoinBackend, Evm, Solana};
+use crate::platform::exchange::{Binance, BinanceRegion};
 use crate::platform::exchange::Coinbase;
+use crate::platform::exchange::CoinbaseAdvanced;
 use crate::platform::exchange::CoinbasePro;
+use crate::platform::exchange::Kraken;
+use crate::util::Util;
 
 #[derive(Serialize, Deserialize)]
 pub struct PlatformConnectionData {
@@ -55,6 +60,22 @@ impl PlatformConnection {
         }
     }
 
+    pub fn get_data(&self, key: &str) -> Option<&str> {
+        self.connection_data.iter().find(|x| x.key == key).map(|x| x.value.as_str())
+    }
+
+    /// Whether the keys of the connection no longer authenticate (e.g. Coinbase Pro, whose API
+    /// has been shut down), so that it has to be added again with new keys before syncing.
+    pub fn needs_new_keys(&self) -> bool {
+        self.get_data("Needs New Keys") == Some("true")
+    }
+
+    pub fn set_needs_new_keys(&mut self) {
+        if !self.needs_new_keys() {
+            self.connection_data.push(PlatformConnectionData::new("Needs New Keys".to_string(), "true".to_string()));
+        }
+    }
+
     pub fn to_concrete_type(&self) -> Rc<Box<dyn SyncClient>> {
         match self.platform.as_str() {
             "Coinbase" => {
@@ -63,6 +84,12 @@ impl PlatformConnection {
                     &self.connection_data[1].value
                 ).unwrap()));
             },
+            "Coinbase Advanced" => {
+                return Rc::new(Box::new(CoinbaseAdvanced::new(
+                    &self.connection_data[0].value,
+                    &self.connection_data[1].value
+                ).unwrap()));
+            },
             "Coinbase Pro" => {
                 return Rc::new(Box::new(CoinbasePro::new(
                     &self.connection_data[0].value,
@@ -70,11 +97,68 @@ impl PlatformConnection {
                     &self.connection_data[2].value
                 ).unwrap()));
             },
-            "Solana" => {
-                return Rc::new(Box::new(Solana::new(
-                    self.connection_data[0].value.to_string()
+            "Binance" | "Binance.US" => {
+                let region = if self.platform == "Binance.US" { BinanceRegion::US } else { BinanceRegion::Global };
+                let mut binance = Binance::new(&self.connection_data[0].value, &self.connection_data[1].value, region).unwrap();
+                if let Some(date) = self.get_data("Start Date").and_then(|x| NaiveDate::parse_from_str(x, "%Y-%m-%d").ok()) {
+                    binance.set_start_date(date);
+                }
+                for data in self.connection_data.iter().skip(2) {
+                    if data.key.starts_with("Pair:") {
+                        let (base, quote) = Util::split_pair(data.value.as_str());
+                        binance.add_pair(base.as_str(), quote.as_str());
+                    }
+                }
+                return Rc::new(Box::new(binance));
+            },
+            "Kraken" => {
+                return Rc::new(Box::new(Kraken::new(
+                    &self.connection_data[0].value,
+                    &self.connection_data[1].value
                 ).unwrap()));
             },
+            "Solana" => {
+                // Connections saved before the RPC settings were configurable use the defaults.
+                let mut solana = Solana::with_config(
+                    self.connection_data[0].value.to_string(),
+                    self.get_data("RPC URL").unwrap_or("https://api.mainnet-beta.solana.com").to_string(),
+                    self.get_data("Commitment").unwrap_or("finalized"),
+                    self.get_data("Page Size").and_then(|x| x.parse::<usize>().ok()).unwrap_or(1000)
+                ).unwrap();
+                solana.set_until(self.get_data("Until Signature").map(|x| x.to_string()));
+                for data in self.connection_data.iter().skip(1) {
+                    if let Some(mint) = data.key.strip_prefix("Token:") {
+                        solana.add_token(mint, data.value.as_str());
+                    }
+                    if let Some(address) = data.key.strip_prefix("Stake Account:") {
+                        let epochs: Vec<u64> = data.value.split(':').filter_map(|x| x.parse::<u64>().ok()).collect();
+                        if let [activation_epoch, deactivation_epoch] = epochs.as_slice() {
+                            solana.add_stake_account(address, *activation_epoch, *deactivation_epoch);
+                        }
+                    }
+                }
+                solana.set_reward_epoch(self.get_data("Reward Epoch").and_then(|x| x.parse::<u64>().ok()).unwrap_or(0));
+                return Rc::new(Box::new(solana));
+            },
+            "Ethereum" | "Arbitrum" | "Base" | "Polygon" | "Anvil" => {
+                let mut evm = Evm::new(
+                    &self.connection_data[0].value,
+                    self.get_data("Chain ID").and_then(|x| x.parse::<u64>().ok()).unwrap_or(1),
+                    self.get_data("RPC URL").unwrap_or("")
+                ).unwrap();
+                evm.set_indexer(self.get_data("Indexer URL").unwrap_or(""), self.get_data("Indexer API Key").unwrap_or(""));
+                evm.set_start_block(self.get_data("Start Block").and_then(|x| x.parse::<u64>().ok()).unwrap_or(0));
+                return Rc::new(Box::new(evm));
+            },
+            "Bitcoin" => {
+                let bitcoin = Bitcoin::new(
+                    &self.connection_data[0].value,
+                    self.get_data("Backend").and_then(|x| x.parse::<BitcoinBackend>().ok()).unwrap_or(BitcoinBackend::Esplora),
+                    self.get_data("Endpoint").unwrap_or("https://blockstream.info/api"),
+                    self.get_data("Gap Limit").and_then(|x| x.parse::<u32>().ok()).unwrap_or(20)
+                ).unwrap();
+                return Rc::new(Box::new(bitcoin));
+            },
             _ => {
                 panic!("Could not convert PlatformConnection to concrete type.");
             }
@@ -0,0 +1,49 @@
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// setting.rs
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Description:
+///     Database entry for a single portfolio-wide setting (e.g. reporting currency).
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Usage:
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Notes:
+///     Writing a setting replaces any existing value for the same key.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+
+use sqlite3::Connection;
+use crate::database::entry::DatabaseEntry;
+use crate::error::CryptfolioError;
+
+#[derive(Default)]
+pub struct Setting {
+    key: String,
+    value: String,
+}
+
+impl Setting {
+    pub fn new(key: String, value: String) -> Setting {
+        Setting {
+            key: key, value: value
+        }
+    }
+}
+
+impl DatabaseEntry for Setting {
+    fn write(&self, dbh: &Connection) -> Result<(), CryptfolioError> {
+        let mut statement = dbh.prepare(
+            "INSERT OR REPLACE INTO settings (key, value) VALUES (?, ?)"
+        ).unwrap();
+        statement.bind(1, self.key.as_str()).unwrap();
+        statement.bind(2, self.value.as_str()).unwrap();
+        statement.next().unwrap();
+        Ok(())
+    }
+}
\ No newline at end of file
@@ -0,0 +1,50 @@
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// transfer_link.rs
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Description:
+///     Database entry linking the outgoing and incoming side of a single coin transfer.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Usage:
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Notes:
+///     The incoming side of a link is ignored when replaying the ledger, as the movement is
+///     already represented by the outgoing side.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+
+use sqlite3::Connection;
+use crate::database::entry::DatabaseEntry;
+use crate::error::CryptfolioError;
+
+#[derive(Default)]
+pub struct TransferLink {
+    outgoing: String,
+    incoming: String,
+}
+
+impl TransferLink {
+    pub fn new(outgoing: String, incoming: String) -> TransferLink {
+        TransferLink {
+            outgoing: outgoing, incoming: incoming
+        }
+    }
+}
+
+impl DatabaseEntry for TransferLink {
+    fn write(&self, dbh: &Connection) -> Result<(), CryptfolioError> {
+        let mut statement = dbh.prepare(
+            "INSERT OR IGNORE INTO transfer_links (outgoing, incoming) VALUES (?, ?)"
+        ).unwrap();
+        statement.bind(1, self.outgoing.as_str()).unwrap();
+        statement.bind(2, self.incoming.as_str()).unwrap();
+        statement.next().unwrap();
+        Ok(())
+    }
+}
\ No newline at end of file
@@ -0,0 +1,56 @@
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// unknown_transaction.rs
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Description:
+///     Database entry for a synced transaction whose type could not be processed.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Usage:
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Notes:
+///     Storing these, rather than failing the sync, lets the rest of a platform's history be
+///     imported while the transaction is surfaced through diagnostics.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+
+use sqlite3::Connection;
+use crate::database::entry::DatabaseEntry;
+use crate::error::CryptfolioError;
+
+#[derive(Default)]
+pub struct UnknownTransaction {
+    id: String,
+    date: String,
+    coin: String,
+    r#type: String,
+    platform: String,
+}
+
+impl UnknownTransaction {
+    pub fn new(id: String, date: String, coin: String, r#type: String, platform: String) -> UnknownTransaction {
+        UnknownTransaction {
+            id: id, date: date, coin: coin, r#type: r#type, platform: platform
+        }
+    }
+}
+
+impl DatabaseEntry for UnknownTransaction {
+    fn write(&self, dbh: &Connection) -> Result<(), CryptfolioError> {
+        let mut statement = dbh.prepare(
+            "INSERT INTO unknown_transactions (id, date, coin, type, platform) VALUES (?, ?, ?, ?, ?)"
+        ).unwrap();
+        statement.bind(1, self.id.as_str()).unwrap();
+        statement.bind(2, self.date.as_str()).unwrap();
+        statement.bind(3, self.coin.as_str()).unwrap();
+        statement.bind(4, self.r#type.as_str()).unwrap();
+        statement.bind(5, self.platform.as_str()).unwrap();
+        statement.next().unwrap();
+        Ok(())
+    }
+}
\ No newline at end of file
@@ -1,5 +1,5 @@
 pub mod entry;
-pub mod script;
+pub(crate) mod script;
 
 use sqlite3::Connection;
 use crate::error::CryptfolioError;
@@ -1,6 +1,14 @@
 use crate::error::CryptfolioError;
 use crate::database::entry::PlatformConnection;
-use crate::recording::CoinRecord;
+use crate::pricing::CurrencyConverter;
+use crate::recording::{
+    CashLedger, CashLedgerEntry, CashLedgerEntryType, CoinRecord, CostBasisMethod, FiatTransferRecord, HistoryRecord,
+    Ledger, LedgerEntry, LedgerEntryType, TransferRecord,
+};
+use crate::util::Util;
+
+use chrono::{NaiveDate, NaiveDateTime};
+use std::collections::HashMap;
 
 use sqlite3::Connection;
 use sqlite3::State;
@@ -29,7 +37,8 @@ impl DatabaseScript {
                 date TEXT,
                 origin TEXT,
                 destination TEXT,
-                amount REAL
+                amount REAL,
+                currency TEXT
             );
 
             CREATE TABLE IF NOT EXISTS orders (
@@ -40,7 +49,10 @@ impl DatabaseScript {
                 unit_size REAL,
                 fee REAL,
                 side TEXT,
-                platform TEXT
+                platform TEXT,
+                currency TEXT,
+                description TEXT,
+                order_id TEXT
             );
             
             CREATE TABLE IF NOT EXISTS rewards (
@@ -50,7 +62,9 @@ impl DatabaseScript {
                 unit_price REAL,
                 unit_size REAL,
                 type TEXT,
-                description TEXT
+                description TEXT,
+                platform TEXT,
+                currency TEXT
             );
             
             CREATE TABLE IF NOT EXISTS transfers (
@@ -62,11 +76,118 @@ impl DatabaseScript {
                 unit_size REAL,
                 fee REAL
             );
+
+            CREATE TABLE IF NOT EXISTS snapshots (
+                date TEXT,
+                coin TEXT,
+                platform TEXT,
+                shares REAL,
+                unit_price REAL,
+                value REAL,
+                currency TEXT,
+                UNIQUE(date, coin, platform)
+            );
+
+            CREATE TABLE IF NOT EXISTS balances (
+                account TEXT,
+                date TEXT,
+                coin TEXT,
+                platform TEXT,
+                balance REAL
+            );
+
+            CREATE TABLE IF NOT EXISTS transfer_links (
+                outgoing TEXT UNIQUE,
+                incoming TEXT UNIQUE
+            );
+
+            CREATE TABLE IF NOT EXISTS settings (
+                key TEXT UNIQUE,
+                value TEXT
+            );
+
+            CREATE TABLE IF NOT EXISTS fx_rates (
+                date TEXT,
+                base TEXT,
+                quote TEXT,
+                rate REAL,
+                UNIQUE(date, base, quote)
+            );
+
+            CREATE TABLE IF NOT EXISTS unknown_transactions (
+                id TEXT,
+                date TEXT,
+                coin TEXT,
+                type TEXT,
+                platform TEXT
+            );
+
+            CREATE TABLE IF NOT EXISTS allocation_targets (
+                target TEXT UNIQUE,
+                weight REAL
+            );
+
+            CREATE TABLE IF NOT EXISTS coin_categories (
+                coin TEXT UNIQUE,
+                category TEXT
+            );
         ")
         {
-            Ok(_) => { Ok(()) },
+            Ok(_) => {},
             Err(_) => { return Err(CryptfolioError::DatabaseQueryFailed("Failed to create default tables".to_string())); }
         }
+
+        // Columns added after a table's creation must be added to existing databases as well.
+        DatabaseScript::add_missing_column(dbh, "rewards", "platform", "TEXT")?;
+        DatabaseScript::add_missing_column(dbh, "orders", "currency", "TEXT")?;
+        DatabaseScript::add_missing_column(dbh, "rewards", "currency", "TEXT")?;
+        DatabaseScript::add_missing_column(dbh, "fiat_transfers", "currency", "TEXT")?;
+        DatabaseScript::add_missing_column(dbh, "snapshots", "currency", "TEXT")?;
+        DatabaseScript::add_missing_column(dbh, "orders", "description", "TEXT")?;
+        DatabaseScript::add_missing_column(dbh, "orders", "order_id", "TEXT")?;
+        DatabaseScript::flag_coinbase_pro_connections(dbh)?;
+        Ok(())
+    }
+
+    /// The Coinbase Pro API has been shut down, and its API keys do not authenticate against
+    /// Coinbase Advanced. Pro connections are kept as they are, but flagged as needing new keys
+    /// so that they are no longer loaded (see CryptfolioApp::get_connections_needing_keys).
+    fn flag_coinbase_pro_connections(dbh: &Connection) -> Result<(), CryptfolioError> {
+        let mut connections = Vec::<(i64, PlatformConnection)>::new();
+        let mut statement = dbh.prepare("SELECT id, object FROM connections WHERE platform = 'Coinbase Pro'").unwrap();
+        while let State::Row = statement.next().unwrap() {
+            let connection: PlatformConnection = match bincode::deserialize(&statement.read::<Vec<u8>>(1).unwrap() as &[u8]) {
+                Ok(connection) => { connection },
+                Err(_) => { continue; }
+            };
+            if !connection.needs_new_keys() {
+                connections.push((statement.read::<i64>(0).unwrap(), connection));
+            }
+        }
+
+        for (id, mut connection) in connections {
+            connection.set_needs_new_keys();
+            let mut statement = dbh.prepare("UPDATE connections SET object = ? WHERE id = ?").unwrap();
+            statement.bind(1, &bincode::serialize(&connection).unwrap() as &[u8]).unwrap();
+            statement.bind(2, id).unwrap();
+            if let Err(e) = statement.next() {
+                return Err(CryptfolioError::DatabaseQueryFailed(e.to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    fn add_missing_column(dbh: &Connection, table: &str, column: &str, definition: &str) -> Result<(), CryptfolioError> {
+        let mut statement = dbh.prepare(format!("PRAGMA table_info({})", table)).unwrap();
+        while let State::Row = statement.next().unwrap() {
+            if statement.read::<String>(1).unwrap() == column {
+                return Ok(());
+            }
+        }
+        match dbh.execute(format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition)) {
+            Ok(_) => { Ok(()) },
+            Err(e) => { return Err(CryptfolioError::DatabaseQueryFailed(e.to_string())); }
+        }
     }
 
     pub fn fetch_connections(dbh: &Connection) -> Vec<PlatformConnection> {
@@ -78,18 +199,112 @@ impl DatabaseScript {
         return connections;
     }
 
-    pub fn fetch_coin_record(dbh: &Connection, coin: String) -> CoinRecord {
+    /// Saves what a client keeps track of between syncs (e.g. the reward epoch of Solana).
+    pub fn update_connection(dbh: &Connection, connection: &PlatformConnection) -> Result<(), CryptfolioError> {
+        let mut statement = dbh.prepare("UPDATE connections SET object = ? WHERE nickname = ? AND platform = ?").unwrap();
+        statement.bind(1, &bincode::serialize(connection).unwrap() as &[u8]).unwrap();
+        statement.bind(2, connection.nickname.as_str()).unwrap();
+        statement.bind(3, connection.platform.as_str()).unwrap();
+        match statement.next() {
+            Ok(_) => { Ok(()) },
+            Err(e) => { Err(CryptfolioError::DatabaseWriteError(e.to_string())) }
+        }
+    }
+
+    pub fn fetch_setting(dbh: &Connection, key: &str) -> Option<String> {
+        let mut statement = dbh.prepare("SELECT value FROM settings WHERE key = ?").unwrap();
+        statement.bind(1, key).unwrap();
+        match statement.next() {
+            Ok(State::Row) => { statement.read::<String>(0).ok() },
+            _ => { None }
+        }
+    }
+
+    pub fn fetch_settings(dbh: &Connection, prefix: &str) -> Vec<(String, String)> {
+        let mut settings = Vec::<(String, String)>::new();
+        let mut statement = dbh.prepare("SELECT key, IFNULL(value, '') FROM settings WHERE substr(key, 1, length(?1)) = ?1").unwrap();
+        statement.bind(1, prefix).unwrap();
+        while let State::Row = statement.next().unwrap() {
+            settings.push((statement.read::<String>(0).unwrap(), statement.read::<String>(1).unwrap()));
+        }
+        return settings;
+    }
+
+    pub fn fetch_fx_rates(dbh: &Connection, quote: &str) -> Vec<(String, NaiveDate, f64)> {
+        let mut rates = Vec::<(String, NaiveDate, f64)>::new();
+        let mut statement = dbh.prepare("SELECT base, date, rate FROM fx_rates WHERE quote = ?").unwrap();
+        statement.bind(1, quote).unwrap();
+        while let State::Row = statement.next().unwrap() {
+            if let Ok(date) = NaiveDate::parse_from_str(statement.read::<String>(1).unwrap().as_str(), "%Y-%m-%d") {
+                rates.push((statement.read::<String>(0).unwrap(), date, statement.read::<f64>(2).unwrap()));
+            }
+        }
+        return rates;
+    }
+
+    /// Every coin of the portfolio, or only those with an entry on or before a point in time.
+    pub fn fetch_coins(dbh: &Connection, as_of: Option<NaiveDateTime>, converter: &CurrencyConverter) -> Vec<String> {
+        let mut coins = Vec::<String>::new();
+        let mut statement = dbh.prepare("
+            SELECT coin, NULL AS date FROM accounts
+            UNION
+
+            SELECT substr(pair, 1, instr(pair, '-') - 1) AS coin, date
+            FROM orders
+            WHERE instr(pair, '-') > 0
+            UNION
+
+            SELECT coin, date FROM rewards
+            UNION
+
+            SELECT coin, date FROM transfers
+
+            ORDER BY coin ASC
+        ").unwrap();
+        while let State::Row = statement.next().unwrap() {
+            if let Ok(coin) = statement.read::<String>(0) {
+                // Fiat accounts are synced alongside coin accounts, but are not coins themselves.
+                if coin.is_empty() || converter.is_cash(coin.as_str()) || coins.contains(&coin) {
+                    continue;
+                }
+
+                // Accounts (and entries without a date) cannot be placed in time.
+                if let Some(as_of) = as_of {
+                    let date = statement.read::<String>(1).ok().and_then(|x| Util::parse_date(x.as_str()).ok());
+                    if !date.map_or(false, |x| x <= as_of) {
+                        continue;
+                    }
+                }
+                coins.push(coin);
+            }
+        }
+        return coins;
+    }
+
+    pub fn fetch_coin_record(dbh: &Connection, coin: String, as_of: Option<NaiveDateTime>, converter: &CurrencyConverter, method: CostBasisMethod) -> CoinRecord {
         let mut record = CoinRecord::new();
+        record.set_cost_basis_method(method);
         let mut statement = dbh.prepare("
-            SELECT date, side, unit_price, unit_size, fee
+            SELECT date, side, unit_price, unit_size, fee, IFNULL(currency, 'USD') AS currency
             FROM orders 
-            WHERE pair = ?
+            WHERE substr(pair, 1, instr(pair, '-') - 1) = ?
+            UNION
+
+            SELECT date,
+            CASE WHEN side = 'buy' THEN 'sell' ELSE 'buy' END side,
+            '1.0' AS unit_price,
+            CASE WHEN side = 'buy' THEN unit_price * unit_size + fee ELSE unit_price * unit_size - fee END unit_size,
+            '0.0' AS fee,
+            currency
+            FROM orders
+            WHERE currency = ?
             UNION
             
             SELECT date,
             CASE WHEN description IS NOT NULL THEN 'buy' END side,
             unit_price, unit_size,
-            '0.0' AS fee
+            '0.0' AS fee,
+            IFNULL(currency, 'USD') AS currency
             FROM rewards
             WHERE coin = ?
             UNION
@@ -98,32 +313,50 @@ impl DatabaseScript {
             'sell' AS side,
             '0.0' AS unit_price,
             fee AS unit_size,
-            '0.0' AS fee
+            '0.0' AS fee,
+            '' AS currency
             FROM transfers
             WHERE coin = ?
             AND fee > 0.0
+            AND id NOT IN (SELECT incoming FROM transfer_links)
             
             ORDER BY date ASC
         ").unwrap();
-        statement.bind(1, format!("{}-USD", coin).as_str()).unwrap();
+        statement.bind(1, coin.as_str()).unwrap();
         statement.bind(2, coin.as_str()).unwrap();
         statement.bind(3, coin.as_str()).unwrap();
+        statement.bind(4, coin.as_str()).unwrap();
         while let State::Row = statement.next().unwrap() {
+            // Replay only the entries dated on or before the requested point in time. Entries whose
+            // date cannot be parsed are kept for the full history, as they always have been.
+            let date = Util::parse_date(statement.read::<String>(0).unwrap().as_str()).ok();
+            match (date, as_of) {
+                (Some(date), Some(as_of)) if date > as_of => { continue; },
+                (None, Some(_)) => { continue; },
+                _ => {}
+            }
+            let day = date.map(|x| x.date()).unwrap_or_default();
+
+            // Prices and fees are converted to the reporting currency at the rate of the day. A coin
+            // spent on another (e.g. USDT on BTC-USDT) is priced at its own rate, being the currency.
+            let currency = statement.read::<String>(5).unwrap();
+            let price = converter.convert(statement.read::<f64>(2).unwrap(), currency.as_str(), day);
+            let fee = converter.convert(statement.read::<f64>(4).unwrap(), currency.as_str(), day);
             match statement.read::<String>(1).unwrap().as_str() {
                 "buy" => {
                     record.add_buy(
                         statement.read::<String>(0).unwrap(),
-                        statement.read::<f64>(2).unwrap(), 
+                        price, 
                         statement.read::<f64>(3).unwrap(),
-                        statement.read::<f64>(4).unwrap()
+                        fee
                     );
                 },
                 "sell" => {
                     record.add_sell(
                         statement.read::<String>(0).unwrap(),
-                        statement.read::<f64>(2).unwrap(),
+                        price,
                         statement.read::<f64>(3).unwrap(),
-                        statement.read::<f64>(4).unwrap()
+                        fee
                     );
                 },
                 _ => {
@@ -135,6 +368,670 @@ impl DatabaseScript {
         record
     }
 
+    pub fn fetch_ledger(dbh: &Connection, as_of: Option<NaiveDateTime>, converter: &CurrencyConverter) -> Ledger {
+        let mut entries = Vec::<LedgerEntry>::new();
+        let mut statement = dbh.prepare("
+            SELECT id, date, pair AS coin, side AS type, unit_price, unit_size, fee, platform AS origin, platform AS destination,
+            IFNULL(currency, 'USD') AS currency
+            FROM orders
+            UNION ALL
+
+            SELECT id, date, coin, 'reward' AS type, unit_price, unit_size, 0.0 AS fee, '' AS origin, IFNULL(platform, '') AS destination,
+            IFNULL(currency, 'USD') AS currency
+            FROM rewards
+            WHERE description IS NOT NULL
+            UNION ALL
+
+            SELECT transfers.id, transfers.date, transfers.coin, 'transfer' AS type, 0.0 AS unit_price, transfers.unit_size,
+            IFNULL(transfers.fee, 0.0) AS fee,
+            IFNULL(origin_account.platform, '') AS origin,
+            IFNULL(IFNULL(linked_account.platform, destination_account.platform), '') AS destination,
+            '' AS currency
+            FROM transfers
+            LEFT JOIN accounts origin_account ON origin_account.id = transfers.origin
+            LEFT JOIN accounts destination_account ON destination_account.id = transfers.destination
+            LEFT JOIN transfer_links ON transfer_links.outgoing = transfers.id
+            LEFT JOIN transfers linked ON linked.id = transfer_links.incoming
+            LEFT JOIN accounts linked_account ON linked_account.id = linked.destination
+            WHERE transfers.id NOT IN (SELECT incoming FROM transfer_links)
+        ").unwrap();
+        while let State::Row = statement.next().unwrap() {
+            let date = match Util::parse_date(statement.read::<String>(1).unwrap().as_str()) {
+                Ok(date) => { date },
+                Err(_) => { continue; }
+            };
+            if as_of.map_or(false, |x| date > x) {
+                continue;
+            }
+            let r#type = match statement.read::<String>(3).unwrap().as_str() {
+                "buy" => { LedgerEntryType::Buy },
+                "sell" => { LedgerEntryType::Sell },
+                "reward" => { LedgerEntryType::Reward },
+                "transfer" => { LedgerEntryType::Transfer },
+                _ => { continue; }
+            };
+
+            // Orders are stored by trading pair (e.g. 'BTC-USD'), but the ledger is tracked by coin.
+            let coin = Util::split_pair(statement.read::<String>(2).unwrap().as_str()).0;
+            let currency = statement.read::<String>(9).unwrap();
+            let id = statement.read::<String>(0).unwrap();
+            let unit_price = statement.read::<f64>(4).unwrap();
+            let unit_size = statement.read::<f64>(5).unwrap();
+
+            // The other side of an order paid in a coin (e.g. BTC-USDT) is a trade of that coin.
+            if !currency.is_empty() && !converter.is_cash(currency.as_str()) {
+                let fee = statement.read::<f64>(6).unwrap().abs();
+                let platform = statement.read::<String>(7).unwrap();
+                let leg = match &r#type {
+                    LedgerEntryType::Buy => { Some((LedgerEntryType::Sell, unit_price * unit_size.abs() + fee, platform, String::new())) },
+                    LedgerEntryType::Sell => { Some((LedgerEntryType::Buy, unit_price * unit_size.abs() - fee, String::new(), platform)) },
+                    _ => { None }
+                };
+                if let Some((leg_type, size, origin, destination)) = leg {
+                    entries.push(LedgerEntry {
+                        id: id.to_string(),
+                        date: date,
+                        coin: currency.to_string(),
+                        r#type: leg_type,
+                        unit_price: converter.convert(1.0, currency.as_str(), date.date()),
+                        unit_size: size,
+                        fee: 0.0,
+                        origin: origin,
+                        destination: destination,
+                    });
+                }
+            }
+
+            // Assets held as cash are a part of the cash ledger instead.
+            if converter.is_cash(coin.as_str()) {
+                continue;
+            }
+
+            // Only the platform a coin leaves (sell) or arrives at (buy) applies to an order.
+            let mut origin = statement.read::<String>(7).unwrap();
+            let mut destination = statement.read::<String>(8).unwrap();
+            match r#type {
+                LedgerEntryType::Buy => { origin = String::new(); },
+                LedgerEntryType::Sell => { destination = String::new(); },
+                _ => {}
+            }
+
+            // Transfer fees are paid in the coin itself, all others in the fiat currency.
+            let fee = match r#type {
+                LedgerEntryType::Transfer => { statement.read::<f64>(6).unwrap() },
+                _ => { converter.convert(statement.read::<f64>(6).unwrap(), currency.as_str(), date.date()) }
+            };
+
+            entries.push(LedgerEntry {
+                id: id,
+                date: date,
+                coin: coin,
+                r#type: r#type,
+                unit_price: converter.convert(unit_price, currency.as_str(), date.date()),
+                unit_size: unit_size,
+                fee: fee,
+                origin: origin,
+                destination: destination,
+            });
+        }
+        Ledger::new(entries)
+    }
+
+    pub fn clear_snapshots(dbh: &Connection) -> Result<(), CryptfolioError> {
+        match dbh.execute("DELETE FROM snapshots") {
+            Ok(_) => { Ok(()) },
+            Err(e) => { return Err(CryptfolioError::DatabaseWriteError(e.to_string())); }
+        }
+    }
+
+    pub fn fetch_history(dbh: &Connection, coin: Option<&str>, platform: Option<&str>, start: NaiveDate, end: NaiveDate) -> HistoryRecord {
+        let mut record = HistoryRecord::new(coin.map(|x| x.to_string()));
+        let mut statement = dbh.prepare("
+            SELECT date, SUM(shares), MAX(unit_price), SUM(value)
+            FROM snapshots
+            WHERE (coin = ? OR ? = '')
+            AND platform = ?
+            AND date >= ?
+            AND date <= ?
+            GROUP BY date
+            ORDER BY date ASC
+        ").unwrap();
+        statement.bind(1, coin.unwrap_or("")).unwrap();
+        statement.bind(2, coin.unwrap_or("")).unwrap();
+        statement.bind(3, platform.unwrap_or("")).unwrap();
+        statement.bind(4, start.format("%Y-%m-%d").to_string().as_str()).unwrap();
+        statement.bind(5, end.format("%Y-%m-%d").to_string().as_str()).unwrap();
+        while let State::Row = statement.next().unwrap() {
+            if let Ok(date) = NaiveDate::parse_from_str(statement.read::<String>(0).unwrap().as_str(), "%Y-%m-%d") {
+                // Shares and unit price are meaningless when summed accross many coins.
+                if coin.is_some() {
+                    record.add_point(date, statement.read::<f64>(1).unwrap(), statement.read::<f64>(2).unwrap(), statement.read::<f64>(3).unwrap());
+                } else {
+                    record.add_point(date, 0.0, 0.0, statement.read::<f64>(3).unwrap());
+                }
+            }
+        }
+        record
+    }
+
+    /// The portfolio-wide price of every coin on every day with a snapshot.
+    pub fn fetch_snapshot_prices(dbh: &Connection) -> HashMap<(String, NaiveDate), f64> {
+        let mut prices = HashMap::<(String, NaiveDate), f64>::new();
+        let mut statement = dbh.prepare("SELECT date, coin, unit_price FROM snapshots WHERE platform = ''").unwrap();
+        while let State::Row = statement.next().unwrap() {
+            if let Ok(date) = NaiveDate::parse_from_str(statement.read::<String>(0).unwrap().as_str(), "%Y-%m-%d") {
+                prices.insert((statement.read::<String>(1).unwrap(), date), statement.read::<f64>(2).unwrap());
+            }
+        }
+        return prices;
+    }
+
+    pub fn fetch_fiat_flows(dbh: &Connection, platform: Option<&str>, converter: &CurrencyConverter) -> Vec<(NaiveDate, f64)> {
+        let mut flows = Vec::<(NaiveDate, f64)>::new();
+        for transfer in DatabaseScript::fetch_fiat_transfers(dbh, converter) {
+            let date = transfer.date.date();
+            let origin = transfer.origin_platform.as_str();
+            let destination = transfer.destination_platform.as_str();
+
+            // A transfer is external to the portfolio (or platform) when only one side belongs to it.
+            match platform {
+                None => {
+                    if transfer.is_deposit() {
+                        flows.push((date, transfer.amount));
+                    } else if transfer.is_withdrawal() {
+                        flows.push((date, -transfer.amount));
+                    }
+                },
+                Some(platform) => {
+                    if destination == platform && origin != platform {
+                        flows.push((date, transfer.amount));
+                    } else if origin == platform && destination != platform {
+                        flows.push((date, -transfer.amount));
+                    }
+                }
+            }
+        }
+        return flows;
+    }
+
+    pub fn fetch_fiat_transfers(dbh: &Connection, converter: &CurrencyConverter) -> Vec<FiatTransferRecord> {
+        let mut transfers = Vec::<FiatTransferRecord>::new();
+        let mut statement = dbh.prepare("
+            SELECT fiat_transfers.id, fiat_transfers.date, fiat_transfers.amount,
+            IFNULL(fiat_transfers.currency, 'USD') AS currency,
+            IFNULL(origin_account.platform, '') AS origin_platform,
+            IFNULL(destination_account.platform, '') AS destination_platform
+            FROM fiat_transfers
+            LEFT JOIN accounts origin_account ON origin_account.id = fiat_transfers.origin
+            LEFT JOIN accounts destination_account ON destination_account.id = fiat_transfers.destination
+            ORDER BY fiat_transfers.date ASC
+        ").unwrap();
+        while let State::Row = statement.next().unwrap() {
+            let date = match Util::parse_date(statement.read::<String>(1).unwrap().as_str()) {
+                Ok(date) => { date },
+                Err(_) => { continue; }
+            };
+            let currency = statement.read::<String>(3).unwrap();
+            transfers.push(FiatTransferRecord {
+                id: statement.read::<String>(0).unwrap(),
+                date: date,
+                amount: converter.convert(statement.read::<f64>(2).unwrap().abs(), currency.as_str(), date.date()),
+                currency: currency,
+                origin_platform: statement.read::<String>(4).unwrap(),
+                destination_platform: statement.read::<String>(5).unwrap(),
+            });
+        }
+
+        // Transfers of an asset held as cash (e.g. USDC) move cash just the same. Both sides of a
+        // linked transfer are known, so the pair is an internal move.
+        let mut statement = dbh.prepare("
+            SELECT transfers.id, transfers.date, transfers.unit_size, transfers.coin,
+            IFNULL(origin_account.platform, '') AS origin_platform,
+            IFNULL(destination_account.platform, '') AS destination_platform
+            FROM transfers
+            LEFT JOIN accounts origin_account ON origin_account.id = transfers.origin
+            LEFT JOIN accounts destination_account ON destination_account.id = transfers.destination
+            WHERE transfers.id NOT IN (SELECT outgoing FROM transfer_links)
+            AND transfers.id NOT IN (SELECT incoming FROM transfer_links)
+            ORDER BY transfers.date ASC
+        ").unwrap();
+        while let State::Row = statement.next().unwrap() {
+            let coin = statement.read::<String>(3).unwrap();
+            if !converter.is_cash(coin.as_str()) {
+                continue;
+            }
+            let date = match Util::parse_date(statement.read::<String>(1).unwrap().as_str()) {
+                Ok(date) => { date },
+                Err(_) => { continue; }
+            };
+            transfers.push(FiatTransferRecord {
+                id: statement.read::<String>(0).unwrap(),
+                date: date,
+                amount: converter.convert(statement.read::<f64>(2).unwrap().abs(), coin.as_str(), date.date()),
+                currency: coin,
+                origin_platform: statement.read::<String>(4).unwrap(),
+                destination_platform: statement.read::<String>(5).unwrap(),
+            });
+        }
+        transfers.sort_by(|a, b| a.date.cmp(&b.date));
+        return transfers;
+    }
+
+    pub fn fetch_cash_ledger(dbh: &Connection, as_of: Option<NaiveDateTime>, converter: &CurrencyConverter) -> CashLedger {
+        let mut entries = Vec::<CashLedgerEntry>::new();
+
+        // Fiat transfers, along with transfers of any asset held as cash (e.g. USDC).
+        let mut statement = dbh.prepare("
+            SELECT fiat_transfers.id, fiat_transfers.date, fiat_transfers.amount,
+            IFNULL(fiat_transfers.currency, 'USD') AS currency,
+            IFNULL(origin_account.id, '') AS origin,
+            IFNULL(origin_account.platform, '') AS origin_platform,
+            IFNULL(destination_account.id, '') AS destination,
+            IFNULL(destination_account.platform, '') AS destination_platform,
+            0.0 AS fee
+            FROM fiat_transfers
+            LEFT JOIN accounts origin_account ON origin_account.id = fiat_transfers.origin
+            LEFT JOIN accounts destination_account ON destination_account.id = fiat_transfers.destination
+            UNION ALL
+
+            SELECT transfers.id, transfers.date, transfers.unit_size,
+            transfers.coin AS currency,
+            IFNULL(origin_account.id, '') AS origin,
+            IFNULL(origin_account.platform, '') AS origin_platform,
+            IFNULL(destination_account.id, '') AS destination,
+            IFNULL(destination_account.platform, '') AS destination_platform,
+            IFNULL(transfers.fee, 0.0) AS fee
+            FROM transfers
+            LEFT JOIN accounts origin_account ON origin_account.id = transfers.origin
+            LEFT JOIN accounts destination_account ON destination_account.id = transfers.destination
+
+            ORDER BY 2 ASC
+        ").unwrap();
+
+        // A move between two known accounts is recorded by both platforms, but must only be applied once.
+        let mut internal = Vec::<(NaiveDateTime, String, String, String, f64)>::new();
+        while let State::Row = statement.next().unwrap() {
+            let currency = statement.read::<String>(3).unwrap();
+            if !converter.is_cash(currency.as_str()) {
+                continue;
+            }
+            let date = match Util::parse_date(statement.read::<String>(1).unwrap().as_str()) {
+                Ok(date) => { date },
+                Err(_) => { continue; }
+            };
+            if as_of.map(|x| date > x).unwrap_or(false) {
+                continue;
+            }
+            let id = statement.read::<String>(0).unwrap();
+            let amount = statement.read::<f64>(2).unwrap().abs();
+            let origin = statement.read::<String>(4).unwrap();
+            let origin_platform = statement.read::<String>(5).unwrap();
+            let destination = statement.read::<String>(6).unwrap();
+            let destination_platform = statement.read::<String>(7).unwrap();
+            let fee = statement.read::<f64>(8).unwrap().abs();
+
+            if !origin.is_empty() && !destination.is_empty() {
+                let duplicate = internal.iter().position(|x| {
+                    x.1 == origin && x.2 == destination && x.3 == currency
+                    && (x.4 - amount).abs() < 0.005
+                    && (date - x.0).num_hours().abs() <= 48
+                });
+                if let Some(i) = duplicate {
+                    internal.remove(i);
+                    continue;
+                }
+                internal.push((date, origin.to_string(), destination.to_string(), currency.to_string(), amount));
+            }
+            if !origin.is_empty() {
+                entries.push(CashLedgerEntry {
+                    id: id.to_string(),
+                    date: date,
+                    account: origin.to_string(),
+                    platform: origin_platform.to_string(),
+                    currency: currency.to_string(),
+                    r#type: CashLedgerEntryType::Withdrawal,
+                    amount: -amount,
+                });
+                if fee > 0.0 {
+                    entries.push(CashLedgerEntry {
+                        id: id.to_string(),
+                        date: date,
+                        account: origin,
+                        platform: origin_platform,
+                        currency: currency.to_string(),
+                        r#type: CashLedgerEntryType::Fee,
+                        amount: -fee,
+                    });
+                }
+            }
+            if !destination.is_empty() {
+                entries.push(CashLedgerEntry {
+                    id: id,
+                    date: date,
+                    account: destination,
+                    platform: destination_platform,
+                    currency: currency,
+                    r#type: CashLedgerEntryType::Deposit,
+                    amount: amount,
+                });
+            }
+        }
+
+        // Rewards paid in an asset held as cash.
+        let mut statement = dbh.prepare("
+            SELECT rewards.id, rewards.date, rewards.coin, rewards.unit_size, IFNULL(rewards.platform, ''),
+            IFNULL((
+                SELECT MIN(accounts.id)
+                FROM accounts
+                WHERE accounts.platform = rewards.platform
+                AND accounts.coin = rewards.coin
+            ), '') AS account
+            FROM rewards
+            WHERE rewards.description IS NOT NULL
+        ").unwrap();
+        while let State::Row = statement.next().unwrap() {
+            let currency = statement.read::<String>(2).unwrap();
+            if !converter.is_cash(currency.as_str()) {
+                continue;
+            }
+            let date = match Util::parse_date(statement.read::<String>(1).unwrap().as_str()) {
+                Ok(date) => { date },
+                Err(_) => { continue; }
+            };
+            if as_of.map(|x| date > x).unwrap_or(false) {
+                continue;
+            }
+            entries.push(CashLedgerEntry {
+                id: statement.read::<String>(0).unwrap(),
+                date: date,
+                account: statement.read::<String>(5).unwrap(),
+                platform: statement.read::<String>(4).unwrap(),
+                currency: currency,
+                r#type: CashLedgerEntryType::Reward,
+                amount: statement.read::<f64>(3).unwrap().abs(),
+            });
+        }
+
+        // Orders are settled from the platform's account in the quote currency. When the coin is
+        // held as cash too (e.g. USDC-USD), the order is a conversion between two cash accounts.
+        let mut statement = dbh.prepare("
+            SELECT orders.id, orders.date, orders.platform,
+            IFNULL(orders.currency, 'USD') AS currency,
+            IFNULL(orders.unit_price, 0.0), orders.unit_size, IFNULL(orders.fee, 0.0), orders.side,
+            IFNULL((
+                SELECT MIN(accounts.id)
+                FROM accounts
+                WHERE accounts.platform = orders.platform
+                AND accounts.coin = IFNULL(orders.currency, 'USD')
+            ), '') AS account,
+            orders.pair,
+            IFNULL((
+                SELECT MIN(accounts.id)
+                FROM accounts
+                WHERE accounts.platform = orders.platform
+                AND accounts.coin = substr(orders.pair, 1, instr(orders.pair, '-') - 1)
+            ), '') AS coin_account
+            FROM orders
+            ORDER BY orders.date ASC
+        ").unwrap();
+        while let State::Row = statement.next().unwrap() {
+            let date = match Util::parse_date(statement.read::<String>(1).unwrap().as_str()) {
+                Ok(date) => { date },
+                Err(_) => { continue; }
+            };
+            if as_of.map(|x| date > x).unwrap_or(false) {
+                continue;
+            }
+            let id = statement.read::<String>(0).unwrap();
+            let platform = statement.read::<String>(2).unwrap();
+            let currency = statement.read::<String>(3).unwrap();
+            let size = statement.read::<f64>(5).unwrap().abs();
+            let amount = statement.read::<f64>(4).unwrap() * size;
+            let fee = statement.read::<f64>(6).unwrap().abs();
+            let side = statement.read::<String>(7).unwrap();
+            let account = statement.read::<String>(8).unwrap();
+            let coin = Util::split_pair(statement.read::<String>(9).unwrap().as_str()).0;
+            let r#type = if side == "sell" { CashLedgerEntryType::Sell } else { CashLedgerEntryType::Buy };
+
+            if converter.is_cash(coin.as_str()) {
+                entries.push(CashLedgerEntry {
+                    id: id.to_string(),
+                    date: date,
+                    account: statement.read::<String>(10).unwrap(),
+                    platform: platform.to_string(),
+                    currency: coin,
+                    r#type: r#type.clone(),
+                    amount: if side == "sell" { -size } else { size },
+                });
+            }
+
+            // An order paid for in a coin (e.g. BTC-USDT) does not touch cash.
+            if !converter.is_cash(currency.as_str()) {
+                continue;
+            }
+            entries.push(CashLedgerEntry {
+                id: id.to_string(),
+                date: date,
+                account: account.to_string(),
+                platform: platform.to_string(),
+                currency: currency.to_string(),
+                r#type: r#type,
+                amount: if side == "sell" { amount } else { -amount },
+            });
+            if fee > 0.0 {
+                entries.push(CashLedgerEntry {
+                    id: id,
+                    date: date,
+                    account: account,
+                    platform: platform,
+                    currency: currency,
+                    r#type: CashLedgerEntryType::Fee,
+                    amount: -fee,
+                });
+            }
+        }
+        CashLedger::new(entries)
+    }
+
+    pub fn fetch_reported_balances(dbh: &Connection) -> Vec<(String, String, f64, String)> {
+        let mut balances = Vec::<(String, String, f64, String)>::new();
+        let mut statement = dbh.prepare("
+            SELECT platform, coin, SUM(balance), MAX(date)
+            FROM balances latest
+            WHERE date = (
+                SELECT MAX(date)
+                FROM balances
+                WHERE account = latest.account
+            )
+            GROUP BY platform, coin
+            ORDER BY platform, coin
+        ").unwrap();
+        while let State::Row = statement.next().unwrap() {
+            balances.push((
+                statement.read::<String>(0).unwrap(),
+                statement.read::<String>(1).unwrap(),
+                statement.read::<f64>(2).unwrap(),
+                statement.read::<String>(3).unwrap()
+            ));
+        }
+        return balances;
+    }
+
+    pub fn fetch_transfers(dbh: &Connection) -> Vec<TransferRecord> {
+        let mut transfers = Vec::<TransferRecord>::new();
+        let mut statement = dbh.prepare("
+            SELECT transfers.id, transfers.date, transfers.coin, transfers.unit_size, IFNULL(transfers.fee, 0.0),
+            IFNULL(transfers.origin, ''), IFNULL(transfers.destination, ''),
+            IFNULL(origin_account.platform, '') AS origin_platform,
+            IFNULL(destination_account.platform, '') AS destination_platform
+            FROM transfers
+            LEFT JOIN accounts origin_account ON origin_account.id = transfers.origin
+            LEFT JOIN accounts destination_account ON destination_account.id = transfers.destination
+            ORDER BY transfers.date ASC
+        ").unwrap();
+        while let State::Row = statement.next().unwrap() {
+            let date = match Util::parse_date(statement.read::<String>(1).unwrap().as_str()) {
+                Ok(date) => { date },
+                Err(_) => { continue; }
+            };
+            transfers.push(TransferRecord {
+                id: statement.read::<String>(0).unwrap(),
+                date: date,
+                coin: statement.read::<String>(2).unwrap(),
+                unit_size: statement.read::<f64>(3).unwrap(),
+                fee: statement.read::<f64>(4).unwrap(),
+                origin: statement.read::<String>(5).unwrap(),
+                destination: statement.read::<String>(6).unwrap(),
+                origin_platform: statement.read::<String>(7).unwrap(),
+                destination_platform: statement.read::<String>(8).unwrap(),
+            });
+        }
+        return transfers;
+    }
+
+    pub fn fetch_linked_transfers(dbh: &Connection) -> Vec<String> {
+        let mut linked = Vec::<String>::new();
+        let mut statement = dbh.prepare("SELECT outgoing, incoming FROM transfer_links").unwrap();
+        while let State::Row = statement.next().unwrap() {
+            linked.push(statement.read::<String>(0).unwrap());
+            linked.push(statement.read::<String>(1).unwrap());
+        }
+        return linked;
+    }
+
+    pub fn fetch_missing_prices(dbh: &Connection) -> Vec<(String, String, String, String)> {
+        let mut entries = Vec::<(String, String, String, String)>::new();
+        let mut statement = dbh.prepare("
+            SELECT 'order', id, substr(pair, 1, instr(pair, '-') - 1), date
+            FROM orders
+            WHERE unit_price IS NULL OR unit_price <= 0.0
+            UNION ALL
+
+            SELECT 'reward', id, coin, date
+            FROM rewards
+            WHERE unit_price IS NULL OR unit_price <= 0.0
+        ").unwrap();
+        while let State::Row = statement.next().unwrap() {
+            entries.push((
+                statement.read::<String>(0).unwrap(),
+                statement.read::<String>(1).unwrap(),
+                statement.read::<String>(2).unwrap(),
+                statement.read::<String>(3).unwrap()
+            ));
+        }
+        return entries;
+    }
+
+    pub fn update_reward_price(dbh: &Connection, id: &str, unit_price: f64, currency: &str) -> Result<(), CryptfolioError> {
+        let mut statement = dbh.prepare("
+            UPDATE rewards
+            SET unit_price = ?, currency = ?
+            WHERE id = ?
+            AND (unit_price IS NULL OR unit_price <= 0.0)
+        ").unwrap();
+        statement.bind(1, unit_price).unwrap();
+        statement.bind(2, currency).unwrap();
+        statement.bind(3, id).unwrap();
+        match statement.next() {
+            Ok(_) => { Ok(()) },
+            Err(e) => { Err(CryptfolioError::DatabaseWriteError(e.to_string())) }
+        }
+    }
+
+    pub fn fetch_missing_fees(dbh: &Connection) -> Vec<(String, String, String)> {
+        let mut entries = Vec::<(String, String, String)>::new();
+
+        // NaN is stored by SQLite as NULL. Only the sender of a transfer pays its fee, so deposits
+        // from outside the portfolio (e.g. Coinbase Pro deposits) are never missing one.
+        let mut statement = dbh.prepare("
+            SELECT id, coin, date
+            FROM transfers
+            WHERE fee IS NULL
+            AND origin IN (SELECT id FROM accounts)
+            AND id NOT IN (SELECT incoming FROM transfer_links)
+        ").unwrap();
+        while let State::Row = statement.next().unwrap() {
+            entries.push((
+                statement.read::<String>(0).unwrap(),
+                statement.read::<String>(1).unwrap(),
+                statement.read::<String>(2).unwrap()
+            ));
+        }
+        return entries;
+    }
+
+    pub fn fetch_unknown_transactions(dbh: &Connection) -> Vec<(String, String, String, String, String)> {
+        let mut entries = Vec::<(String, String, String, String, String)>::new();
+        let mut statement = dbh.prepare("
+            SELECT DISTINCT id, date, coin, type, platform
+            FROM unknown_transactions
+            ORDER BY date ASC
+        ").unwrap();
+        while let State::Row = statement.next().unwrap() {
+            entries.push((
+                statement.read::<String>(0).unwrap(),
+                statement.read::<String>(1).unwrap(),
+                statement.read::<String>(2).unwrap(),
+                statement.read::<String>(3).unwrap(),
+                statement.read::<String>(4).unwrap()
+            ));
+        }
+        return entries;
+    }
+
+    pub fn fetch_duplicate_ids(dbh: &Connection) -> Vec<(String, String, i64)> {
+        let mut entries = Vec::<(String, String, i64)>::new();
+        let mut statement = dbh.prepare("
+            SELECT id, tbl, COUNT(*)
+            FROM (
+                SELECT id, 'orders' AS tbl FROM orders
+                UNION ALL
+                SELECT id, 'rewards' AS tbl FROM rewards
+                UNION ALL
+                SELECT id, 'transfers' AS tbl FROM transfers
+                UNION ALL
+                SELECT id, 'fiat_transfers' AS tbl FROM fiat_transfers
+            )
+            GROUP BY id, tbl
+            HAVING COUNT(*) > 1
+        ").unwrap();
+        while let State::Row = statement.next().unwrap() {
+            entries.push((
+                statement.read::<String>(0).unwrap(),
+                statement.read::<String>(1).unwrap(),
+                statement.read::<i64>(2).unwrap()
+            ));
+        }
+        return entries;
+    }
+
+    pub fn fetch_allocation_targets(dbh: &Connection) -> Vec<(String, f64)> {
+        let mut targets = Vec::<(String, f64)>::new();
+        let mut statement = dbh.prepare("SELECT target, weight FROM allocation_targets ORDER BY weight DESC").unwrap();
+        while let State::Row = statement.next().unwrap() {
+            targets.push((statement.read::<String>(0).unwrap(), statement.read::<f64>(1).unwrap()));
+        }
+        return targets;
+    }
+
+    pub fn delete_allocation_target(dbh: &Connection, target: &str) -> Result<(), CryptfolioError> {
+        let mut statement = dbh.prepare("DELETE FROM allocation_targets WHERE target = ?").unwrap();
+        statement.bind(1, target).unwrap();
+        match statement.next() {
+            Ok(_) => { Ok(()) },
+            Err(e) => { Err(CryptfolioError::DatabaseWriteError(e.to_string())) }
+        }
+    }
+
+    pub fn fetch_coin_categories(dbh: &Connection) -> HashMap<String, String> {
+        let mut categories = HashMap::<String, String>::new();
+        let mut statement = dbh.prepare("SELECT coin, category FROM coin_categories").unwrap();
+        while let State::Row = statement.next().unwrap() {
+            categories.insert(statement.read::<String>(0).unwrap(), statement.read::<String>(1).unwrap());
+        }
+        return categories;
+    }
+
     pub fn update_default_values(dbh: &Connection) {
         // Updating fiat_transfers table to replace 'Coinbase Pro' destination entries
         dbh.execute("
@@ -143,12 +1040,24 @@ impl DatabaseScript {
                 SELECT id
                 FROM accounts
                 WHERE platform = 'Coinbase Pro'
-                AND platform IS NOT NULL
+                AND coin = IFNULL(fiat_transfers.currency, 'USD')
                 LIMIT 1
             )
             WHERE destination = 'Coinbase Pro'
         ").unwrap();
 
+        dbh.execute("
+            UPDATE fiat_transfers
+            SET origin = (
+                SELECT id
+                FROM accounts
+                WHERE platform = 'Coinbase Pro'
+                AND coin = IFNULL(fiat_transfers.currency, 'USD')
+                LIMIT 1
+            )
+            WHERE origin = 'Coinbase Pro'
+        ").unwrap();
+
         // Updating transfers table to replace 'Coinbase Pro' origin & destination entries
         dbh.execute("
             UPDATE transfers
@@ -156,7 +1065,7 @@ impl DatabaseScript {
                 SELECT id
                 FROM accounts
                 WHERE platform = 'Coinbase Pro'
-                AND platform IS NOT NULL
+                AND coin = transfers.coin
                 LIMIT 1
             )
             WHERE origin = 'Coinbase Pro'
@@ -168,10 +1077,26 @@ impl DatabaseScript {
                 SELECT id
                 FROM accounts
                 WHERE platform = 'Coinbase Pro'
-                AND platform IS NOT NULL
+                AND coin = transfers.coin
                 LIMIT 1
             )
             WHERE destination = 'Coinbase Pro'
         ").unwrap();
+
+        // Advanced Trade fills imported by Coinbase give way to the fills of the same order from
+        // Coinbase Advanced, which have the exact fee. The pair and size of the two may differ
+        // (e.g. BTC-EUR in the native currency against BTC-USDC), so only the order id is matched.
+        dbh.execute("
+            DELETE FROM orders
+            WHERE platform = 'Coinbase'
+            AND description = 'Advanced Trade fill'
+            AND order_id IN (
+                SELECT order_id
+                FROM orders
+                WHERE platform = 'Coinbase'
+                AND description LIKE 'Advanced Trade: %'
+                AND IFNULL(order_id, '') != ''
+            )
+        ").unwrap();
     }
 }
\ No newline at end of file
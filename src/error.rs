@@ -25,6 +25,13 @@ pub enum CryptfolioError {
     #[error("Platform is already staged for connection")]
     PlatformAlreadyExists,
 
+    #[error("Invalid connection data: {0}")]
+    InvalidConnectionData(String),
+
+    // setting errors
+    #[error("Invalid setting: {0}")]
+    InvalidSetting(String),
+
     // coin errors
     #[error("Coin already exists (code: {0} | name: {1}")]
     CoinAlreadyExists(String, String),
@@ -46,14 +53,37 @@ pub enum CryptfolioError {
     #[error("Coinbase Pro API Error: {0}")]
     CoinbaseProAPIError(String),
 
+    #[error("Coinbase Advanced API Error: {0}")]
+    CoinbaseAdvancedAPIError(String),
+
+    // kraken api errors
+    #[error("Kraken API Error: {0}")]
+    KrakenAPIError(String),
+
+    // binance api errors
+    #[error("Binance API Error: {0}")]
+    BinanceAPIError(String),
+
     // solana errors
     #[error("Solana API Error: {0}")]
     SolanaAPIError(String),
 
+    // evm errors
+    #[error("EVM API Error: {0}")]
+    EvmAPIError(String),
+
+    // bitcoin errors
+    #[error("Bitcoin API Error: {0}")]
+    BitcoinAPIError(String),
+
     // sync error
     #[error("Error while syncing client: {0}")]
     SyncError(String),
 
+    // pricing errors
+    #[error("Could not load price history: {0}")]
+    PriceHistoryError(String),
+
     // parsing error
     #[error("Could not parse DateTime: {0}")]
     DateTimeParseError(String),
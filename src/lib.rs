@@ -25,12 +25,17 @@
 /// 
 /// Platforms:
 ///     [Exchanges]
+///         Binance, Binance.US
 ///         Coinbase
-///         Coinbase Pro
+///         Coinbase Advanced
+///         Coinbase Pro (shut down, connections need new keys as Coinbase Advanced)
+///         Kraken
 ///     [Wallets]
 ///         --
 ///     [Blockchains]
-///         --
+///         Bitcoin (xpub, ypub, zpub)
+///         Ethereum, Arbitrum, Base, Polygon
+///         Solana
 ///     [Manual Transaction Records]
 /// 
 /// ///////////////////////////////////////////////////////////////////////////////////////////////
@@ -41,7 +46,8 @@
 
 pub mod app;
 pub mod recording;
-mod database;
+pub mod database;
 pub mod error;
 pub mod platform;
+pub mod pricing;
 mod util;
\ No newline at end of file
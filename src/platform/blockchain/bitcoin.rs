@@ -0,0 +1,531 @@
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// bitcoin.rs
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Description:
+///     Bitcoin (Blockchain) implementation for syncing the transaction history of an HD wallet
+///     from its extended public key (xpub, ypub or zpub).
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Usage:
+///     let wallet = Bitcoin::new("zpub...", BitcoinBackend::Esplora, "https://blockstream.info/api", 20).unwrap();
+///     let handle = app.add_platform("Cold Storage", wallet);
+///     app.sync_platform(handle).await.unwrap();
+/// 
+///     // Or through an Electrum server (plain TCP), such as a local electrs or Fulcrum.
+///     let wallet = Bitcoin::new("xpub...", BitcoinBackend::Electrum, "127.0.0.1:50001", 20).unwrap();
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Notes:
+///     The prefix of the extended key picks the address type: xpub for legacy (P2PKH), ypub for
+///     nested segwit (P2SH-P2WPKH) and zpub for native segwit (P2WPKH). Testnet keys (tpub, upub,
+///     vpub) work the same way. Receive (0/i) and change (1/i) addresses are derived until the
+///     gap limit of consecutive unused addresses is reached on each chain.
+/// 
+///     The wallet as a whole is one account ("bitcoin:<fingerprint>"). Every output received from
+///     outside the wallet is an incoming transfer of its own ("<txid>:<vout>"), so that each UTXO
+///     arrives as a separate lot. When the wallet spends, only the amount which leaves the wallet
+///     is an outgoing transfer, and it carries the miner fee. Change returning to the wallet is
+///     recorded as a transfer from the wallet to itself. When other wallets contributed inputs
+///     (e.g. a coinjoin), the fee is shared in proportion to the value of the inputs.
+/// 
+///     Unconfirmed transactions are skipped until they are confirmed. The balance of the unspent
+///     outputs is recorded as the wallet's balance for reconciliation.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+
+use crate::database::entry::{AccountBalance, CoinAccount, CoinTransfer, DatabaseEntry, PlatformConnection, PlatformConnectionData};
+use crate::error::CryptfolioError;
+use crate::platform::SyncClient;
+use async_trait::async_trait;
+use bitcoin::consensus::encode::deserialize;
+use bitcoin::hashes::hex::{FromHex, ToHex};
+use bitcoin::hashes::{sha256, Hash};
+use bitcoin::secp256k1::Secp256k1;
+use bitcoin::util::base58;
+use bitcoin::util::bip32::{ChildNumber, ExtendedPubKey};
+use bitcoin::{Address, PublicKey, Transaction};
+use chrono::DateTime;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+
+const SATOSHIS_PER_BITCOIN: f64 = 100_000_000.0;
+const ESPLORA_PAGE_SIZE: usize = 25;
+const XPUB_VERSION: [u8; 4] = [0x04, 0x88, 0xb2, 0x1e];
+const TPUB_VERSION: [u8; 4] = [0x04, 0x35, 0x87, 0xcf];
+
+// A stalled Electrum server fails the sync rather than hanging it.
+const ELECTRUM_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BitcoinBackend {
+    Esplora,
+    Electrum,
+}
+
+impl fmt::Display for BitcoinBackend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Esplora => { write!(f, "esplora") },
+            Self::Electrum => { write!(f, "electrum") },
+        }
+    }
+}
+
+impl FromStr for BitcoinBackend {
+    type Err = CryptfolioError;
+
+    fn from_str(backend: &str) -> Result<BitcoinBackend, CryptfolioError> {
+        match backend.to_lowercase().as_str() {
+            "esplora" => { Ok(Self::Esplora) },
+            "electrum" => { Ok(Self::Electrum) },
+            _ => { Err(CryptfolioError::InvalidConnectionData(format!("Unknown Bitcoin backend '{}'", backend))) }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum AddressType {
+    Legacy,
+    NestedSegwit,
+    NativeSegwit,
+}
+
+struct BitcoinInput {
+    outpoint: String,
+    address: Option<String>,
+    value: u64,
+}
+
+struct BitcoinTransaction {
+    txid: String,
+    timestamp: i64,
+    inputs: Vec<BitcoinInput>,
+    outputs: Vec<(Option<String>, u64)>,
+    fee: u64,
+}
+
+struct ElectrumConnection {
+    reader: BufReader<OwnedReadHalf>,
+    writer: OwnedWriteHalf,
+    id: u64,
+}
+
+impl ElectrumConnection {
+    async fn connect(endpoint: &str) -> Result<ElectrumConnection, CryptfolioError> {
+        let stream = match timeout(ELECTRUM_TIMEOUT, TcpStream::connect(endpoint)).await {
+            Ok(Ok(stream)) => { stream },
+            Ok(Err(e)) => { return Err(CryptfolioError::BitcoinAPIError(e.to_string())); },
+            Err(_) => { return Err(CryptfolioError::BitcoinAPIError(format!("Timed out connecting to {}", endpoint))); }
+        };
+        let (reader, writer) = stream.into_split();
+        let mut connection = ElectrumConnection { reader: BufReader::new(reader), writer: writer, id: 0 };
+        connection.call("server.version", json!(["cryptfolio", "1.4"])).await?;
+        Ok(connection)
+    }
+
+    async fn call(&mut self, method: &str, params: Value) -> Result<Value, CryptfolioError> {
+        self.id += 1;
+        let request = json!({ "jsonrpc": "2.0", "id": self.id, "method": method, "params": params });
+        let mut line = String::new();
+        let exchange = async {
+            self.writer.write_all(format!("{}\n", request).as_bytes()).await?;
+            self.reader.read_line(&mut line).await
+        };
+        match timeout(ELECTRUM_TIMEOUT, exchange).await {
+            Ok(Ok(_)) => {},
+            Ok(Err(e)) => { return Err(CryptfolioError::BitcoinAPIError(e.to_string())); },
+            Err(_) => { return Err(CryptfolioError::BitcoinAPIError(format!("Timed out waiting for {}", method))); }
+        }
+        let response: Value = match serde_json::from_str(line.as_str()) {
+            Ok(response) => { response },
+            Err(e) => { return Err(CryptfolioError::BitcoinAPIError(e.to_string())); }
+        };
+        if !response["error"].is_null() {
+            return Err(CryptfolioError::BitcoinAPIError(format!("{} failed: {}", method, response["error"])));
+        }
+        Ok(response["result"].clone())
+    }
+}
+
+pub struct Bitcoin {
+    extended_key: String,
+    xpub: ExtendedPubKey,
+    address_type: AddressType,
+    backend: BitcoinBackend,
+    endpoint: String,
+    gap_limit: u32,
+    client: reqwest::Client,
+}
+
+impl Bitcoin {
+    pub fn new(extended_key: &str, backend: BitcoinBackend, endpoint: &str, gap_limit: u32) -> Result<Bitcoin, CryptfolioError> {
+        let (xpub, address_type) = Self::parse_extended_key(extended_key)?;
+        if gap_limit == 0 {
+            return Err(CryptfolioError::InvalidConnectionData("Gap limit must be at least 1".to_string()));
+        }
+        Ok(
+            Bitcoin {
+                extended_key: extended_key.to_string(),
+                xpub: xpub,
+                address_type: address_type,
+                backend: backend,
+                endpoint: endpoint.trim_end_matches('/').to_string(),
+                gap_limit: gap_limit,
+                client: reqwest::Client::new(),
+            }
+        )
+    }
+
+    pub fn get_extended_key(&self) -> String {
+        self.extended_key.to_string()
+    }
+
+    pub fn get_backend(&self) -> &BitcoinBackend {
+        return &self.backend;
+    }
+
+    pub fn get_endpoint(&self) -> &str {
+        return self.endpoint.as_str();
+    }
+
+    pub fn get_gap_limit(&self) -> u32 {
+        return self.gap_limit;
+    }
+
+    fn get_account(&self) -> String {
+        format!("bitcoin:{}", self.xpub.fingerprint())
+    }
+
+    /// ypub and zpub (and their testnet equivalents) only differ from an xpub by their version
+    /// bytes, which select the address type.
+    fn parse_extended_key(extended_key: &str) -> Result<(ExtendedPubKey, AddressType), CryptfolioError> {
+        let (version, address_type) = match extended_key.get(0..4) {
+            Some("xpub") => { (XPUB_VERSION, AddressType::Legacy) },
+            Some("ypub") => { (XPUB_VERSION, AddressType::NestedSegwit) },
+            Some("zpub") => { (XPUB_VERSION, AddressType::NativeSegwit) },
+            Some("tpub") => { (TPUB_VERSION, AddressType::Legacy) },
+            Some("upub") => { (TPUB_VERSION, AddressType::NestedSegwit) },
+            Some("vpub") => { (TPUB_VERSION, AddressType::NativeSegwit) },
+            _ => { return Err(CryptfolioError::InvalidConnectionData("Expected an xpub, ypub or zpub".to_string())); }
+        };
+        let mut data = match base58::from_check(extended_key) {
+            Ok(data) if data.len() == 78 => { data },
+            _ => { return Err(CryptfolioError::InvalidConnectionData("Invalid extended public key".to_string())); }
+        };
+        data[0..4].copy_from_slice(&version);
+        match ExtendedPubKey::decode(&data) {
+            Ok(xpub) => { Ok((xpub, address_type)) },
+            Err(e) => { Err(CryptfolioError::InvalidConnectionData(e.to_string())) }
+        }
+    }
+
+    /// The address at an index of the receive (0) or change (1) chain.
+    pub fn derive_address(&self, chain: u32, index: u32) -> Result<Address, CryptfolioError> {
+        let secp = Secp256k1::verification_only();
+        let path = [ChildNumber::Normal { index: chain }, ChildNumber::Normal { index: index }];
+        let child = match self.xpub.derive_pub(&secp, &path) {
+            Ok(child) => { child },
+            Err(e) => { return Err(CryptfolioError::BitcoinAPIError(e.to_string())); }
+        };
+        let public_key = PublicKey::new(child.public_key);
+        let address = match self.address_type {
+            AddressType::Legacy => { Ok(Address::p2pkh(&public_key, self.xpub.network)) },
+            AddressType::NestedSegwit => { Address::p2shwpkh(&public_key, self.xpub.network) },
+            AddressType::NativeSegwit => { Address::p2wpkh(&public_key, self.xpub.network) },
+        };
+        match address {
+            Ok(address) => { Ok(address) },
+            Err(e) => { Err(CryptfolioError::BitcoinAPIError(e.to_string())) }
+        }
+    }
+
+    fn format_timestamp(timestamp: i64) -> String {
+        DateTime::from_timestamp(timestamp, 0).unwrap_or_default().format("%Y-%m-%dT%H:%M:%SZ").to_string()
+    }
+
+    async fn get_json(&self, path: &str) -> Result<Value, CryptfolioError> {
+        let response = match self.client.get(format!("{}{}", self.endpoint, path)).send().await {
+            Ok(response) => { response },
+            Err(e) => { return Err(CryptfolioError::BitcoinAPIError(e.to_string())); }
+        };
+        match response.json::<Value>().await {
+            Ok(response) => { Ok(response) },
+            Err(e) => { Err(CryptfolioError::BitcoinAPIError(e.to_string())) }
+        }
+    }
+
+    /// Confirmed transactions of an address, a page at a time.
+    async fn fetch_esplora_history(&self, address: &Address) -> Result<Vec<BitcoinTransaction>, CryptfolioError> {
+        let mut transactions = Vec::<BitcoinTransaction>::new();
+        let mut path = format!("/address/{}/txs/chain", address);
+        loop {
+            let page = self.get_json(path.as_str()).await?;
+            let page = page.as_array().cloned().unwrap_or_default();
+            for transaction in page.iter() {
+                if transaction["status"]["confirmed"].as_bool() != Some(true) {
+                    continue;
+                }
+                transactions.push(BitcoinTransaction {
+                    txid: transaction["txid"].as_str().unwrap_or("").to_string(),
+                    timestamp: transaction["status"]["block_time"].as_i64().unwrap_or(0),
+                    inputs: transaction["vin"]
+                        .as_array()
+                        .cloned()
+                        .unwrap_or_default()
+                        .iter()
+                        .map(|x| BitcoinInput {
+                            outpoint: format!("{}:{}", x["txid"].as_str().unwrap_or(""), x["vout"].as_u64().unwrap_or(0)),
+                            address: x["prevout"]["scriptpubkey_address"].as_str().map(|x| x.to_string()),
+                            value: x["prevout"]["value"].as_u64().unwrap_or(0),
+                        })
+                        .collect(),
+                    outputs: transaction["vout"]
+                        .as_array()
+                        .cloned()
+                        .unwrap_or_default()
+                        .iter()
+                        .map(|x| (x["scriptpubkey_address"].as_str().map(|x| x.to_string()), x["value"].as_u64().unwrap_or(0)))
+                        .collect(),
+                    fee: transaction["fee"].as_u64().unwrap_or(0),
+                });
+            }
+            if page.len() < ESPLORA_PAGE_SIZE {
+                break;
+            }
+            path = format!("/address/{}/txs/chain/{}", address, page.last().unwrap()["txid"].as_str().unwrap_or(""));
+        }
+        Ok(transactions)
+    }
+
+    async fn fetch_electrum_transaction(&self, connection: &mut ElectrumConnection, txid: &str, cache: &mut HashMap<String, Transaction>) -> Result<Transaction, CryptfolioError> {
+        if let Some(transaction) = cache.get(txid) {
+            return Ok(transaction.clone());
+        }
+        let raw = connection.call("blockchain.transaction.get", json!([txid])).await?;
+        let bytes = match Vec::<u8>::from_hex(raw.as_str().unwrap_or("")) {
+            Ok(bytes) => { bytes },
+            Err(e) => { return Err(CryptfolioError::BitcoinAPIError(e.to_string())); }
+        };
+        let transaction: Transaction = match deserialize(&bytes) {
+            Ok(transaction) => { transaction },
+            Err(e) => { return Err(CryptfolioError::BitcoinAPIError(e.to_string())); }
+        };
+        cache.insert(txid.to_string(), transaction.clone());
+        Ok(transaction)
+    }
+
+    /// Electrum only serves raw transactions, so the value of each input is read from the
+    /// transaction which created it, and the time from the header of the block it was mined in.
+    async fn fetch_electrum_history(&self, connection: &mut ElectrumConnection, address: &Address, cache: &mut HashMap<String, Transaction>) -> Result<Vec<BitcoinTransaction>, CryptfolioError> {
+        let mut transactions = Vec::<BitcoinTransaction>::new();
+        let mut script_hash = sha256::Hash::hash(address.script_pubkey().as_bytes()).into_inner();
+        script_hash.reverse();
+        let history = connection.call("blockchain.scripthash.get_history", json!([script_hash.to_hex()])).await?;
+        for entry in history.as_array().cloned().unwrap_or_default() {
+            let height = entry["height"].as_i64().unwrap_or(0);
+            if height <= 0 {
+                continue;
+            }
+            let txid = entry["tx_hash"].as_str().unwrap_or("").to_string();
+            let transaction = self.fetch_electrum_transaction(connection, txid.as_str(), cache).await?;
+
+            let header = connection.call("blockchain.block.header", json!([height])).await?;
+            let header = Vec::<u8>::from_hex(header.as_str().unwrap_or("")).unwrap_or_default();
+            let timestamp = if header.len() >= 72 {
+                u32::from_le_bytes([header[68], header[69], header[70], header[71]]) as i64
+            } else {
+                0
+            };
+
+            let mut inputs = Vec::<BitcoinInput>::new();
+            for input in transaction.input.iter() {
+                if input.previous_output.is_null() {
+                    continue;
+                }
+                let previous = self.fetch_electrum_transaction(connection, input.previous_output.txid.to_string().as_str(), cache).await?;
+                let output = &previous.output[input.previous_output.vout as usize];
+                inputs.push(BitcoinInput {
+                    outpoint: input.previous_output.to_string(),
+                    address: Address::from_script(&output.script_pubkey, self.xpub.network).ok().map(|x| x.to_string()),
+                    value: output.value,
+                });
+            }
+            let outputs: Vec<(Option<String>, u64)> = transaction.output
+                .iter()
+                .map(|x| (Address::from_script(&x.script_pubkey, self.xpub.network).ok().map(|x| x.to_string()), x.value))
+                .collect();
+            let input_value: u64 = inputs.iter().map(|x| x.value).sum();
+            let output_value: u64 = outputs.iter().map(|x| x.1).sum();
+            transactions.push(BitcoinTransaction {
+                txid: txid,
+                timestamp: timestamp,
+                inputs: inputs,
+                outputs: outputs,
+                fee: input_value.saturating_sub(output_value),
+            });
+        }
+        Ok(transactions)
+    }
+
+    fn process_transaction(&self, transaction: &BitcoinTransaction, own: &HashSet<String>) -> Vec<Box<dyn DatabaseEntry + Send>> {
+        let mut result = Vec::<Box<dyn DatabaseEntry + Send>>::new();
+        let account = self.get_account();
+        let date = Self::format_timestamp(transaction.timestamp);
+        let is_own = |x: &Option<String>| x.as_ref().map_or(false, |x| own.contains(x));
+
+        let own_inputs: u64 = transaction.inputs.iter().filter(|x| is_own(&x.address)).map(|x| x.value).sum();
+        if own_inputs == 0 {
+            // Every output received from outside the wallet is a lot of its own.
+            let sender = transaction.inputs
+                .iter()
+                .filter_map(|x| x.address.clone())
+                .next()
+                .unwrap_or("Unknown".to_string());
+            for (vout, (address, value)) in transaction.outputs.iter().enumerate() {
+                if is_own(address) {
+                    result.push(Box::new(CoinTransfer::new(
+                        format!("{}:{}", transaction.txid, vout),
+                        date.to_string(),
+                        sender.to_string(),
+                        account.to_string(),
+                        "BTC".to_string(),
+                        *value as f64 / SATOSHIS_PER_BITCOIN,
+                        0.0
+                    )));
+                }
+            }
+            return result;
+        }
+
+        // Other wallets may have contributed inputs, in which case they share the fee.
+        let total_inputs: u64 = transaction.inputs.iter().map(|x| x.value).sum();
+        let fee = if own_inputs >= total_inputs {
+            transaction.fee
+        } else {
+            (transaction.fee as f64 * own_inputs as f64 / total_inputs as f64).round() as u64
+        };
+        let own_outputs: u64 = transaction.outputs.iter().filter(|x| is_own(&x.0)).map(|x| x.1).sum();
+        let sent = own_inputs.saturating_sub(own_outputs).saturating_sub(fee);
+
+        let recipient = transaction.outputs
+            .iter()
+            .filter(|x| !is_own(&x.0))
+            .max_by_key(|x| x.1)
+            .and_then(|x| x.0.clone())
+            .unwrap_or("Unknown".to_string());
+        if sent > 0 {
+            result.push(Box::new(CoinTransfer::new(
+                transaction.txid.to_string(),
+                date.to_string(),
+                account.to_string(),
+                recipient,
+                "BTC".to_string(),
+                sent as f64 / SATOSHIS_PER_BITCOIN,
+                fee as f64 / SATOSHIS_PER_BITCOIN
+            )));
+        }
+
+        // Change, or a consolidation of the wallet's own outputs, which then carries the fee.
+        if own_outputs > 0 || (sent == 0 && fee > 0) {
+            result.push(Box::new(CoinTransfer::new(
+                if sent > 0 { format!("{}:change", transaction.txid) } else { transaction.txid.to_string() },
+                date,
+                account.to_string(),
+                account,
+                "BTC".to_string(),
+                own_outputs as f64 / SATOSHIS_PER_BITCOIN,
+                if sent > 0 { 0.0 } else { fee as f64 / SATOSHIS_PER_BITCOIN }
+            )));
+        }
+        result
+    }
+}
+
+#[async_trait]
+impl SyncClient for Bitcoin {
+    fn get_name(&self) -> &str {
+        "Bitcoin"
+    }
+
+    fn get_connection(&self, nickname: &String) -> PlatformConnection {
+        return PlatformConnection::new(
+            nickname.to_string(),
+            "Bitcoin".to_string(),
+            vec![
+                PlatformConnectionData { key: "Extended Public Key".to_string(), value: self.get_extended_key() },
+                PlatformConnectionData { key: "Backend".to_string(), value: self.backend.to_string() },
+                PlatformConnectionData { key: "Endpoint".to_string(), value: self.endpoint.to_string() },
+                PlatformConnectionData { key: "Gap Limit".to_string(), value: self.gap_limit.to_string() },
+            ]
+        );
+    }
+
+    async fn sync(&self) -> Result<Vec<Box<dyn DatabaseEntry + Send>>, CryptfolioError> {
+        let mut result = Vec::<Box<dyn DatabaseEntry + Send>>::new();
+        let mut connection = match self.backend {
+            BitcoinBackend::Electrum => { Some(ElectrumConnection::connect(self.endpoint.as_str()).await?) },
+            BitcoinBackend::Esplora => { None }
+        };
+        let mut cache = HashMap::<String, Transaction>::new();
+
+        // Scan the receive (0) and change (1) chains until the gap limit of unused addresses.
+        let mut own = HashSet::<String>::new();
+        let mut transactions = HashMap::<String, BitcoinTransaction>::new();
+        for chain in 0..2 {
+            let mut index: u32 = 0;
+            let mut gap: u32 = 0;
+            while gap < self.gap_limit {
+                let address = self.derive_address(chain, index)?;
+                let history = match connection.as_mut() {
+                    Some(connection) => { self.fetch_electrum_history(connection, &address, &mut cache).await? },
+                    None => { self.fetch_esplora_history(&address).await? }
+                };
+                if history.is_empty() { gap += 1; } else { gap = 0; }
+                for transaction in history {
+                    transactions.insert(transaction.txid.to_string(), transaction);
+                }
+                own.insert(address.to_string());
+                index += 1;
+            }
+        }
+
+        let mut transactions: Vec<BitcoinTransaction> = transactions.into_values().collect();
+        transactions.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        result.push(Box::new(CoinAccount::new(self.get_account(), "BTC".to_string(), "Bitcoin".to_string())));
+        for transaction in transactions.iter() {
+            result.append(&mut self.process_transaction(transaction, &own));
+        }
+
+        // Unspent outputs are those of the wallet which no transaction of the wallet spent.
+        let spent: HashSet<&String> = transactions.iter().flat_map(|x| x.inputs.iter().map(|x| &x.outpoint)).collect();
+        let mut unspent: u64 = 0;
+        for transaction in transactions.iter() {
+            for (vout, (address, value)) in transaction.outputs.iter().enumerate() {
+                let outpoint = format!("{}:{}", transaction.txid, vout);
+                if address.as_ref().map_or(false, |x| own.contains(x)) && !spent.contains(&outpoint) {
+                    unspent += value;
+                }
+            }
+        }
+        result.push(Box::new(AccountBalance::new(
+            self.get_account(),
+            chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            "BTC".to_string(),
+            "Bitcoin".to_string(),
+            unspent as f64 / SATOSHIS_PER_BITCOIN
+        )));
+        Ok(result)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,507 @@
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// evm.rs
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Description:
+///     EVM (Blockchain) implementation for syncing the transaction history of an address on
+///     Ethereum and compatible chains (Arbitrum, Base, Polygon).
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Usage:
+///     // Through an Etherscan-compatible indexer.
+///     let mut ethereum = Evm::new("<address>", 1, "https://eth.llamarpc.com").unwrap();
+///     ethereum.set_indexer("https://api.etherscan.io/v2/api", "<api key>");
+///     let handle = app.add_platform("Main Wallet", ethereum);
+///     app.sync_platform(handle).await.unwrap();
+/// 
+///     // Against a local node only, such as anvil.
+///     let local = Evm::new("<address>", 31337, "http://127.0.0.1:8545").unwrap();
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Notes:
+///     The chain id picks the platform name (e.g. "Arbitrum") and native coin (e.g. ETH) of the
+///     connection. The same address on two chains is two connections, and its accounts are kept
+///     apart by prefixing them with the chain id ("<chain id>:<address>"). Token accounts also
+///     carry the token contract ("<chain id>:<address>:<contract>"). Counterparties use the same
+///     format, so that transfers between two synced addresses line up.
+/// 
+///     With an indexer, native, internal and ERC-20 transfers are read from its 'txlist',
+///     'txlistinternal' and 'tokentx' actions. Without one, every block from the start block on
+///     is read from the node and ERC-20 transfers come from Transfer logs. This is only
+///     practical on a local or short chain, and does not see internal transfers, which require
+///     tracing.
+/// 
+///     Gas is only paid by the sender of a transaction, and includes the L1 data fee reported by
+///     rollups such as Base (the receipt's 'l1Fee', or the indexer's 'L1FeesPaid'). A
+///     transaction which moves no native coin (e.g. a contract call, or a failed transaction)
+///     records its gas as a transfer of 0 from the address to itself.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+
+use crate::database::entry::{CoinAccount, CoinTransfer, DatabaseEntry, PlatformConnection, PlatformConnectionData};
+use crate::error::CryptfolioError;
+use crate::platform::SyncClient;
+use async_trait::async_trait;
+use chrono::DateTime;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+
+const CHAINS: [(u64, &str, &str); 5] = [
+    (1, "Ethereum", "ETH"),
+    (42161, "Arbitrum", "ETH"),
+    (8453, "Base", "ETH"),
+    (137, "Polygon", "POL"),
+    (31337, "Anvil", "ETH"),
+];
+// keccak256("Transfer(address,address,uint256)")
+const TRANSFER_TOPIC: &str = "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+const DECIMALS_SELECTOR: &str = "0x313ce567";
+const SYMBOL_SELECTOR: &str = "0x95d89b41";
+const LOG_BLOCK_RANGE: u64 = 10000;
+const INDEXER_PAGE_SIZE: usize = 1000;
+
+#[derive(Clone)]
+struct Token {
+    contract: String,
+    symbol: String,
+    decimals: u32,
+}
+
+struct EvmTransfer {
+    id: String,
+    timestamp: i64,
+    from: String,
+    to: String,
+    token: Option<Token>,
+    amount: f64,
+    fee: f64,
+}
+
+pub struct Evm {
+    address: String,
+    chain_id: u64,
+    name: &'static str,
+    native: &'static str,
+    rpc_url: String,
+    indexer_url: Option<String>,
+    indexer_api_key: String,
+    start_block: u64,
+    client: reqwest::Client,
+}
+
+impl Evm {
+    pub fn new(address: &str, chain_id: u64, rpc_url: &str) -> Result<Evm, CryptfolioError> {
+        let chain = match CHAINS.iter().find(|x| x.0 == chain_id) {
+            Some(chain) => { chain },
+            None => { return Err(CryptfolioError::InvalidConnectionData(format!("Unsupported chain id: {}", chain_id))); }
+        };
+        if !Self::is_address(address) {
+            return Err(CryptfolioError::InvalidConnectionData(format!("Invalid address: {}", address)));
+        }
+        Ok(
+            Evm {
+                address: address.to_lowercase(),
+                chain_id: chain_id,
+                name: chain.1,
+                native: chain.2,
+                rpc_url: rpc_url.to_string(),
+                indexer_url: None,
+                indexer_api_key: String::new(),
+                start_block: 0,
+                client: reqwest::Client::new(),
+            }
+        )
+    }
+
+    /// Reads history from an Etherscan-compatible indexer rather than block by block.
+    pub fn set_indexer(&mut self, indexer_url: &str, api_key: &str) {
+        self.indexer_url = if indexer_url.is_empty() { None } else { Some(indexer_url.to_string()) };
+        self.indexer_api_key = api_key.to_string();
+    }
+
+    /// Skips every block before the first one the address was used in.
+    pub fn set_start_block(&mut self, start_block: u64) {
+        self.start_block = start_block;
+    }
+
+    pub fn get_address(&self) -> String {
+        self.address.to_string()
+    }
+
+    pub fn get_chain_id(&self) -> u64 {
+        return self.chain_id;
+    }
+
+    pub fn get_native_coin(&self) -> &str {
+        return self.native;
+    }
+
+    fn is_address(address: &str) -> bool {
+        address.len() == 42 && address.starts_with("0x") && address[2..].chars().all(|x| x.is_ascii_hexdigit())
+    }
+
+    fn get_account(&self, address: &str, token: &Option<Token>) -> String {
+        match token {
+            Some(token) => { format!("{}:{}:{}", self.chain_id, address, token.contract) },
+            None => { format!("{}:{}", self.chain_id, address) }
+        }
+    }
+
+    fn api_error(error: impl ToString) -> CryptfolioError {
+        CryptfolioError::EvmAPIError(error.to_string())
+    }
+
+    fn parse_u64(value: &Value) -> u64 {
+        match value.as_str() {
+            Some(hex) if hex.starts_with("0x") => { u64::from_str_radix(&hex[2..], 16).unwrap_or(0) },
+            Some(decimal) => { decimal.parse::<u64>().unwrap_or(0) },
+            None => { value.as_u64().unwrap_or(0) }
+        }
+    }
+
+    /// Parses a (possibly 256 bit) hex or decimal quantity. Anything beyond 128 bits is only
+    /// approximated, which is no worse than the f64 it ends up as.
+    fn parse_quantity(value: &str) -> f64 {
+        let (digits, radix) = match value.strip_prefix("0x") {
+            Some(hex) => { (hex.trim_start_matches('0'), 16) },
+            None => { (value, 10) }
+        };
+        if digits.is_empty() {
+            return 0.0;
+        }
+        match u128::from_str_radix(digits, radix) {
+            Ok(quantity) => { quantity as f64 },
+            Err(_) => { digits.chars().fold(0.0, |total, x| total * radix as f64 + x.to_digit(radix).unwrap_or(0) as f64) }
+        }
+    }
+
+    /// Decodes the ABI encoded result of symbol(), which older tokens return as a bytes32.
+    fn decode_string(result: &str) -> String {
+        let data = result.trim_start_matches("0x");
+        let bytes: Vec<u8> = (0..data.len() / 2)
+            .filter_map(|i| u8::from_str_radix(&data[i * 2..i * 2 + 2], 16).ok())
+            .collect();
+        let text = if bytes.len() == 32 {
+            bytes
+        } else if bytes.len() >= 64 {
+            let length = bytes[32..64]
+                .iter()
+                .fold(0usize, |total, x| total.saturating_mul(256).saturating_add(*x as usize))
+                .min(bytes.len() - 64);
+            bytes[64..64 + length].to_vec()
+        } else {
+            return String::new();
+        };
+        String::from_utf8_lossy(&text).trim_matches(char::from(0)).trim().to_uppercase()
+    }
+
+    fn format_timestamp(timestamp: i64) -> String {
+        DateTime::from_timestamp(timestamp, 0).unwrap_or_default().format("%Y-%m-%dT%H:%M:%SZ").to_string()
+    }
+
+    async fn call(&self, method: &str, params: Value) -> Result<Value, CryptfolioError> {
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let response = match self.client.post(self.rpc_url.as_str()).json(&request).send().await {
+            Ok(response) => { response },
+            Err(e) => { return Err(Self::api_error(e)); }
+        };
+        let response: Value = match response.json().await {
+            Ok(response) => { response },
+            Err(e) => { return Err(Self::api_error(e)); }
+        };
+        if let Some(error) = response.get("error") {
+            return Err(Self::api_error(format!("{} failed: {}", method, error)));
+        }
+        Ok(response["result"].clone())
+    }
+
+    async fn get_token(&self, contract: &str, tokens: &mut HashMap<String, Token>) -> Result<Token, CryptfolioError> {
+        if let Some(token) = tokens.get(contract) {
+            return Ok(token.clone());
+        }
+        // Both are optional in ERC-20, so a token which reverts on either still syncs: without a
+        // symbol it keeps its contract address as a name, and without decimals it uses 18.
+        let decimals = self.call("eth_call", json!([{ "to": contract, "data": DECIMALS_SELECTOR }, "latest"])).await.ok();
+        let symbol = self.call("eth_call", json!([{ "to": contract, "data": SYMBOL_SELECTOR }, "latest"])).await.ok();
+
+        let symbol = Self::decode_string(symbol.as_ref().and_then(|x| x.as_str()).unwrap_or(""));
+        let decimals = decimals.as_ref().and_then(|x| x.as_str()).filter(|x| x.len() > 2).unwrap_or("0x12");
+        let token = Token {
+            contract: contract.to_string(),
+            symbol: if symbol.is_empty() { contract.to_string() } else { symbol },
+            decimals: Self::parse_quantity(decimals) as u32,
+        };
+        tokens.insert(contract.to_string(), token.clone());
+        Ok(token)
+    }
+
+    async fn get_block_timestamp(&self, number: u64, timestamps: &mut HashMap<u64, i64>) -> Result<i64, CryptfolioError> {
+        if let Some(timestamp) = timestamps.get(&number) {
+            return Ok(*timestamp);
+        }
+        let block = self.call("eth_getBlockByNumber", json!([format!("{:#x}", number), false])).await?;
+        let timestamp = Self::parse_u64(&block["timestamp"]) as i64;
+        timestamps.insert(number, timestamp);
+        Ok(timestamp)
+    }
+
+    /// Reads every block for native transfers and the Transfer logs for ERC-20 transfers.
+    async fn fetch_from_node(&self) -> Result<Vec<EvmTransfer>, CryptfolioError> {
+        let mut transfers = Vec::<EvmTransfer>::new();
+        let mut timestamps = HashMap::<u64, i64>::new();
+        let mut tokens = HashMap::<String, Token>::new();
+        let latest = Self::parse_u64(&self.call("eth_blockNumber", json!([])).await?);
+        let empty = Vec::<Value>::new();
+
+        for number in self.start_block..=latest {
+            let block = self.call("eth_getBlockByNumber", json!([format!("{:#x}", number), true])).await?;
+            let timestamp = Self::parse_u64(&block["timestamp"]) as i64;
+            timestamps.insert(number, timestamp);
+            for transaction in block["transactions"].as_array().unwrap_or(&empty) {
+                let from = transaction["from"].as_str().unwrap_or("").to_lowercase();
+                let to = transaction["to"].as_str().unwrap_or("").to_lowercase();
+                if from != self.address && to != self.address {
+                    continue;
+                }
+                let hash = transaction["hash"].as_str().unwrap_or("").to_string();
+                let receipt = self.call("eth_getTransactionReceipt", json!([hash])).await?;
+                let success = Self::parse_u64(&receipt["status"]) == 1;
+
+                // Rollups add the cost of posting the transaction to L1 on top of L2 gas.
+                let gas = Self::parse_quantity(receipt["gasUsed"].as_str().unwrap_or("0x0"))
+                    * Self::parse_quantity(receipt["effectiveGasPrice"].as_str().unwrap_or("0x0"))
+                    + Self::parse_quantity(receipt["l1Fee"].as_str().unwrap_or("0x0"));
+                transfers.push(EvmTransfer {
+                    id: hash,
+                    timestamp: timestamp,
+                    from: from.to_string(),
+                    // Contract creations have no recipient.
+                    to: if to.is_empty() { receipt["contractAddress"].as_str().unwrap_or("").to_lowercase() } else { to },
+                    token: None,
+                    amount: if success { Self::parse_quantity(transaction["value"].as_str().unwrap_or("0x0")) / 1e18 } else { 0.0 },
+                    fee: if from == self.address { gas / 1e18 } else { 0.0 },
+                });
+            }
+        }
+
+        // The address is either the sender (topic 1) or the recipient (topic 2) of a Transfer.
+        let topic = format!("0x{:0>64}", &self.address[2..]);
+        let mut seen = HashSet::<String>::new();
+        for topics in [json!([TRANSFER_TOPIC, topic]), json!([TRANSFER_TOPIC, Value::Null, topic])] {
+            let mut from_block = self.start_block;
+            while from_block <= latest {
+                let to_block = (from_block + LOG_BLOCK_RANGE - 1).min(latest);
+                let filter = json!([{
+                    "fromBlock": format!("{:#x}", from_block),
+                    "toBlock": format!("{:#x}", to_block),
+                    "topics": topics,
+                }]);
+                for log in self.call("eth_getLogs", filter).await?.as_array().unwrap_or(&empty) {
+                    let log_topics: Vec<&str> = log["topics"].as_array().unwrap_or(&empty).iter().filter_map(|x| x.as_str()).collect();
+
+                    // ERC-721 transfers share the event, but index the token id as a fourth topic.
+                    if log_topics.len() != 3 {
+                        continue;
+                    }
+                    let hash = log["transactionHash"].as_str().unwrap_or("");
+                    let id = format!("{}:{}", hash, Self::parse_u64(&log["logIndex"]));
+                    if !seen.insert(id.to_string()) {
+                        continue;
+                    }
+                    let token = self.get_token(&log["address"].as_str().unwrap_or("").to_lowercase(), &mut tokens).await?;
+                    let timestamp = self.get_block_timestamp(Self::parse_u64(&log["blockNumber"]), &mut timestamps).await?;
+                    transfers.push(EvmTransfer {
+                        id: id,
+                        timestamp: timestamp,
+                        from: format!("0x{}", &log_topics[1][26..]).to_lowercase(),
+                        to: format!("0x{}", &log_topics[2][26..]).to_lowercase(),
+                        amount: Self::parse_quantity(log["data"].as_str().unwrap_or("0x0")) / 10f64.powi(token.decimals as i32),
+                        token: Some(token),
+                        fee: 0.0,
+                    });
+                }
+                from_block = to_block + 1;
+            }
+        }
+        Ok(transfers)
+    }
+
+    /// Every page of an account action of an Etherscan-compatible indexer.
+    async fn fetch_indexer_action(&self, indexer_url: &str, action: &str) -> Result<Vec<Value>, CryptfolioError> {
+        let mut results = Vec::<Value>::new();
+        let mut page: usize = 1;
+        loop {
+            let query = [
+                ("chainid", self.chain_id.to_string()),
+                ("module", "account".to_string()),
+                ("action", action.to_string()),
+                ("address", self.address.to_string()),
+                ("startblock", self.start_block.to_string()),
+                ("sort", "asc".to_string()),
+                ("page", page.to_string()),
+                ("offset", INDEXER_PAGE_SIZE.to_string()),
+                ("apikey", self.indexer_api_key.to_string()),
+            ];
+            let response = match self.client.get(indexer_url).query(&query).send().await {
+                Ok(response) => { response },
+                Err(e) => { return Err(Self::api_error(e)); }
+            };
+            let response: Value = match response.json().await {
+                Ok(response) => { response },
+                Err(e) => { return Err(Self::api_error(e)); }
+            };
+
+            // An empty history is reported as an error with no results, rather than as an empty list.
+            let entries = match response["result"].as_array() {
+                Some(entries) => { entries.clone() },
+                None if response["message"].as_str().unwrap_or("").starts_with("No transactions") => { Vec::<Value>::new() },
+                None => { return Err(Self::api_error(format!("{} failed: {}", action, response["result"]))); }
+            };
+            let count = entries.len();
+            results.extend(entries);
+            if count < INDEXER_PAGE_SIZE {
+                break;
+            }
+            page += 1;
+        }
+        Ok(results)
+    }
+
+    async fn fetch_from_indexer(&self, indexer_url: &str) -> Result<Vec<EvmTransfer>, CryptfolioError> {
+        let mut transfers = Vec::<EvmTransfer>::new();
+        for transaction in self.fetch_indexer_action(indexer_url, "txlist").await? {
+            let from = transaction["from"].as_str().unwrap_or("").to_lowercase();
+            let success = transaction["isError"].as_str() != Some("1");
+            // Rollups report the fee for posting the transaction to L1 separately.
+            let gas = Self::parse_quantity(transaction["gasUsed"].as_str().unwrap_or("0"))
+                * Self::parse_quantity(transaction["gasPrice"].as_str().unwrap_or("0"))
+                + Self::parse_quantity(transaction["L1FeesPaid"].as_str().unwrap_or("0"));
+            let to = transaction["to"].as_str().unwrap_or("").to_lowercase();
+            transfers.push(EvmTransfer {
+                id: transaction["hash"].as_str().unwrap_or("").to_string(),
+                timestamp: Self::parse_u64(&transaction["timeStamp"]) as i64,
+                to: if to.is_empty() { transaction["contractAddress"].as_str().unwrap_or("").to_lowercase() } else { to },
+                token: None,
+                amount: if success { Self::parse_quantity(transaction["value"].as_str().unwrap_or("0")) / 1e18 } else { 0.0 },
+                fee: if from == self.address { gas / 1e18 } else { 0.0 },
+                from: from,
+            });
+        }
+
+        // Contracts sending the native coin (e.g. unwrapping WETH) do so through internal transactions.
+        for transaction in self.fetch_indexer_action(indexer_url, "txlistinternal").await? {
+            if transaction["isError"].as_str() == Some("1") {
+                continue;
+            }
+            transfers.push(EvmTransfer {
+                // The trace id (e.g. '0_1') stays the same between syncs, unlike a position in the list.
+                id: format!("{}:internal:{}", transaction["hash"].as_str().unwrap_or(""), transaction["traceId"].as_str().unwrap_or("")),
+                timestamp: Self::parse_u64(&transaction["timeStamp"]) as i64,
+                from: transaction["from"].as_str().unwrap_or("").to_lowercase(),
+                to: transaction["to"].as_str().unwrap_or("").to_lowercase(),
+                token: None,
+                amount: Self::parse_quantity(transaction["value"].as_str().unwrap_or("0")) / 1e18,
+                fee: 0.0,
+            });
+        }
+
+        for transaction in self.fetch_indexer_action(indexer_url, "tokentx").await? {
+            let contract = transaction["contractAddress"].as_str().unwrap_or("").to_lowercase();
+            let symbol = transaction["tokenSymbol"].as_str().unwrap_or("").to_uppercase();
+            let decimals = Self::parse_u64(&transaction["tokenDecimal"]) as u32;
+            transfers.push(EvmTransfer {
+                id: format!("{}:{}", transaction["hash"].as_str().unwrap_or(""), Self::parse_u64(&transaction["logIndex"])),
+                timestamp: Self::parse_u64(&transaction["timeStamp"]) as i64,
+                from: transaction["from"].as_str().unwrap_or("").to_lowercase(),
+                to: transaction["to"].as_str().unwrap_or("").to_lowercase(),
+                amount: Self::parse_quantity(transaction["value"].as_str().unwrap_or("0")) / 10f64.powi(decimals as i32),
+                token: Some(Token {
+                    symbol: if symbol.is_empty() { contract.to_string() } else { symbol },
+                    contract: contract,
+                    decimals: decimals,
+                }),
+                fee: 0.0,
+            });
+        }
+        Ok(transfers)
+    }
+
+    fn process_transfer(&self, transfer: EvmTransfer) -> Option<Box<dyn DatabaseEntry + Send>> {
+        let coin = match &transfer.token {
+            Some(token) => { token.symbol.to_string() },
+            None => { self.native.to_string() }
+        };
+        let date = Self::format_timestamp(transfer.timestamp);
+        let account = self.get_account(self.address.as_str(), &transfer.token);
+
+        // Gas spent without moving any of the native coin.
+        if transfer.amount == 0.0 {
+            if transfer.fee == 0.0 {
+                return None;
+            }
+            return Some(Box::new(CoinTransfer::new(transfer.id, date, account.to_string(), account, coin, 0.0, transfer.fee)));
+        }
+        let origin = self.get_account(transfer.from.as_str(), &transfer.token);
+        let destination = self.get_account(transfer.to.as_str(), &transfer.token);
+        Some(Box::new(CoinTransfer::new(transfer.id, date, origin, destination, coin, transfer.amount, transfer.fee)))
+    }
+}
+
+#[async_trait]
+impl SyncClient for Evm {
+    fn get_name(&self) -> &str {
+        self.name
+    }
+
+    fn get_connection(&self, nickname: &String) -> PlatformConnection {
+        return PlatformConnection::new(
+            nickname.to_string(),
+            self.name.to_string(),
+            vec![
+                PlatformConnectionData { key: "Wallet Address".to_string(), value: self.get_address() },
+                PlatformConnectionData { key: "Chain ID".to_string(), value: self.chain_id.to_string() },
+                PlatformConnectionData { key: "RPC URL".to_string(), value: self.rpc_url.to_string() },
+                PlatformConnectionData { key: "Indexer URL".to_string(), value: self.indexer_url.clone().unwrap_or_default() },
+                PlatformConnectionData { key: "Indexer API Key".to_string(), value: self.indexer_api_key.to_string() },
+                PlatformConnectionData { key: "Start Block".to_string(), value: self.start_block.to_string() },
+            ]
+        );
+    }
+
+    async fn sync(&self) -> Result<Vec<Box<dyn DatabaseEntry + Send>>, CryptfolioError> {
+        let mut result = Vec::<Box<dyn DatabaseEntry + Send>>::new();
+        let transfers = match &self.indexer_url {
+            Some(indexer_url) => { self.fetch_from_indexer(indexer_url.as_str()).await? },
+            None => { self.fetch_from_node().await? }
+        };
+
+        result.push(Box::new(CoinAccount::new(
+            self.get_account(self.address.as_str(), &None),
+            self.native.to_string(),
+            self.name.to_string()
+        )));
+        let mut contracts = HashSet::<String>::new();
+        for transfer in transfers.iter() {
+            if let Some(token) = &transfer.token {
+                if contracts.insert(token.contract.to_string()) {
+                    result.push(Box::new(CoinAccount::new(
+                        self.get_account(self.address.as_str(), &transfer.token),
+                        token.symbol.to_string(),
+                        self.name.to_string()
+                    )));
+                }
+            }
+        }
+
+        for transfer in transfers {
+            if let Some(entry) = self.process_transfer(transfer) {
+                result.push(entry);
+            }
+        }
+        Ok(result)
+    }
+}
\ No newline at end of file
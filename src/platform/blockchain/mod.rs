@@ -1,2 +1,6 @@
+mod bitcoin;
+mod evm;
 mod solana;
+pub use bitcoin::{Bitcoin, BitcoinBackend};
+pub use evm::Evm;
 pub use solana::Solana;
\ No newline at end of file
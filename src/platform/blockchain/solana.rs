@@ -10,36 +10,182 @@
 /// ///////////////////////////////////////////////////////////////////////////////////////////////
 /// 
 /// Usage:
+///     let mut solana = Solana::with_config(
+///         "<wallet address>".to_string(), "http://127.0.0.1:8899".to_string(), "confirmed", 500
+///     ).unwrap();
+///     solana.add_token("<mint address>", "PYTH");
+///     let handle = app.add_platform("My Wallet", solana);
+///     app.sync_platform(handle).await.unwrap();
 /// 
 /// ///////////////////////////////////////////////////////////////////////////////////////////////
 /// 
 /// Notes:
+///     The RPC URL, commitment and page size are kept with the connection. Signatures are
+///     fetched a page at a time, newest first, until either the full history or the optional
+///     'until' signature is reached. After each sync, the newest signature synced becomes the
+///     'until' signature kept with the connection, so that the next sync only fetches what is
+///     new. Solana::new() uses the public mainnet endpoint, which is heavily rate-limited.
+/// 
+///     Every signature of the wallet becomes a CoinTransfer of SOL. Transfers out of the wallet
+///     go to the account which received the most lamports, and transfers into the wallet come
+///     from the account which lost the most. Balances are compared in lamports and only
+///     converted to SOL once the amount is known, so no precision is lost along the way.
+/// 
+///     The network fee is only attributed to the wallet when it is the fee payer (the first
+///     account of the transaction). A fee paid without any other change in balance (e.g. a
+///     failed transaction) is recorded as a transfer of 0 SOL from the wallet to itself.
+/// 
+///     SPL tokens are held in token accounts owned by the wallet, each of which is synced as a
+///     CoinAccount and searched for signatures of its own, as tokens sent to an existing token
+///     account never mention the wallet itself. Token transfers come from the difference
+///     between the pre and post token balances of a transaction. Mints are named through the
+///     token list (see add_token()); mints which are not listed keep their address as a name.
+///     Wrapped SOL is skipped, as its lamports are already counted once unwrapped.
+/// 
+///     Stake accounts whose stake authority is the wallet are synced as SOL accounts of their
+///     own, so that delegating or withdrawing stake is a transfer within the wallet. Closed
+///     stake accounts no longer belong to the stake program, so stake accounts are also taken
+///     from the stake instructions of the wallet's transactions, and kept with the connection.
+///     Inflation rewards are credited to stake accounts directly and never appear in a
+///     transaction, so they are fetched for every epoch the stake was active and dated by the
+///     block time of the slot they were paid in. The next epoch to fetch is kept with the
+///     connection, so each sync only fetches the epochs which have passed since the last one.
+///     Rewards are stored with a price of 0.0, to be priced afterwards from a price source (see
+///     CryptfolioApp::fill_reward_prices).
+/// 
+///     A transaction in which the wallet gives up exactly one asset (SOL or a token) for exactly
+///     one other is a swap, and becomes an order of one coin quoted in the other (e.g. BONK-SOL)
+///     instead of two transfers, so that both sides are valued by the price source of the app.
+///     The exchange (e.g. Jupiter, Raydium) and the network fee are kept in the description.
+///     When SOL is one of the two, the transaction must also call a known DEX program, as the
+///     rent paid or refunded for a token account moves SOL alongside a plain token transfer.
+///     The fee is charged to the order when SOL is a side of the swap, and is otherwise still
+///     deducted as a SOL transfer, as the fee of an order is paid in its quote coin.
 /// 
 /// ///////////////////////////////////////////////////////////////////////////////////////////////
 
 use crate::error::CryptfolioError;
-use crate::database::entry::{DatabaseEntry, PlatformConnection, PlatformConnectionData};
+use crate::database::entry::{
+    CoinAccount, CoinOrder, CoinReward, CoinTransfer, DatabaseEntry, PlatformConnection, PlatformConnectionData,
+};
 use crate::platform::SyncClient;
 use async_trait::async_trait;
-use solana_client::rpc_client::RpcClient;
+use chrono::DateTime;
+use solana_account_decoder::{UiAccountData, UiAccountEncoding};
+use solana_client::rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient};
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcTransactionConfig};
+use solana_client::rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType};
+use solana_client::rpc_request::TokenAccountsFilter;
+use solana_client::rpc_response::RpcConfirmedTransactionStatusWithSignature;
 use solana_program::pubkey::Pubkey;
 use solana_program::native_token::LAMPORTS_PER_SOL;
+use solana_program::stake::state::StakeStateV2;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::epoch_schedule::EpochSchedule;
 use solana_sdk::signature::Signature;
-use solana_transaction_status::UiTransactionEncoding;
-use solana_transaction_status::UiTransactionStatusMeta;
+use solana_transaction_status::{
+    EncodedConfirmedTransactionWithStatusMeta, EncodedTransaction, UiLoadedAddresses, UiMessage, UiTransactionEncoding,
+    UiTransactionStatusMeta, UiTransactionTokenBalance,
+};
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
 
-pub struct Solana { 
+const DEFAULT_RPC_URL: &str = "https://api.mainnet-beta.solana.com";
+const MAX_PAGE_SIZE: usize = 1000;
+const TOKEN_PROGRAM: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+const STAKE_PROGRAM: &str = "Stake11111111111111111111111111111111111111";
+// Offset of the stake authority within a stake account (enum tag, then the rent exempt reserve).
+const STAKER_OFFSET: usize = 12;
+const WRAPPED_SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+const DEFAULT_TOKENS: [(&str, &str); 7] = [
+    ("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v", "USDC"),
+    ("Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB", "USDT"),
+    ("JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN", "JUP"),
+    ("MNDEFzGvMt87ueuHvVU9VcTqsAP5b3fTGPsHuuPA5ey", "MNDE"),
+    ("DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263", "BONK"),
+    ("mSoLzYCxHdYgdzU16g5QSh3i5K3z3KZK7ytfqcJm7So", "MSOL"),
+    ("J1toso1uCk3RLmjorhTtrVwY9HJ7X8V9yYac6Y7kGCPn", "JITOSOL"),
+];
+
+const STABLECOINS: [&str; 2] = ["USDC", "USDT"];
+const DEX_PROGRAMS: [(&str, &str); 6] = [
+    ("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4", "Jupiter"),
+    ("JUP4Fb2cqiRUcaTHdrPC8h2gNsA2ETXiPDD33WcGuJB", "Jupiter"),
+    ("JUP3c2Uh3WA4Ng34tw6kPd2G4C5BB21Xo36Je1s32Ph", "Jupiter"),
+    ("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8", "Raydium"),
+    ("CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK", "Raydium"),
+    ("whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc", "Orca"),
+];
+const INFRASTRUCTURE_PROGRAMS: [&str; 5] = [
+    "11111111111111111111111111111111",
+    "ComputeBudget111111111111111111111111111111",
+    "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL",
+    "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr",
+    TOKEN_PROGRAM,
+];
+
+struct TokenBalanceChange {
+    account: String,
+    mint: String,
+    owner: String,
+    decimals: u8,
+    pre: i128,
+    post: i128,
+}
+
+impl TokenBalanceChange {
+    fn get_amount(&self) -> f64 {
+        (self.post - self.pre).abs() as f64 / 10f64.powi(self.decimals as i32)
+    }
+}
+
+pub struct Solana {
     wallet: String,
+    rpc_url: String,
+    commitment: CommitmentConfig,
+    page_size: usize,
+    until: Mutex<Option<String>>,
     client: RpcClient,
+    tokens: HashMap<String, String>,
+    stake_accounts: Mutex<HashMap<String, (u64, u64)>>,
+    reward_epoch: AtomicU64,
 }
 
 impl Solana {
     pub fn new(wallet: String) -> Result<Solana, CryptfolioError> {
+        Solana::with_config(wallet, DEFAULT_RPC_URL.to_string(), "finalized", MAX_PAGE_SIZE)
+    }
+
+    /// Connects through any RPC endpoint, such as a private node, a local test validator
+    /// (http://127.0.0.1:8899) or a mock JSON-RPC server.
+    pub fn with_config(wallet: String, rpc_url: String, commitment: &str, page_size: usize) -> Result<Solana, CryptfolioError> {
+        // Transactions can only be fetched once they have been confirmed.
+        let commitment = match CommitmentConfig::from_str(commitment) {
+            Ok(commitment) if commitment.is_at_least_confirmed() => { commitment },
+            _ => {
+                return Err(CryptfolioError::InvalidConnectionData(
+                    format!("Commitment must be 'confirmed' or 'finalized' (got '{}')", commitment)
+                ));
+            }
+        };
+        if page_size == 0 || page_size > MAX_PAGE_SIZE {
+            return Err(CryptfolioError::InvalidConnectionData(
+                format!("Page size must be between 1 and {} (got {})", MAX_PAGE_SIZE, page_size)
+            ));
+        }
         Ok(
             Solana {
                 wallet: wallet,
-                client: RpcClient::new("https://api.mainnet-beta.solana.com"),
+                client: RpcClient::new_with_commitment(rpc_url.to_string(), commitment),
+                rpc_url: rpc_url,
+                commitment: commitment,
+                page_size: page_size,
+                until: Mutex::new(None),
+                tokens: DEFAULT_TOKENS.iter().map(|x| (x.0.to_string(), x.1.to_string())).collect(),
+                stake_accounts: Mutex::new(HashMap::<String, (u64, u64)>::new()),
+                reward_epoch: AtomicU64::new(0),
             }
         )
     }
@@ -48,13 +194,537 @@ impl Solana {
         self.wallet.to_string()
     }
 
-    // fn process_transaction(&self, transaction: UiTransactionStatusMeta) -> Box<dyn DatabaseEntry + Send> {
+    pub fn get_rpc_url(&self) -> &str {
+        return self.rpc_url.as_str();
+    }
+
+    pub fn get_commitment(&self) -> String {
+        self.commitment.commitment.to_string()
+    }
+
+    pub fn get_page_size(&self) -> usize {
+        return self.page_size;
+    }
+
+    pub fn get_until(&self) -> Option<String> {
+        self.until.lock().unwrap().clone()
+    }
+
+    /// Stops syncing at a signature (exclusive), such as the most recent one already synced.
+    pub fn set_until(&self, signature: Option<String>) {
+        *self.until.lock().unwrap() = signature;
+    }
+
+    pub fn get_tokens(&self) -> &HashMap<String, String> {
+        return &self.tokens;
+    }
+
+    /// Names the token of a mint, replacing any previous name.
+    pub fn add_token(&mut self, mint: &str, symbol: &str) {
+        self.tokens.insert(mint.to_string(), symbol.to_uppercase());
+    }
 
-    // }
+    /// Every stake account of the wallet seen so far, along with the first and last epoch it
+    /// was active.
+    pub fn get_stake_accounts(&self) -> Vec<(String, u64, u64)> {
+        let mut stake_accounts: Vec<(String, u64, u64)> = self.stake_accounts
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|x| (x.0.to_string(), x.1.0, x.1.1))
+            .collect();
+        stake_accounts.sort();
+        stake_accounts
+    }
+
+    /// Adds a stake account (or widens the epochs it was active over), such as one which was
+    /// closed before the wallet was first synced.
+    pub fn add_stake_account(&self, address: &str, activation_epoch: u64, deactivation_epoch: u64) {
+        let mut stake_accounts = self.stake_accounts.lock().unwrap();
+        let epochs = stake_accounts.entry(address.to_string()).or_insert((activation_epoch, deactivation_epoch));
+        *epochs = (epochs.0.min(activation_epoch), epochs.1.max(deactivation_epoch));
+    }
+
+    /// The first epoch whose staking rewards have not been synced yet.
+    pub fn get_reward_epoch(&self) -> u64 {
+        self.reward_epoch.load(Ordering::SeqCst)
+    }
+
+    pub fn set_reward_epoch(&self, epoch: u64) {
+        self.reward_epoch.store(epoch, Ordering::SeqCst);
+    }
+
+    fn get_symbol(&self, mint: &str) -> String {
+        self.tokens.get(mint).map(|x| x.to_string()).unwrap_or(mint.to_string())
+    }
 
-    fn process_transaction(&self, transaction: UiTransactionStatusMeta) {
-        println!("Amount: {}", (transaction.pre_balances[0].wrapping_sub(transaction.post_balances[0])) / LAMPORTS_PER_SOL);
-        println!("Fee: {}", transaction.fee / LAMPORTS_PER_SOL);
+    fn to_sol(lamports: i128) -> f64 {
+        lamports as f64 / LAMPORTS_PER_SOL as f64
+    }
+
+    fn format_block_time(block_time: i64) -> String {
+        DateTime::from_timestamp(block_time, 0).unwrap_or_default().format("%Y-%m-%dT%H:%M:%SZ").to_string()
+    }
+
+    /// Versioned (v0) transactions also load accounts from address lookup tables, which are
+    /// indexed after the static keys of the message, writable ones first.
+    fn get_account_keys(transaction: &EncodedTransaction, meta: &UiTransactionStatusMeta) -> Result<Vec<String>, CryptfolioError> {
+        match transaction {
+            EncodedTransaction::Json(transaction) => {
+                match &transaction.message {
+                    UiMessage::Raw(message) => {
+                        let mut account_keys = message.account_keys.clone();
+                        if let Some(loaded_addresses) = Option::<&UiLoadedAddresses>::from(meta.loaded_addresses.as_ref()) {
+                            account_keys.extend(loaded_addresses.writable.iter().cloned());
+                            account_keys.extend(loaded_addresses.readonly.iter().cloned());
+                        }
+                        Ok(account_keys)
+                    },
+                    UiMessage::Parsed(message) => { Ok(message.account_keys.iter().map(|x| x.pubkey.to_string()).collect()) }
+                }
+            },
+            _ => { Err(CryptfolioError::SolanaAPIError("Transaction was not JSON encoded".to_string())) }
+        }
+    }
+
+    /// Every signature of an address, newest first, fetched a page at a time.
+    fn get_signatures(&self, address: &Pubkey) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>, CryptfolioError> {
+        let until = match &self.get_until() {
+            Some(until) => {
+                match Signature::from_str(until.as_str()) {
+                    Ok(until) => { Some(until) },
+                    Err(e) => { return Err(CryptfolioError::InvalidConnectionData(e.to_string())); }
+                }
+            },
+            None => { None }
+        };
+
+        let mut signatures = Vec::<RpcConfirmedTransactionStatusWithSignature>::new();
+        let mut before: Option<Signature> = None;
+        loop {
+            let config = GetConfirmedSignaturesForAddress2Config {
+                before: before,
+                until: until,
+                limit: Some(self.page_size),
+                commitment: Some(self.commitment),
+            };
+            let mut page = match self.client.get_signatures_for_address_with_config(address, config) {
+                Ok(page) => { page },
+                Err(e) => { return Err(CryptfolioError::SolanaAPIError(e.to_string())); }
+            };
+
+            // A short page is the last one.
+            let count = page.len();
+            before = page.last().and_then(|x| Signature::from_str(x.signature.as_str()).ok());
+            signatures.append(&mut page);
+            if count < self.page_size || before.is_none() {
+                break;
+            }
+        }
+        Ok(signatures)
+    }
+
+    /// The stake accounts of a transaction, which are the first account of every instruction to the
+    /// stake program (e.g. delegating, deactivating or withdrawing stake).
+    fn get_stake_instruction_accounts(transaction: &EncodedTransaction, account_keys: &Vec<String>) -> Vec<String> {
+        let mut stake_accounts = Vec::<String>::new();
+        if let EncodedTransaction::Json(transaction) = transaction {
+            if let UiMessage::Raw(message) = &transaction.message {
+                for instruction in &message.instructions {
+                    if account_keys.get(instruction.program_id_index as usize).map(|x| x.as_str()) != Some(STAKE_PROGRAM) {
+                        continue;
+                    }
+                    if let Some(account) = instruction.accounts.first().and_then(|x| account_keys.get(*x as usize)) {
+                        if !stake_accounts.contains(account) {
+                            stake_accounts.push(account.to_string());
+                        }
+                    }
+                }
+            }
+        }
+        stake_accounts
+    }
+
+    /// Every program invoked by the top level instructions of a transaction.
+    fn get_programs(transaction: &EncodedTransaction, account_keys: &Vec<String>) -> Vec<String> {
+        let mut programs = Vec::<String>::new();
+        if let EncodedTransaction::Json(transaction) = transaction {
+            if let UiMessage::Raw(message) = &transaction.message {
+                for instruction in &message.instructions {
+                    if let Some(program) = account_keys.get(instruction.program_id_index as usize) {
+                        if !programs.contains(program) {
+                            programs.push(program.to_string());
+                        }
+                    }
+                }
+            }
+        }
+        programs
+    }
+
+    /// The name of the exchange behind a swap, or the address of its program when unknown.
+    fn get_swap_program(programs: &Vec<String>) -> String {
+        for program in programs {
+            if let Some(dex) = DEX_PROGRAMS.iter().find(|x| x.0 == program.as_str()) {
+                return dex.1.to_string();
+            }
+        }
+        programs
+            .iter()
+            .find(|x| !INFRASTRUCTURE_PROGRAMS.contains(&x.as_str()))
+            .map(|x| x.to_string())
+            .unwrap_or("Unknown".to_string())
+    }
+
+    fn process_transaction(&self, signature: &str, transaction: EncodedConfirmedTransactionWithStatusMeta, block_time: Option<i64>, token_accounts: &HashSet<String>) -> Result<Vec<Box<dyn DatabaseEntry + Send>>, CryptfolioError> {
+        let mut result = Vec::<Box<dyn DatabaseEntry + Send>>::new();
+        let date = match transaction.block_time.or(block_time) {
+            Some(block_time) => { Self::format_block_time(block_time) },
+            None => { return Err(CryptfolioError::SolanaAPIError(format!("No block time for transaction '{}'", signature))); }
+        };
+        let meta = match transaction.transaction.meta {
+            Some(meta) => { meta },
+            None => { return Err(CryptfolioError::SolanaAPIError(format!("No status for transaction '{}'", signature))); }
+        };
+        let account_keys = Self::get_account_keys(&transaction.transaction.transaction, &meta)?;
+        let programs = Self::get_programs(&transaction.transaction.transaction, &account_keys);
+        let changes = Self::get_token_balance_changes(&account_keys, &meta);
+        let owned: Vec<&TokenBalanceChange> = changes
+            .iter()
+            .filter(|x| x.post != x.pre && x.mint != WRAPPED_SOL_MINT)
+            .filter(|x| x.owner == self.wallet || token_accounts.contains(&x.account))
+            .collect();
+
+        // The wallet may only appear through one of its token accounts.
+        let index = account_keys.iter().position(|x| *x == self.wallet);
+        let deltas: Vec<i128> = meta.pre_balances
+            .iter()
+            .zip(meta.post_balances.iter())
+            .map(|(pre, post)| *post as i128 - *pre as i128)
+            .collect();
+
+        // Only the fee payer is charged the network fee.
+        let mut fee: i128 = if index == Some(0) { meta.fee as i128 } else { 0 };
+        let mut amount = index.map_or(0, |x| deltas[x] + fee);
+
+        // A swap gives up one asset for another. When SOL is a side of the swap, the wallet's whole
+        // change in SOL (rent for a token account opened along the way included) is counted as that
+        // side, and the network fee is charged to the order. Otherwise any change in SOL (e.g. rent)
+        // is still a transfer, and the fee is charged as one. A token moving alongside SOL is only a
+        // swap through a DEX, as claiming a token (paying rent) or closing its account after sending
+        // it away (refunding rent) moves SOL as well.
+        let is_dex = programs.iter().any(|x| DEX_PROGRAMS.iter().any(|dex| dex.0 == x.as_str()));
+        let sold: Vec<&&TokenBalanceChange> = owned.iter().filter(|x| x.post < x.pre).collect();
+        let bought: Vec<&&TokenBalanceChange> = owned.iter().filter(|x| x.post > x.pre).collect();
+        let swap = match (sold.as_slice(), bought.as_slice()) {
+            ([sold], [bought]) if sold.mint != bought.mint => {
+                Some((self.get_symbol(sold.mint.as_str()), sold.get_amount(), self.get_symbol(bought.mint.as_str()), bought.get_amount()))
+            },
+            ([], [bought]) if is_dex && amount < 0 => {
+                let swap = ("SOL".to_string(), Self::to_sol(-amount), self.get_symbol(bought.mint.as_str()), bought.get_amount());
+                amount = 0;
+                Some(swap)
+            },
+            ([sold], []) if is_dex && amount > 0 => {
+                let swap = (self.get_symbol(sold.mint.as_str()), sold.get_amount(), "SOL".to_string(), Self::to_sol(amount));
+                amount = 0;
+                Some(swap)
+            },
+            _ => { None }
+        };
+        match swap {
+            Some((sold_coin, sold_amount, bought_coin, bought_amount)) => {
+                let description = format!("{} swap (network fee: {} SOL)", Self::get_swap_program(&programs), Self::to_sol(fee));
+                let order_fee = if sold_coin == "SOL" || bought_coin == "SOL" { Self::to_sol(std::mem::take(&mut fee)) } else { 0.0 };
+                result.push(Self::process_swap(signature, date.as_str(), sold_coin, sold_amount, bought_coin, bought_amount, order_fee, description));
+            },
+            None => {
+                result.append(&mut self.process_token_balances(signature, date.as_str(), &changes, &owned));
+            }
+        }
+
+        if index.is_none() {
+            return Ok(result);
+        }
+        let index = index.unwrap();
+        if amount < 0 {
+            let counterparty = Self::get_counterparty(&account_keys, &deltas, index, true);
+            result.push(Box::new(CoinTransfer::new(
+                signature.to_string(),
+                date,
+                self.wallet.to_string(),
+                counterparty,
+                "SOL".to_string(),
+                Self::to_sol(-amount),
+                Self::to_sol(fee)
+            )));
+        } else if amount > 0 {
+            let counterparty = Self::get_counterparty(&account_keys, &deltas, index, false);
+            result.push(Box::new(CoinTransfer::new(
+                signature.to_string(),
+                date.to_string(),
+                counterparty,
+                self.wallet.to_string(),
+                "SOL".to_string(),
+                Self::to_sol(amount),
+                0.0
+            )));
+
+            // A wallet can pay the fee of a transaction which sends it SOL (e.g. withdrawing stake).
+            if fee > 0 {
+                result.push(Box::new(CoinTransfer::new(
+                    format!("{}:fee", signature),
+                    date,
+                    self.wallet.to_string(),
+                    self.wallet.to_string(),
+                    "SOL".to_string(),
+                    0.0,
+                    Self::to_sol(fee)
+                )));
+            }
+        } else if fee > 0 {
+            result.push(Box::new(CoinTransfer::new(
+                signature.to_string(),
+                date,
+                self.wallet.to_string(),
+                self.wallet.to_string(),
+                "SOL".to_string(),
+                0.0,
+                Self::to_sol(fee)
+            )));
+        }
+        Ok(result)
+    }
+
+    /// A swap becomes a single order of one coin quoted in the other, as with Kraken's coin pairs,
+    /// so that the other side is valued through the price source of the app. SOL is quoted
+    /// whenever it is a side (as the fee is paid in SOL), then stablecoins, then the coin sold.
+    fn process_swap(signature: &str, date: &str, sold_coin: String, sold_amount: f64, bought_coin: String, bought_amount: f64, fee: f64, description: String) -> Box<dyn DatabaseEntry + Send> {
+        let quote_bought = bought_coin == "SOL" || (sold_coin != "SOL" && STABLECOINS.contains(&bought_coin.as_str()));
+        let mut order = if quote_bought {
+            CoinOrder::new(
+                format!("{}:swap", signature),
+                date.to_string(),
+                format!("{}-{}", sold_coin, bought_coin),
+                bought_amount / sold_amount,
+                sold_amount,
+                fee,
+                "sell".to_string(),
+                "Solana".to_string()
+            )
+        } else {
+            CoinOrder::new(
+                format!("{}:swap", signature),
+                date.to_string(),
+                format!("{}-{}", bought_coin, sold_coin),
+                sold_amount / bought_amount,
+                bought_amount,
+                fee,
+                "buy".to_string(),
+                "Solana".to_string()
+            )
+        };
+        order.set_description(description);
+        Box::new(order)
+    }
+
+    /// Token accounts may be opened or closed by the transaction, so may only have one balance.
+    fn get_token_balance_changes(account_keys: &Vec<String>, meta: &UiTransactionStatusMeta) -> Vec<TokenBalanceChange> {
+        let mut changes = Vec::<TokenBalanceChange>::new();
+        let pre_balances = Option::<&Vec<UiTransactionTokenBalance>>::from(meta.pre_token_balances.as_ref());
+        let post_balances = Option::<&Vec<UiTransactionTokenBalance>>::from(meta.post_token_balances.as_ref());
+        let balances = pre_balances.into_iter().flatten().map(|x| (x, true))
+            .chain(post_balances.into_iter().flatten().map(|x| (x, false)));
+        for (balance, is_pre) in balances {
+            let account = match account_keys.get(balance.account_index as usize) {
+                Some(account) => { account.to_string() },
+                None => { continue; }
+            };
+            let amount = balance.ui_token_amount.amount.parse::<i128>().unwrap_or(0);
+            let index = match changes.iter().position(|x| x.account == account) {
+                Some(index) => { index },
+                None => {
+                    changes.push(TokenBalanceChange {
+                        account: account,
+                        mint: balance.mint.to_string(),
+                        owner: Option::<String>::from(balance.owner.clone()).unwrap_or_default(),
+                        decimals: balance.ui_token_amount.decimals,
+                        pre: 0,
+                        post: 0,
+                    });
+                    changes.len() - 1
+                }
+            };
+            if is_pre { changes[index].pre = amount; } else { changes[index].post = amount; }
+        }
+        changes
+    }
+
+    fn process_token_balances(&self, signature: &str, date: &str, changes: &Vec<TokenBalanceChange>, owned: &Vec<&TokenBalanceChange>) -> Vec<Box<dyn DatabaseEntry + Send>> {
+        let mut result = Vec::<Box<dyn DatabaseEntry + Send>>::new();
+        for change in owned {
+            let delta = change.post - change.pre;
+
+            // The counterparty holds the same mint, and moved the most in the opposite direction.
+            let counterparty = changes
+                .iter()
+                .filter(|x| x.mint == change.mint && x.account != change.account)
+                .map(|x| (x.account.to_string(), (x.post - x.pre) * -delta.signum()))
+                .filter(|x| x.1 > 0)
+                .max_by_key(|x| x.1)
+                .map(|x| x.0)
+                .unwrap_or("Unknown".to_string());
+            let (origin, destination) = if delta < 0 {
+                (change.account.to_string(), counterparty)
+            } else {
+                (counterparty, change.account.to_string())
+            };
+            result.push(Box::new(CoinTransfer::new(
+                format!("{}:{}", signature, change.mint),
+                date.to_string(),
+                origin,
+                destination,
+                self.get_symbol(change.mint.as_str()),
+                change.get_amount(),
+                0.0
+            )));
+        }
+        result
+    }
+
+    /// The wallet's token accounts, along with the mint each one holds.
+    fn get_token_accounts(&self, wallet: &Pubkey) -> Result<Vec<(String, String)>, CryptfolioError> {
+        let program = Pubkey::from_str(TOKEN_PROGRAM).unwrap();
+        match self.client.get_token_accounts_by_owner(wallet, TokenAccountsFilter::ProgramId(program)) {
+            Ok(accounts) => {
+                let mut token_accounts = Vec::<(String, String)>::new();
+                for account in accounts {
+                    if let UiAccountData::Json(data) = &account.account.data {
+                        if let Some(mint) = data.parsed.get("info").and_then(|x| x.get("mint")).and_then(|x| x.as_str()) {
+                            token_accounts.push((account.pubkey.to_string(), mint.to_string()));
+                        }
+                    }
+                }
+                Ok(token_accounts)
+            },
+            Err(e) => { Err(CryptfolioError::SolanaAPIError(e.to_string())) }
+        }
+    }
+
+    /// Adds the wallet's open stake accounts, along with the first and last epoch each one was active.
+    fn fetch_stake_accounts(&self) -> Result<(), CryptfolioError> {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new(
+                STAKER_OFFSET,
+                MemcmpEncodedBytes::Base58(self.wallet.to_string())
+            ))]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..RpcAccountInfoConfig::default()
+            },
+            with_context: None,
+        };
+        let program = Pubkey::from_str(STAKE_PROGRAM).unwrap();
+        match self.client.get_program_accounts_with_config(&program, config) {
+            Ok(accounts) => {
+                for (address, account) in accounts {
+                    // Stake which was never delegated has never earned anything.
+                    if let Ok(StakeStateV2::Stake(_meta, stake, _stake_flags)) = bincode::deserialize::<StakeStateV2>(&account.data) {
+                        self.add_stake_account(
+                            address.to_string().as_str(),
+                            stake.delegation.activation_epoch,
+                            stake.delegation.deactivation_epoch
+                        );
+                    }
+                }
+                Ok(())
+            },
+            Err(e) => { Err(CryptfolioError::SolanaAPIError(e.to_string())) }
+        }
+    }
+
+    fn get_staking_rewards(&self) -> Result<Vec<Box<dyn DatabaseEntry + Send>>, CryptfolioError> {
+        let mut result = Vec::<Box<dyn DatabaseEntry + Send>>::new();
+        let current_epoch = match self.client.get_epoch_info() {
+            Ok(info) => { info.epoch },
+            Err(e) => { return Err(CryptfolioError::SolanaAPIError(e.to_string())); }
+        };
+
+        // Rewards for an epoch are paid at the start of the next, so the current epoch has none yet.
+        let stake_accounts: Vec<(Pubkey, u64, u64)> = self.get_stake_accounts()
+            .iter()
+            .filter_map(|x| Pubkey::from_str(x.0.as_str()).ok().map(|address| (address, x.1, x.2)))
+            .collect();
+        let first_epoch = match stake_accounts.iter().map(|x| x.1).min() {
+            Some(epoch) => { epoch.max(self.get_reward_epoch()) },
+            None => { return Ok(result); }
+        };
+
+        // Rewards of the same epoch are usually paid in the same slot.
+        let mut block_times = HashMap::<u64, i64>::new();
+        for epoch in first_epoch..current_epoch {
+            let active: Vec<Pubkey> = stake_accounts
+                .iter()
+                .filter(|x| x.1 <= epoch && epoch <= x.2)
+                .map(|x| x.0)
+                .collect();
+            if active.is_empty() {
+                continue;
+            }
+            let rewards = match self.client.get_inflation_reward(&active, Some(epoch)) {
+                Ok(rewards) => { rewards },
+                Err(e) => { return Err(CryptfolioError::SolanaAPIError(e.to_string())); }
+            };
+            for (address, reward) in active.iter().zip(rewards) {
+                let reward = match reward {
+                    Some(reward) if reward.amount > 0 => { reward },
+                    _ => { continue; }
+                };
+                let block_time = match block_times.get(&reward.effective_slot) {
+                    Some(block_time) => { *block_time },
+                    None => {
+                        match self.client.get_block_time(reward.effective_slot) {
+                            Ok(block_time) => {
+                                block_times.insert(reward.effective_slot, block_time);
+                                block_time
+                            },
+                            Err(e) => { return Err(CryptfolioError::SolanaAPIError(e.to_string())); }
+                        }
+                    }
+                };
+                result.push(Box::new(CoinReward::new(
+                    format!("{}:{}", address, reward.epoch),
+                    Self::format_block_time(block_time),
+                    "SOL".to_string(),
+                    0.0,
+                    Self::to_sol(reward.amount as i128),
+                    "Staking".to_string(),
+                    format!("Epoch {}", reward.epoch),
+                    "Solana".to_string(),
+                    "USD".to_string()
+                )));
+            }
+        }
+        self.set_reward_epoch(current_epoch.max(self.get_reward_epoch()));
+        Ok(result)
+    }
+
+    /// The account which received the most lamports (outgoing) or lost the most (incoming).
+    fn get_counterparty(account_keys: &Vec<String>, deltas: &Vec<i128>, index: usize, outgoing: bool) -> String {
+        let mut counterparty: Option<(usize, i128)> = None;
+        for (i, delta) in deltas.iter().enumerate() {
+            if i == index || i >= account_keys.len() {
+                continue;
+            }
+            let change = if outgoing { *delta } else { -*delta };
+            if change > 0 && counterparty.map_or(true, |x| change > x.1) {
+                counterparty = Some((i, change));
+            }
+        }
+        match counterparty {
+            Some((i, _change)) => { account_keys[i].to_string() },
+            None => { "Unknown".to_string() }
+        }
     }
 }
 
@@ -65,44 +735,129 @@ impl SyncClient for Solana {
     }
 
     fn get_connection(&self, nickname: &String) -> PlatformConnection {
+        let mut connection_data = vec![
+            PlatformConnectionData { key: "Wallet Address".to_string(), value: self.get_wallet() },
+            PlatformConnectionData { key: "RPC URL".to_string(), value: self.rpc_url.to_string() },
+            PlatformConnectionData { key: "Commitment".to_string(), value: self.get_commitment() },
+            PlatformConnectionData { key: "Page Size".to_string(), value: self.page_size.to_string() },
+        ];
+        if let Some(until) = self.get_until() {
+            connection_data.push(PlatformConnectionData { key: "Until Signature".to_string(), value: until.to_string() });
+        }
+
+        // The token list is kept with the connection, so that added tokens survive a restart.
+        let mut tokens: Vec<(&String, &String)> = self.tokens.iter().collect();
+        tokens.sort();
+        for (mint, symbol) in tokens {
+            connection_data.push(PlatformConnectionData { key: format!("Token:{}", mint), value: symbol.to_string() });
+        }
+
+        // As are the stake accounts and reward epoch, which move on with every sync.
+        for (address, activation_epoch, deactivation_epoch) in self.get_stake_accounts() {
+            connection_data.push(PlatformConnectionData {
+                key: format!("Stake Account:{}", address),
+                value: format!("{}:{}", activation_epoch, deactivation_epoch)
+            });
+        }
+        connection_data.push(PlatformConnectionData { key: "Reward Epoch".to_string(), value: self.get_reward_epoch().to_string() });
         return PlatformConnection::new(
-            nickname.to_string(), 
+            nickname.to_string(),
             "Solana".to_string(),
-           vec![ 
-                PlatformConnectionData { key: "Wallet Address".to_string(), value: self.get_wallet() }
-            ]
+            connection_data
         );
     }
 
     async fn sync(&self) -> Result<Vec<Box<dyn DatabaseEntry + Send>>, CryptfolioError> {
-        let result = Vec::<Box<dyn DatabaseEntry + Send>>::new();
-
-        // let config = GetConfirmedSignaturesForAddress2Config {
-        //     before: None,
-        //     until: None,
-        //     limit: Some(3),
-        //     commitment: Some(CommitmentConfig::confirmed()),
-        // };
-
-        match self.client.get_signatures_for_address(&Pubkey::from_str(self.wallet.as_str()).unwrap()) {
-            Ok(signatures) => {
-                for signature in signatures {
-                    match self.client.get_transaction(&Signature::from_str(signature.signature.as_str()).unwrap(), UiTransactionEncoding::Json) {
-                        Ok(transaction) => {
-                            // result.push(self.process_transaction(transaction.transaction.meta.unwrap()));
-                            self.process_transaction(transaction.transaction.meta.unwrap());
-                        }
-                        Err(e) => {
-                            return Err(CryptfolioError::SolanaAPIError(e.to_string()));
+        let mut result = Vec::<Box<dyn DatabaseEntry + Send>>::new();
+        let wallet = match Pubkey::from_str(self.wallet.as_str()) {
+            Ok(wallet) => { wallet },
+            Err(e) => { return Err(CryptfolioError::SolanaAPIError(e.to_string())); }
+        };
+        result.push(Box::new(CoinAccount::new(
+            self.wallet.to_string(),
+            "SOL".to_string(),
+            "Solana".to_string()
+        )));
+
+        let mut addresses = vec![wallet];
+        let mut token_accounts = HashSet::<String>::new();
+        for (account, mint) in self.get_token_accounts(&wallet)? {
+            if mint == WRAPPED_SOL_MINT {
+                continue;
+            }
+            result.push(Box::new(CoinAccount::new(
+                account.to_string(),
+                self.get_symbol(mint.as_str()),
+                "Solana".to_string()
+            )));
+            if let Ok(address) = Pubkey::from_str(account.as_str()) {
+                addresses.push(address);
+            }
+            token_accounts.insert(account);
+        }
+
+        self.fetch_stake_accounts()?;
+
+        // A transaction may involve both the wallet and its token accounts, but is only synced once.
+        let mut synced = HashSet::<String>::new();
+        let mut newest: Option<(u64, String)> = None;
+        let mut epoch_schedule: Option<EpochSchedule> = None;
+        for address in addresses {
+            for signature in self.get_signatures(&address)? {
+                if !synced.insert(signature.signature.to_string()) {
+                    continue;
+                }
+                if newest.as_ref().map_or(true, |x| signature.slot > x.0) {
+                    newest = Some((signature.slot, signature.signature.to_string()));
+                }
+                let id = match Signature::from_str(signature.signature.as_str()) {
+                    Ok(id) => { id },
+                    Err(e) => { return Err(CryptfolioError::SolanaAPIError(e.to_string())); }
+                };
+                let config = RpcTransactionConfig {
+                    encoding: Some(UiTransactionEncoding::Json),
+                    commitment: Some(self.commitment),
+                    max_supported_transaction_version: Some(0),
+                };
+                match self.client.get_transaction_with_config(&id, config) {
+                    Ok(transaction) => {
+                        // Closed stake accounts are only found through the instructions which used them.
+                        if let Some(meta) = &transaction.transaction.meta {
+                            let account_keys = Self::get_account_keys(&transaction.transaction.transaction, meta)?;
+                            for account in Self::get_stake_instruction_accounts(&transaction.transaction.transaction, &account_keys) {
+                                if epoch_schedule.is_none() {
+                                    epoch_schedule = match self.client.get_epoch_schedule() {
+                                        Ok(schedule) => { Some(schedule) },
+                                        Err(e) => { return Err(CryptfolioError::SolanaAPIError(e.to_string())); }
+                                    };
+                                }
+                                let epoch = epoch_schedule.as_ref().unwrap().get_epoch(transaction.slot);
+                                self.add_stake_account(account.as_str(), epoch, epoch);
+                            }
                         }
+                        result.append(&mut self.process_transaction(signature.signature.as_str(), transaction, signature.block_time, &token_accounts)?);
+                    }
+                    Err(e) => {
+                        return Err(CryptfolioError::SolanaAPIError(e.to_string()));
                     }
                 }
-            },
-            Err(e) => {
-                return Err(CryptfolioError::SolanaAPIError(e.to_string()));
             }
         }
 
+        for (address, _activation_epoch, _deactivation_epoch) in self.get_stake_accounts() {
+            result.push(Box::new(CoinAccount::new(
+                address,
+                "SOL".to_string(),
+                "Solana".to_string()
+            )));
+        }
+        result.append(&mut self.get_staking_rewards()?);
+
+        // The next sync stops at the newest signature of this one, which is only moved on once
+        // everything before it has been synced.
+        if let Some((_slot, signature)) = newest {
+            self.set_until(Some(signature));
+        }
         Ok(result)
     }
 }
\ No newline at end of file
@@ -0,0 +1,669 @@
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// binance.rs
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Description:
+///     Binance and Binance.US (Exchange) implementation for syncing transaction history.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Usage:
+///     let mut binance = Binance::new("<api key>", "<api secret>", BinanceRegion::Global).unwrap();
+/// 
+///     // Pairs traded without the coin ever being held, deposited or withdrawn (e.g. a coin
+///     // bought and sold back to USDT) are not found on their own.
+///     binance.add_pair("PEPE", "USDT");
+///     let handle = app.add_platform("Binance", binance);
+///     app.sync_platform(handle).await.unwrap();
+/// 
+///     // Recorded API responses (see BinanceHistory) can be processed directly.
+///     let entries = binance.process_history(&history).unwrap();
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Notes:
+///     The API key only needs the 'Enable Reading' permission.
+/// 
+///     Binance only returns trades one symbol at a time. The symbols queried are every listed
+///     pair between two assets found in the account's balances, deposits, withdrawals, converts
+///     or rewards, plus any pair added with add_pair (which may since have been delisted).
+/// 
+///     Trading fees paid in BNB are a disposal of BNB. Each one is recorded as a sale of the BNB
+///     in the trade's quote currency, at the day's closing price, and the proceeds are the fee
+///     of the trade. Fees paid in the coin bought reduce the amount received. When BNB is not
+///     listed against the quote currency, the disposal is left without a price (NaN) to be
+///     reported by the diagnostics. Trades quoted in a coin (e.g. BNB-BTC) are converted to the
+///     reporting currency through the price source (see CryptfolioApp::set_price_source).
+/// 
+///     Deposit, withdrawal and convert history can only be queried a few months at a time, so
+///     it is walked in windows from the start date (by default, the launch of the exchange).
+///     Distributions cannot be paged, so a window which returns a full page is split in two and
+///     queried again.
+///     Fiat deposits and withdrawals are only available on Binance (Global).
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+
+use crate::database::entry::{
+    AccountBalance, CoinAccount, CoinOrder, CoinReward, CoinTransfer, DatabaseEntry, FiatTransfer, PlatformConnection,
+    PlatformConnectionData,
+};
+use crate::error::CryptfolioError;
+use crate::platform::SyncClient;
+use crate::util::Util;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use hmac::{Hmac, Mac};
+use serde_json::Value;
+use sha2::Sha256;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+
+const TRADES_PAGE_SIZE: usize = 1000;
+
+// How an endpoint pages through the records of a time window.
+#[derive(Clone, Copy)]
+enum Paging {
+    None,
+    Offset(usize),
+    Current(usize),
+    Page(usize),
+    Split(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BinanceRegion {
+    Global,
+    US,
+}
+
+impl fmt::Display for BinanceRegion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Global => { write!(f, "Binance") },
+            Self::US => { write!(f, "Binance.US") },
+        }
+    }
+}
+
+impl BinanceRegion {
+    fn get_api_url(&self) -> &str {
+        match self {
+            Self::Global => { "https://api.binance.com" },
+            Self::US => { "https://api.binance.us" },
+        }
+    }
+
+    fn get_launch_date(&self) -> NaiveDate {
+        match self {
+            Self::Global => { NaiveDate::from_ymd_opt(2017, 7, 14).unwrap() },
+            Self::US => { NaiveDate::from_ymd_opt(2019, 9, 18).unwrap() },
+        }
+    }
+}
+
+/// The records of every endpoint synced, as returned by the API (pages and windows joined).
+#[derive(Default)]
+pub struct BinanceHistory {
+    pub account: Value,
+    pub exchange_info: Value,
+    pub deposits: Vec<Value>,
+    pub withdrawals: Vec<Value>,
+    pub fiat_deposits: Vec<Value>,
+    pub fiat_withdrawals: Vec<Value>,
+    pub converts: Vec<Value>,
+    pub flexible_rewards: Vec<Value>,
+    pub locked_rewards: Vec<Value>,
+    pub dividends: Vec<Value>,
+    pub staking_rewards: Vec<Value>,
+    // Trades and daily BNB klines, by symbol.
+    pub trades: HashMap<String, Vec<Value>>,
+    pub klines: HashMap<String, Vec<Value>>,
+}
+
+pub struct Binance {
+    api_key: String,
+    api_secret: String,
+    region: BinanceRegion,
+    start_date: NaiveDate,
+    pairs: Vec<(String, String)>,
+    client: reqwest::Client,
+}
+
+impl Binance {
+    pub fn new(api_key: &str, api_secret: &str, region: BinanceRegion) -> Result<Binance, CryptfolioError> {
+        Ok(
+            Binance {
+                api_key: api_key.to_string(),
+                api_secret: api_secret.to_string(),
+                start_date: region.get_launch_date(),
+                region: region,
+                pairs: Vec::<(String, String)>::new(),
+                client: reqwest::Client::new(),
+            }
+        )
+    }
+
+    pub fn get_api_key(&self) -> String {
+        self.api_key.to_string()
+    }
+
+    pub fn get_api_secret(&self) -> String {
+        self.api_secret.to_string()
+    }
+
+    pub fn get_region(&self) -> &BinanceRegion {
+        return &self.region;
+    }
+
+    pub fn get_start_date(&self) -> NaiveDate {
+        return self.start_date;
+    }
+
+    /// Skips history before the account was opened, which saves many empty requests.
+    pub fn set_start_date(&mut self, start_date: NaiveDate) {
+        self.start_date = start_date;
+    }
+
+    pub fn get_pairs(&self) -> &Vec<(String, String)> {
+        return &self.pairs;
+    }
+
+    /// Adds a pair (e.g. 'PEPE' and 'USDT' for the symbol 'PEPEUSDT') to query for trades.
+    pub fn add_pair(&mut self, base: &str, quote: &str) {
+        let pair = (base.to_uppercase(), quote.to_uppercase());
+        if !self.pairs.contains(&pair) {
+            self.pairs.push(pair);
+        }
+    }
+
+    fn get_account(&self, asset: &str) -> String {
+        format!("{}:{}", self.region.to_string().to_lowercase(), asset)
+    }
+
+    fn format_timestamp(time: &Value) -> String {
+        let time = time.as_i64().unwrap_or(0);
+        DateTime::from_timestamp(time / 1000, 0).unwrap_or_default().format("%Y-%m-%dT%H:%M:%SZ").to_string()
+    }
+
+    fn parse_amount(value: &Value) -> f64 {
+        match value {
+            Value::String(value) => { value.parse::<f64>().unwrap_or(0.0) },
+            _ => { value.as_f64().unwrap_or(0.0) }
+        }
+    }
+
+    fn sign(&self, query: &str) -> Result<String, CryptfolioError> {
+        let mut mac = match Hmac::<Sha256>::new_from_slice(self.api_secret.as_bytes()) {
+            Ok(mac) => { mac },
+            Err(e) => { return Err(CryptfolioError::BinanceAPIError(e.to_string())); }
+        };
+        mac.update(query.as_bytes());
+        Ok(mac.finalize().into_bytes().iter().map(|x| format!("{:02x}", x)).collect())
+    }
+
+    async fn request(&self, path: &str, params: &str, signed: bool) -> Result<Value, CryptfolioError> {
+        let url = if signed {
+            let query = format!("{}{}recvWindow=60000&timestamp={}", params, if params.is_empty() { "" } else { "&" }, Utc::now().timestamp_millis());
+            format!("{}{}?{}&signature={}", self.region.get_api_url(), path, query, self.sign(query.as_str())?)
+        } else {
+            format!("{}{}?{}", self.region.get_api_url(), path, params)
+        };
+        let response = match self.client.get(url).header("X-MBX-APIKEY", self.api_key.as_str()).send().await {
+            Ok(response) => { response },
+            Err(e) => { return Err(CryptfolioError::BinanceAPIError(e.to_string())); }
+        };
+        let response = match response.json::<Value>().await {
+            Ok(response) => { response },
+            Err(e) => { return Err(CryptfolioError::BinanceAPIError(e.to_string())); }
+        };
+        if let Some(code) = response["code"].as_i64() {
+            // Some endpoints answer successfully with a code (e.g. '000000' for fiat orders).
+            if code < 0 {
+                return Err(CryptfolioError::BinanceAPIError(format!("{} ({})", response["msg"].as_str().unwrap_or(""), code)));
+            }
+        }
+        Ok(response)
+    }
+
+    /// Splits the time since the start date into windows of at most the given number of days,
+    /// as epoch milliseconds.
+    fn get_windows(&self, days: i64) -> Vec<(i64, i64)> {
+        let mut windows = Vec::<(i64, i64)>::new();
+        let now = Utc::now().timestamp_millis();
+        let mut start = self.start_date.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp_millis();
+        while start < now {
+            let end = (start + Duration::days(days).num_milliseconds() - 1).min(now);
+            windows.push((start, end));
+            start = end + 1;
+        }
+        windows
+    }
+
+    async fn fetch_windowed(&self, path: &str, params: &str, days: i64, rows: &str, paging: Paging) -> Result<Vec<Value>, CryptfolioError> {
+        let mut result = Vec::<Value>::new();
+        let mut windows = self.get_windows(days);
+        windows.reverse();
+        while let Some((start, end)) = windows.pop() {
+            let mut page: usize = 0;
+            loop {
+                let page_params = match paging {
+                    Paging::None => { String::new() },
+                    Paging::Split(size) => { format!("&limit={}", size) },
+                    Paging::Offset(size) => { format!("&offset={}&limit={}", page * size, size) },
+                    Paging::Current(size) => { format!("&current={}&size={}", page + 1, size) },
+                    Paging::Page(size) => { format!("&page={}&rows={}", page + 1, size) },
+                };
+                let response = self.request(
+                    path,
+                    format!("{}startTime={}&endTime={}{}", params, start, end, page_params).as_str(),
+                    true
+                ).await?;
+                let records = if rows.is_empty() { response.as_array().cloned() } else { response[rows].as_array().cloned() };
+                let records = records.unwrap_or_default();
+                let count = records.len();
+
+                // A full page may have left records out, so the window is queried again in halves.
+                if let Paging::Split(size) = paging {
+                    if count >= size && end > start {
+                        let middle = start + (end - start) / 2;
+                        windows.push((middle + 1, end));
+                        windows.push((start, middle));
+                        break;
+                    }
+                }
+                result.extend(records);
+                match paging {
+                    Paging::Offset(size) | Paging::Current(size) | Paging::Page(size) if count >= size => { page += 1; },
+                    _ => { break; }
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    async fn fetch_trades(&self, symbol: &str) -> Result<Vec<Value>, CryptfolioError> {
+        let mut result = Vec::<Value>::new();
+        let mut from_id: u64 = 0;
+        loop {
+            let response = self.request(
+                "/api/v3/myTrades",
+                format!("symbol={}&fromId={}&limit={}", symbol, from_id, TRADES_PAGE_SIZE).as_str(),
+                true
+            ).await?;
+            let trades = response.as_array().cloned().unwrap_or_default();
+            let count = trades.len();
+            if let Some(last) = trades.last() {
+                from_id = last["id"].as_u64().unwrap_or(0) + 1;
+            }
+            result.extend(trades);
+            if count < TRADES_PAGE_SIZE {
+                break;
+            }
+        }
+        Ok(result)
+    }
+
+    /// The day's closing price of BNB in the given quote currency, or NaN if there is no kline
+    /// for the day (BNB is not listed against it, or was not yet trading on the day).
+    fn get_bnb_price(quote: &str, time: i64, history: &BinanceHistory) -> f64 {
+        let day = time / 86_400_000 * 86_400_000;
+        history.klines
+            .get(&format!("BNB{}", quote))
+            .and_then(|x| x.iter().find(|x| x[0].as_i64() == Some(day)))
+            .map(|x| Self::parse_amount(&x[4]))
+            .unwrap_or(f64::NAN)
+    }
+
+    fn process_trade(&self, trade: &Value, base: &str, quote: &str, history: &BinanceHistory) -> Vec<Box<dyn DatabaseEntry + Send>> {
+        let mut result = Vec::<Box<dyn DatabaseEntry + Send>>::new();
+        let id = format!("{}:{}", trade["symbol"].as_str().unwrap_or(""), trade["id"]);
+        let date = Self::format_timestamp(&trade["time"]);
+        let is_buyer = trade["isBuyer"].as_bool().unwrap_or(false);
+        let price = Self::parse_amount(&trade["price"]);
+        let mut size = Self::parse_amount(&trade["qty"]);
+        let commission = Self::parse_amount(&trade["commission"]);
+        let commission_asset = trade["commissionAsset"].as_str().unwrap_or("");
+
+        let fee = if commission_asset == quote {
+            commission
+        } else if commission_asset == base {
+            if is_buyer {
+                size -= commission;
+                0.0
+            } else {
+                commission * price
+            }
+        } else if commission_asset == "BNB" {
+            let bnb_price = Self::get_bnb_price(quote, trade["time"].as_i64().unwrap_or(0), history);
+            let mut disposal = CoinOrder::new(
+                format!("{}:fee", id),
+                date.to_string(),
+                format!("BNB-{}", quote),
+                bnb_price,
+                commission,
+                0.0,
+                "sell".to_string(),
+                self.region.to_string()
+            );
+            disposal.set_description(format!("BNB trading fee for {}", id));
+            result.push(Box::new(disposal));
+            commission * bnb_price
+        } else {
+            0.0
+        };
+
+        result.insert(0, Box::new(CoinOrder::new(
+            id,
+            date,
+            format!("{}-{}", base, quote),
+            price,
+            size,
+            fee,
+            if is_buyer { "buy".to_string() } else { "sell".to_string() },
+            self.region.to_string()
+        )));
+        result
+    }
+
+    /// A convert spends one asset to receive another, without a fee.
+    fn process_convert(&self, convert: &Value) -> Box<dyn DatabaseEntry + Send> {
+        let from_asset = convert["fromAsset"].as_str().unwrap_or("");
+        let to_asset = convert["toAsset"].as_str().unwrap_or("");
+        let from_amount = Self::parse_amount(&convert["fromAmount"]);
+        let to_amount = Self::parse_amount(&convert["toAmount"]);
+
+        let mut order = if Util::is_fiat(to_asset) && !Util::is_fiat(from_asset) {
+            CoinOrder::new(
+                convert["orderId"].to_string().trim_matches('"').to_string(),
+                Self::format_timestamp(&convert["createTime"]),
+                format!("{}-{}", from_asset, to_asset),
+                if from_amount > 0.0 { to_amount / from_amount } else { 0.0 },
+                from_amount,
+                0.0,
+                "sell".to_string(),
+                self.region.to_string()
+            )
+        } else {
+            CoinOrder::new(
+                convert["orderId"].to_string().trim_matches('"').to_string(),
+                Self::format_timestamp(&convert["createTime"]),
+                format!("{}-{}", to_asset, from_asset),
+                if to_amount > 0.0 { from_amount / to_amount } else { 0.0 },
+                to_amount,
+                0.0,
+                "buy".to_string(),
+                self.region.to_string()
+            )
+        };
+        order.set_description("Binance Convert".to_string());
+        Box::new(order)
+    }
+
+    fn process_reward(&self, id: String, time: &Value, asset: &str, amount: f64, description: &str) -> Box<dyn DatabaseEntry + Send> {
+        Box::new(CoinReward::new(
+            id,
+            Self::format_timestamp(time),
+            asset.to_string(),
+            0.0,
+            amount,
+            "Staking".to_string(),
+            description.to_string(),
+            self.region.to_string(),
+            "USD".to_string()
+        ))
+    }
+
+    fn get_listed(history: &BinanceHistory) -> HashSet<String> {
+        history.exchange_info["symbols"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default()
+            .iter()
+            .map(|x| x["symbol"].as_str().unwrap_or("").to_string())
+            .collect()
+    }
+
+    /// Every asset held, deposited, withdrawn, converted or rewarded.
+    fn get_assets(history: &BinanceHistory) -> BTreeSet<String> {
+        let mut assets = BTreeSet::<String>::new();
+        for balance in history.account["balances"].as_array().cloned().unwrap_or_default() {
+            if Self::parse_amount(&balance["free"]) + Self::parse_amount(&balance["locked"]) > 0.0 {
+                assets.insert(balance["asset"].as_str().unwrap_or("").to_string());
+            }
+        }
+        for record in history.deposits.iter().chain(history.withdrawals.iter()) {
+            assets.insert(record["coin"].as_str().unwrap_or("").to_string());
+        }
+        for order in history.fiat_deposits.iter().chain(history.fiat_withdrawals.iter()).filter(|x| x["status"] == "Successful") {
+            assets.insert(order["fiatCurrency"].as_str().unwrap_or("").to_string());
+        }
+        for convert in history.converts.iter().filter(|x| x["orderStatus"] == "SUCCESS") {
+            assets.insert(convert["fromAsset"].as_str().unwrap_or("").to_string());
+            assets.insert(convert["toAsset"].as_str().unwrap_or("").to_string());
+        }
+        let rewards = history.flexible_rewards.iter().chain(history.locked_rewards.iter()).chain(history.staking_rewards.iter());
+        for reward in rewards.chain(history.dividends.iter().filter(|x| !Self::is_simple_earn(x))) {
+            assets.insert(reward["asset"].as_str().unwrap_or("").to_string());
+        }
+        assets.remove("");
+        assets
+    }
+
+    fn is_simple_earn(dividend: &Value) -> bool {
+        dividend["enInfo"].as_str().unwrap_or("").contains("Simple Earn")
+    }
+
+    /// Trades are queried for every added pair, and every listed pair between two of the assets
+    /// found. Added pairs are queried even once delisted, as their trades are still returned.
+    fn get_symbols(&self, history: &BinanceHistory) -> Vec<(String, String, String)> {
+        let assets = Self::get_assets(history);
+        let mut symbols: Vec<(String, String, String)> = self.pairs
+            .iter()
+            .map(|(base, quote)| (format!("{}{}", base, quote), base.to_string(), quote.to_string()))
+            .collect();
+        for symbol in history.exchange_info["symbols"].as_array().cloned().unwrap_or_default() {
+            let name = symbol["symbol"].as_str().unwrap_or("").to_string();
+            let base = symbol["baseAsset"].as_str().unwrap_or("").to_string();
+            let quote = symbol["quoteAsset"].as_str().unwrap_or("").to_string();
+            if assets.contains(&base) && assets.contains(&quote) && !symbols.iter().any(|x| x.0 == name) {
+                symbols.push((name, base, quote));
+            }
+        }
+        symbols
+    }
+
+    /// Processes the records of every endpoint, whether fetched or recorded.
+    pub fn process_history(&self, history: &BinanceHistory) -> Result<Vec<Box<dyn DatabaseEntry + Send>>, CryptfolioError> {
+        let mut result = Vec::<Box<dyn DatabaseEntry + Send>>::new();
+        let platform = self.region.to_string();
+        let synced_at = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+
+        let mut balances = HashMap::<String, f64>::new();
+        for balance in history.account["balances"].as_array().cloned().unwrap_or_default() {
+            let total = Self::parse_amount(&balance["free"]) + Self::parse_amount(&balance["locked"]);
+            if total > 0.0 {
+                balances.insert(balance["asset"].as_str().unwrap_or("").to_string(), total);
+            }
+        }
+
+        for deposit in history.deposits.iter() {
+            let asset = deposit["coin"].as_str().unwrap_or("").to_string();
+            result.push(Box::new(CoinTransfer::new(
+                deposit["id"].as_str().map(|x| x.to_string()).unwrap_or(deposit["txId"].as_str().unwrap_or("").to_string()),
+                Self::format_timestamp(&deposit["insertTime"]),
+                String::new(),
+                self.get_account(asset.as_str()),
+                asset,
+                Self::parse_amount(&deposit["amount"]),
+                0.0
+            )));
+        }
+
+        for withdrawal in history.withdrawals.iter() {
+            let asset = withdrawal["coin"].as_str().unwrap_or("").to_string();
+            result.push(Box::new(CoinTransfer::new(
+                withdrawal["id"].as_str().unwrap_or("").to_string(),
+                format!("{}Z", withdrawal["applyTime"].as_str().unwrap_or("").replace(' ', "T")),
+                self.get_account(asset.as_str()),
+                withdrawal["address"].as_str().unwrap_or("").to_string(),
+                asset,
+                Self::parse_amount(&withdrawal["amount"]),
+                Self::parse_amount(&withdrawal["transactionFee"])
+            )));
+        }
+
+        for (orders, is_deposit) in [(&history.fiat_deposits, true), (&history.fiat_withdrawals, false)] {
+            for order in orders.iter().filter(|x| x["status"] == "Successful") {
+                let currency = order["fiatCurrency"].as_str().unwrap_or("").to_string();
+                let account = self.get_account(currency.as_str());
+                let amount = Self::parse_amount(&order["amount"]);
+                let fee = Self::parse_amount(&order["totalFee"]);
+                let (origin, destination, amount) = if is_deposit {
+                    (String::new(), account, amount - fee)
+                } else {
+                    (account, String::new(), amount + fee)
+                };
+                result.push(Box::new(FiatTransfer::new(
+                    order["orderNo"].as_str().unwrap_or("").to_string(),
+                    Self::format_timestamp(&order["createTime"]),
+                    origin,
+                    destination,
+                    amount,
+                    currency
+                )));
+            }
+        }
+
+        for convert in history.converts.iter().filter(|x| x["orderStatus"] == "SUCCESS") {
+            result.push(self.process_convert(convert));
+        }
+
+        for (kind, rewards, field) in [("flexible", &history.flexible_rewards, "rewards"), ("locked", &history.locked_rewards, "amount")] {
+            for reward in rewards.iter() {
+                let asset = reward["asset"].as_str().unwrap_or("");
+                result.push(self.process_reward(
+                    format!("{}:{}:{}", kind, asset, reward["time"]),
+                    &reward["time"],
+                    asset,
+                    Self::parse_amount(&reward[field]),
+                    format!("Simple Earn {}", if kind == "flexible" { "Flexible" } else { "Locked" }).as_str()
+                ));
+            }
+        }
+
+        // Distributions (staking, launchpool, airdrops). Simple Earn is imported above.
+        for dividend in history.dividends.iter().filter(|x| !Self::is_simple_earn(x)) {
+            result.push(self.process_reward(
+                dividend["id"].to_string(),
+                &dividend["divTime"],
+                dividend["asset"].as_str().unwrap_or(""),
+                Self::parse_amount(&dividend["amount"]),
+                dividend["enInfo"].as_str().unwrap_or("Distribution")
+            ));
+        }
+
+        for reward in history.staking_rewards.iter() {
+            result.push(self.process_reward(
+                reward["tranId"].to_string(),
+                &reward["time"],
+                reward["asset"].as_str().unwrap_or(""),
+                Self::parse_amount(&reward["amount"]),
+                "Binance.US Staking"
+            ));
+        }
+
+        let mut assets = Self::get_assets(history);
+        for (name, base, quote) in self.get_symbols(history) {
+            for trade in history.trades.get(&name).cloned().unwrap_or_default() {
+                result.append(&mut self.process_trade(&trade, base.as_str(), quote.as_str(), history));
+            }
+            assets.insert(base);
+            assets.insert(quote);
+        }
+
+        for asset in assets {
+            result.push(Box::new(CoinAccount::new(self.get_account(asset.as_str()), asset.to_string(), platform.to_string())));
+            result.push(Box::new(AccountBalance::new(
+                self.get_account(asset.as_str()),
+                synced_at.to_string(),
+                asset.to_string(),
+                platform.to_string(),
+                balances.get(&asset).cloned().unwrap_or(0.0)
+            )));
+        }
+        Ok(result)
+    }
+}
+
+#[async_trait]
+impl SyncClient for Binance {
+    fn get_name(&self) -> &str {
+        match self.region {
+            BinanceRegion::Global => { "Binance" },
+            BinanceRegion::US => { "Binance.US" },
+        }
+    }
+
+    fn get_connection(&self, nickname: &String) -> PlatformConnection {
+        let mut data = vec![
+            PlatformConnectionData { key: "API Key".to_string(), value: self.get_api_key() },
+            PlatformConnectionData { key: "API Secret".to_string(), value: self.get_api_secret() },
+            PlatformConnectionData { key: "Start Date".to_string(), value: self.start_date.format("%Y-%m-%d").to_string() },
+        ];
+        for (base, quote) in self.pairs.iter() {
+            data.push(PlatformConnectionData { key: format!("Pair:{}{}", base, quote), value: format!("{}-{}", base, quote) });
+        }
+        return PlatformConnection::new(nickname.to_string(), self.region.to_string(), data);
+    }
+
+    async fn sync(&self) -> Result<Vec<Box<dyn DatabaseEntry + Send>>, CryptfolioError> {
+        let mut history = BinanceHistory::default();
+        history.account = self.request("/api/v3/account", "", true).await?;
+        history.exchange_info = self.request("/api/v3/exchangeInfo", "", false).await?;
+        history.deposits = self.fetch_windowed("/sapi/v1/capital/deposit/hisrec", "status=1&", 90, "", Paging::Offset(1000)).await?;
+        history.withdrawals = self.fetch_windowed("/sapi/v1/capital/withdraw/history", "status=6&", 90, "", Paging::Offset(1000)).await?;
+        history.converts = self.fetch_windowed("/sapi/v1/convert/tradeFlow", "limit=1000&", 30, "list", Paging::None).await?;
+        match self.region {
+            BinanceRegion::Global => {
+                history.fiat_deposits = self.fetch_windowed("/sapi/v1/fiat/orders", "transactionType=0&", 90, "data", Paging::Page(500)).await?;
+                history.fiat_withdrawals = self.fetch_windowed("/sapi/v1/fiat/orders", "transactionType=1&", 90, "data", Paging::Page(500)).await?;
+                history.flexible_rewards = self.fetch_windowed(
+                    "/sapi/v1/simple-earn/flexible/history/rewardsRecord", "type=REWARDS&", 90, "rows", Paging::Current(100)
+                ).await?;
+                history.locked_rewards = self.fetch_windowed(
+                    "/sapi/v1/simple-earn/locked/history/rewardsRecord", "", 90, "rows", Paging::Current(100)
+                ).await?;
+                history.dividends = self.fetch_windowed("/sapi/v1/asset/assetDividend", "", 180, "rows", Paging::Split(500)).await?;
+            },
+            BinanceRegion::US => {
+                history.staking_rewards = self.fetch_windowed("/sapi/v1/staking/stakingRewardsHistory", "", 90, "data", Paging::None).await?;
+            }
+        }
+
+        // Trades can only be queried once the assets of the account are known.
+        let listed = Self::get_listed(&history);
+        for (name, _base, quote) in self.get_symbols(&history) {
+            let trades = self.fetch_trades(name.as_str()).await?;
+
+            // Fees paid in BNB are priced at the day's close, which is fetched once per day.
+            let symbol = format!("BNB{}", quote);
+            if listed.contains(&symbol) {
+                for trade in trades.iter().filter(|x| x["commissionAsset"] == "BNB" && name != symbol) {
+                    let day = trade["time"].as_i64().unwrap_or(0) / 86_400_000 * 86_400_000;
+                    let klines = history.klines.entry(symbol.to_string()).or_default();
+                    if klines.iter().any(|x| x[0].as_i64() == Some(day)) {
+                        continue;
+                    }
+                    let response = self.request("/api/v3/klines", format!("symbol={}&interval=1d&startTime={}&limit=1", symbol, day).as_str(), false).await?;
+                    if let Some(kline) = response.as_array().and_then(|x| x.first()).filter(|x| x[0].as_i64() == Some(day)) {
+                        klines.push(kline.clone());
+                    }
+                }
+            }
+            history.trades.insert(name, trades);
+        }
+        self.process_history(&history)
+    }
+}
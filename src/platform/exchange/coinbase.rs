@@ -20,8 +20,8 @@
 use std::collections::HashMap;
 
 use crate::database::entry::{
-    CoinAccount, CoinOrder, CoinTransfer, CoinReward, DatabaseEntry, 
-    Dud, FiatTransfer, PlatformConnection, PlatformConnectionData,
+    AccountBalance, CoinAccount, CoinOrder, CoinTransfer, CoinReward, DatabaseEntry, 
+    Dud, FiatTransfer, PlatformConnection, PlatformConnectionData, UnknownTransaction,
 };
 use crate::error::CryptfolioError;
 use crate::platform::SyncClient;
@@ -69,7 +69,9 @@ impl Coinbase {
                                     transaction.native_amount.amount.parse::<f64>().unwrap() / transaction.amount.amount.parse::<f64>().unwrap(),
                                     transaction.amount.amount.parse::<f64>().unwrap(),
                                     "Learn & Earn".to_string(),
-                                    "Coinbase Earn".to_string()
+                                    "Coinbase Earn".to_string(),
+                                    "Coinbase".to_string(),
+                                    transaction.native_amount.currency
                                 )))
                             },
                             "From Coinbase Rewards" => {
@@ -80,7 +82,9 @@ impl Coinbase {
                                     transaction.native_amount.amount.parse::<f64>().unwrap() / transaction.amount.amount.parse::<f64>().unwrap(),
                                     transaction.amount.amount.parse::<f64>().unwrap(),
                                     "Staking".to_string(),
-                                    "Coinbase Rewards".to_string()
+                                    "Coinbase Rewards".to_string(),
+                                    "Coinbase".to_string(),
+                                    transaction.native_amount.currency
                                 )))
                             },
                             _ => {
@@ -134,7 +138,9 @@ impl Coinbase {
                         transaction.native_amount.amount.parse::<f64>().unwrap() / transaction.amount.amount.parse::<f64>().unwrap(),
                         transaction.amount.amount.parse::<f64>().unwrap(),
                         "Staking".to_string(),
-                        "Coinbase Rewards".to_string()
+                        "Coinbase Rewards".to_string(),
+                        "Coinbase".to_string(),
+                        transaction.native_amount.currency
                     )))
                 }
                 
@@ -153,7 +159,7 @@ impl Coinbase {
                     Ok(Box::new(CoinOrder::new(
                         transaction.id,
                         transaction.created_at,
-                        format!("{}-USD", transaction.amount.currency),
+                        format!("{}-{}", transaction.amount.currency, transaction.native_amount.currency),
                         transaction.buy.as_ref().unwrap().unit_price.as_ref().unwrap().amount.parse::<f64>().unwrap(),
                         transaction.amount.amount.parse::<f64>().unwrap(),
                         transaction.buy.as_ref().unwrap().fee.amount.parse::<f64>().unwrap(),
@@ -166,7 +172,7 @@ impl Coinbase {
             //     Ok(Box::new(CoinOrder::new(
             //         transaction.id,
             //         transaction.created_at,
-            //         format!("{}-USD", transaction.amount.currency),
+            //         format!("{}-{}", transaction.amount.currency, transaction.native_amount.currency),
             //         0.0,
             //         0.0,
             //         0.0,
@@ -180,7 +186,8 @@ impl Coinbase {
                     transaction.created_at,
                     "FIAT Institution".to_string(),
                     account_id.to_string(),
-                    transaction.amount.amount.parse::<f64>().unwrap().abs()
+                    transaction.amount.amount.parse::<f64>().unwrap().abs(),
+                    transaction.amount.currency
                 )))
             },
             "fiat_withdrawal" => {
@@ -189,7 +196,8 @@ impl Coinbase {
                     transaction.created_at,
                     account_id.to_string(),
                     "FIAT Institution".to_string(),
-                    transaction.amount.amount.parse::<f64>().unwrap().abs()
+                    transaction.amount.amount.parse::<f64>().unwrap().abs(),
+                    transaction.amount.currency
                 )))
             },
             "exchange_deposit" => {
@@ -198,7 +206,8 @@ impl Coinbase {
                     transaction.created_at,
                     account_id.to_string(),
                     "Coinbase Pro".to_string(),
-                    transaction.amount.amount.parse::<f64>().unwrap().abs()
+                    transaction.amount.amount.parse::<f64>().unwrap().abs(),
+                    transaction.amount.currency
                 )))
             },
             "exchange_withdrawal" => {
@@ -207,7 +216,8 @@ impl Coinbase {
                     transaction.created_at,
                     "Coinbase Pro".to_string(),
                     account_id.to_string(),
-                    transaction.amount.amount.parse::<f64>().unwrap().abs()
+                    transaction.amount.amount.parse::<f64>().unwrap().abs(),
+                    transaction.amount.currency
                 )))
 
             },
@@ -250,10 +260,10 @@ impl Coinbase {
                         side = "buy".to_string();
                     }
 
-                    Ok(Box::new(CoinOrder::new(
+                    let mut order = CoinOrder::new(
                         transaction.id,
                         transaction.created_at,
-                        format!("{}-USD", transaction.amount.currency),
+                        format!("{}-{}", transaction.amount.currency, transaction.native_amount.currency),
                         transaction.advanced_trade_fill.as_ref().unwrap().fill_price.parse::<f64>().unwrap(),
                         transaction.amount.amount.parse::<f64>().unwrap().abs(),
                         (transaction.native_amount.amount.parse::<f64>().unwrap().abs() - 
@@ -261,7 +271,12 @@ impl Coinbase {
                         transaction.advanced_trade_fill.as_ref().unwrap().fill_price.parse::<f64>().unwrap())).abs(),
                         side,
                         "Coinbase".to_string()
-                    )))
+                    );
+
+                    // Marks the fill, so that it gives way to the fills of its order from Coinbase Advanced.
+                    order.set_description("Advanced Trade fill".to_string());
+                    order.set_order_id(transaction.advanced_trade_fill.as_ref().unwrap().order_id.to_string());
+                    Ok(Box::new(order))
                 }
             },
             "trade" => {
@@ -283,7 +298,10 @@ impl Coinbase {
                         );
                     }
                 } else {
-                    trade_resources.insert(transaction.trade.as_ref().unwrap().user_reference.to_string(), TradeResource::new(transaction.created_at));
+                    trade_resources.insert(
+                        transaction.trade.as_ref().unwrap().user_reference.to_string(),
+                        TradeResource::new(transaction.created_at, transaction.native_amount.currency.to_string())
+                    );
                     if transaction.amount.amount.parse::<f64>().unwrap() < 0.0 {
                         trade_resources.get_mut(&transaction.trade.as_ref().unwrap().user_reference).unwrap().set_sell_side(
                             transaction.id,
@@ -304,7 +322,14 @@ impl Coinbase {
                 Ok(Box::new(Dud {}))
             },
             _ => {
-                return Err(CryptfolioError::SyncError(format!("Unknown type of transaction '{}'", transaction.type_transaction)));
+                // Keep syncing the remaining history; unknown types are reported through diagnostics.
+                Ok(Box::new(UnknownTransaction::new(
+                    transaction.id,
+                    transaction.created_at,
+                    transaction.amount.currency,
+                    transaction.type_transaction.to_string(),
+                    "Coinbase".to_string()
+                )))
             }
         }
     }
@@ -330,6 +355,7 @@ impl SyncClient for Coinbase {
     async fn sync(&self) -> Result<Vec<Box<dyn DatabaseEntry + Send>>, CryptfolioError> {
         let mut result = Vec::<Box<dyn DatabaseEntry + Send>>::new();
         let mut trade_resources = HashMap::<String, TradeResource>::new();
+        let synced_at = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
         match self.client.fetch_accounts().await {
             Ok(response) => {
                 for account in response {
@@ -342,6 +368,13 @@ impl SyncClient for Coinbase {
                         account.currency.code.to_string(),
                         "Coinbase".to_string()
                     )));
+                    result.push(Box::new(AccountBalance::new(
+                        account.id.to_string(),
+                        synced_at.to_string(),
+                        account.currency.code.to_string(),
+                        "Coinbase".to_string(),
+                        account.balance.amount.parse::<f64>().unwrap_or(0.0)
+                    )));
                     match self.client.fetch_account_transactions(&account.id).await {
                         Ok(response) => {
                             for transaction in response {
@@ -373,7 +406,7 @@ impl SyncClient for Coinbase {
                 result.push(Box::new(CoinOrder::new(
                     trade.1.buy_side_id,
                     trade.1.created_at.to_string(),
-                    format!("{}-USD", trade.1.buy_side_currency),
+                    format!("{}-{}", trade.1.buy_side_currency, trade.1.native_currency),
                     trade.1.buy_side_native_amount / trade.1.buy_side_amount,
                     trade.1.buy_side_amount,
                     trade.1.sell_side_native_amount.abs() - trade.1.buy_side_native_amount,
@@ -383,7 +416,7 @@ impl SyncClient for Coinbase {
                 result.push(Box::new(CoinOrder::new(
                     trade.1.sell_side_id,
                     trade.1.created_at.to_string(),
-                    format!("{}-USD", trade.1.sell_side_currency),
+                    format!("{}-{}", trade.1.sell_side_currency, trade.1.native_currency),
                     trade.1.sell_side_native_amount / trade.1.sell_side_amount,
                     trade.1.sell_side_amount,
                     0.0,
@@ -441,6 +474,7 @@ impl SyncClient for Coinbase {
 
 struct TradeResource {
     pub created_at: String,
+    pub native_currency: String,
     pub buy_side_id: String,
     pub buy_side_currency: String,
     pub buy_side_amount: f64,
@@ -452,9 +486,10 @@ struct TradeResource {
 }
 
 impl TradeResource {
-    pub fn new(created_at: String) -> TradeResource {
+    pub fn new(created_at: String, native_currency: String) -> TradeResource {
         TradeResource { 
            created_at: created_at, 
+           native_currency: native_currency,
            buy_side_id: String::new(), 
            buy_side_currency: String::new(),
            buy_side_amount: 0.0, 
@@ -0,0 +1,232 @@
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// coinbase_advanced.rs
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Description:
+///     Coinbase Advanced Trade (Exchange) implementation for syncing fills, the successor of
+///     Coinbase Pro.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Usage:
+///     let advanced = CoinbaseAdvanced::new("<api key>", "<api secret>").unwrap();
+///     let handle = app.add_platform("Default profile", advanced);
+///     app.sync_platform(handle).await.unwrap();
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Notes:
+///     Signs requests with Coinbase API keys (key and secret, as used by Coinbase). Cloud (CDP)
+///     keys, which sign with a JWT, are not supported.
+/// 
+///     Unlike Coinbase Pro, Advanced Trade settles into the same wallets as Coinbase, so its
+///     accounts and fills are recorded under the 'Coinbase' platform. Each portfolio's accounts
+///     are recorded, and each fill's description names its portfolio.
+/// 
+///     Coinbase also imports Advanced Trade fills, from the 'advanced_trade_fill' transactions of
+///     each wallet, but their fee is only derived from the native amount. When both are synced,
+///     the duplicates imported by Coinbase are removed in favour of these fills, matched by the
+///     id of their order (see DatabaseScript::update_default_values).
+/// 
+///     The Pro API has been shut down, and Coinbase Pro keys do not authenticate here. Existing
+///     'Coinbase Pro' connections are flagged as needing new keys when the database is opened,
+///     and are no longer loaded (see CryptfolioApp::get_connections_needing_keys); they are to be
+///     added again as Coinbase Advanced connections with new keys. Orders already imported from
+///     Coinbase Pro keep their platform.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+
+use crate::database::entry::{
+    AccountBalance, CoinAccount, CoinOrder, DatabaseEntry, PlatformConnection, PlatformConnectionData,
+};
+use crate::error::CryptfolioError;
+use crate::platform::SyncClient;
+use async_trait::async_trait;
+use chrono::DateTime;
+use hmac::{Hmac, Mac};
+use serde_json::Value;
+use sha2::Sha256;
+use std::collections::HashMap;
+
+const API_URL: &str = "https://api.coinbase.com";
+const PAGE_SIZE: usize = 250;
+
+pub struct CoinbaseAdvanced {
+    api_key: String,
+    api_secret: String,
+    client: reqwest::Client,
+}
+
+impl CoinbaseAdvanced {
+    pub fn new(api_key: &str, api_secret: &str) -> Result<CoinbaseAdvanced, CryptfolioError> {
+        if api_key.starts_with("organizations/") {
+            return Err(CryptfolioError::InvalidConnectionData("Cloud (CDP) API keys are not supported".to_string()));
+        }
+        Ok(
+            CoinbaseAdvanced {
+                api_key: api_key.to_string(),
+                api_secret: api_secret.to_string(),
+                client: reqwest::Client::new(),
+            }
+        )
+    }
+
+    pub fn get_api_key(&self) -> String {
+        self.api_key.to_string()
+    }
+
+    pub fn get_api_secret(&self) -> String {
+        self.api_secret.to_string()
+    }
+
+    fn parse_amount(value: &Value) -> f64 {
+        value.as_str().and_then(|x| x.parse::<f64>().ok()).unwrap_or(0.0)
+    }
+
+    /// CB-ACCESS-SIGN is the hex HMAC-SHA256 of the timestamp, method and path (without the
+    /// query). Requests are all GETs, so there is no body to sign.
+    fn sign(&self, timestamp: i64, method: &str, path: &str) -> Result<String, CryptfolioError> {
+        let mut mac = match Hmac::<Sha256>::new_from_slice(self.api_secret.as_bytes()) {
+            Ok(mac) => { mac },
+            Err(e) => { return Err(CryptfolioError::CoinbaseAdvancedAPIError(e.to_string())); }
+        };
+        mac.update(format!("{}{}{}", timestamp, method, path).as_bytes());
+        Ok(mac.finalize().into_bytes().iter().map(|x| format!("{:02x}", x)).collect())
+    }
+
+    async fn request(&self, path: &str, params: &str) -> Result<Value, CryptfolioError> {
+        let timestamp = chrono::Utc::now().timestamp();
+        let url = if params.is_empty() { format!("{}{}", API_URL, path) } else { format!("{}{}?{}", API_URL, path, params) };
+        let response = self.client
+            .get(url)
+            .header("CB-ACCESS-KEY", self.api_key.as_str())
+            .header("CB-ACCESS-SIGN", self.sign(timestamp, "GET", path)?)
+            .header("CB-ACCESS-TIMESTAMP", timestamp.to_string())
+            .send()
+            .await;
+        let response = match response {
+            Ok(response) => { response },
+            Err(e) => { return Err(CryptfolioError::CoinbaseAdvancedAPIError(e.to_string())); }
+        };
+        if !response.status().is_success() {
+            return Err(CryptfolioError::CoinbaseAdvancedAPIError(format!("{} returned {}", path, response.status())));
+        }
+        match response.json::<Value>().await {
+            Ok(response) => { Ok(response) },
+            Err(e) => { Err(CryptfolioError::CoinbaseAdvancedAPIError(e.to_string())) }
+        }
+    }
+
+    /// Follows the cursor until a page reports that there are no more.
+    async fn fetch_paged(&self, path: &str, params: &str, key: &str) -> Result<Vec<Value>, CryptfolioError> {
+        let mut result = Vec::<Value>::new();
+        let mut cursor = String::new();
+        loop {
+            let query = if cursor.is_empty() {
+                format!("{}limit={}", params, PAGE_SIZE)
+            } else {
+                format!("{}limit={}&cursor={}", params, PAGE_SIZE, cursor)
+            };
+            let page = self.request(path, query.as_str()).await?;
+            result.extend(page[key].as_array().cloned().unwrap_or_default());
+
+            // Accounts report 'has_next', while fills only return a cursor when there are more.
+            cursor = page["cursor"].as_str().unwrap_or("").to_string();
+            if cursor.is_empty() || page["has_next"] == false {
+                break;
+            }
+        }
+        Ok(result)
+    }
+
+    fn process_fill(&self, fill: &Value, portfolios: &HashMap<String, String>) -> Box<dyn DatabaseEntry + Send> {
+        let price = Self::parse_amount(&fill["price"]);
+        let size = Self::parse_amount(&fill["size"]);
+
+        // Market orders placed in the quote currency report the size in the quote currency.
+        let size = if fill["size_in_quote"].as_bool().unwrap_or(false) && price > 0.0 { size / price } else { size };
+        let date = DateTime::parse_from_rfc3339(fill["trade_time"].as_str().unwrap_or(""))
+            .map(|x| x.naive_utc().format("%Y-%m-%dT%H:%M:%SZ").to_string())
+            .unwrap_or_default();
+        let portfolio = fill["retail_portfolio_id"]
+            .as_str()
+            .and_then(|x| portfolios.get(x))
+            .map(|x| x.as_str())
+            .unwrap_or("Default");
+
+        let mut order = CoinOrder::new(
+            fill["entry_id"].as_str().unwrap_or("").to_string(),
+            date,
+            fill["product_id"].as_str().unwrap_or("").to_string(),
+            price,
+            size,
+            Self::parse_amount(&fill["commission"]),
+            fill["side"].as_str().unwrap_or("").to_lowercase(),
+            "Coinbase".to_string()
+        );
+        order.set_description(format!("Advanced Trade: {}", portfolio));
+        order.set_order_id(fill["order_id"].as_str().unwrap_or("").to_string());
+        Box::new(order)
+    }
+}
+
+#[async_trait]
+impl SyncClient for CoinbaseAdvanced {
+    fn get_name(&self) -> &str {
+        "Coinbase Advanced"
+    }
+
+    fn get_connection(&self, nickname: &String) -> PlatformConnection {
+        return PlatformConnection::new(
+            nickname.to_string(),
+            "Coinbase Advanced".to_string(),
+            vec![
+                PlatformConnectionData { key: "API Key".to_string(), value: self.get_api_key() },
+                PlatformConnectionData { key: "API Secret".to_string(), value: self.get_api_secret() }
+            ]
+        );
+    }
+
+    async fn sync(&self) -> Result<Vec<Box<dyn DatabaseEntry + Send>>, CryptfolioError> {
+        let mut result = Vec::<Box<dyn DatabaseEntry + Send>>::new();
+        let synced_at = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+
+        let response = self.request("/api/v3/brokerage/portfolios", "").await?;
+        let mut portfolios = HashMap::<String, String>::new();
+        for portfolio in response["portfolios"].as_array().cloned().unwrap_or_default() {
+            if portfolio["deleted"].as_bool().unwrap_or(false) {
+                continue;
+            }
+            portfolios.insert(
+                portfolio["uuid"].as_str().unwrap_or("").to_string(),
+                portfolio["name"].as_str().unwrap_or("").to_string()
+            );
+        }
+
+        for uuid in portfolios.keys() {
+            let params = format!("retail_portfolio_id={}&", uuid);
+            for account in self.fetch_paged("/api/v3/brokerage/accounts", params.as_str(), "accounts").await? {
+                let id = account["uuid"].as_str().unwrap_or("").to_string();
+                let currency = account["currency"].as_str().unwrap_or("").to_string();
+                result.push(Box::new(CoinAccount::new(id.to_string(), currency.to_string(), "Coinbase".to_string())));
+                result.push(Box::new(AccountBalance::new(
+                    id,
+                    synced_at.to_string(),
+                    currency,
+                    "Coinbase".to_string(),
+                    Self::parse_amount(&account["available_balance"]["value"]) + Self::parse_amount(&account["hold"]["value"])
+                )));
+            }
+        }
+
+        for fill in self.fetch_paged("/api/v3/brokerage/orders/historical/fills", "", "fills").await? {
+            if fill["trade_type"].as_str().unwrap_or("FILL") != "FILL" {
+                continue;
+            }
+            result.push(self.process_fill(&fill, &portfolios));
+        }
+        Ok(result)
+    }
+}
\ No newline at end of file
@@ -14,13 +14,22 @@
 /// ///////////////////////////////////////////////////////////////////////////////////////////////
 /// 
 /// Notes:
+///     The Coinbase Pro API has been shut down, and replaced by Advanced Trade (CoinbaseAdvanced).
+///     Saved Coinbase Pro connections are flagged as needing new keys ("Needs New Keys") when
+///     the database is opened (see DatabaseScript::flag_coinbase_pro_connections), and are no
+///     longer loaded. CryptfolioApp::get_connections_needing_keys lists them, so that they can
+///     be added again as Coinbase Advanced connections with new keys.
 /// 
 /// ///////////////////////////////////////////////////////////////////////////////////////////////
 
-use crate::database::entry::{CoinAccount, CoinOrder, CoinTransfer, FiatTransfer, PlatformConnection, PlatformConnectionData};
+use crate::database::entry::{
+    AccountBalance, CoinAccount, CoinOrder, CoinTransfer, FiatTransfer, PlatformConnection, PlatformConnectionData,
+    UnknownTransaction,
+};
 use crate::database::entry::DatabaseEntry;
 use crate::error::CryptfolioError;
 use crate::platform::SyncClient;
+use crate::util::Util;
 use async_trait::async_trait;
 use coinbase::pro::account::Account;
 use coinbase::pro::client::Client;
@@ -59,6 +68,12 @@ impl CoinbasePro {
         self.passphrase.to_string()
     }
 
+    /// Coinbase Pro answers a product which does not exist with 'NotFound' (or 'Invalid product_id').
+    fn is_unknown_product(error: &str) -> bool {
+        let error = error.to_lowercase();
+        error.contains("notfound") || error.contains("not found") || error.contains("product")
+    }
+
     fn process_fill(&self, fill: Order) -> Box<dyn DatabaseEntry + Send> {
         Box::new(CoinOrder::new(
             fill.order_id,
@@ -104,17 +119,26 @@ impl CoinbasePro {
                     fee = 0.0;
                 }
             },
-            _ => { origin = "".to_string(); destination = "".to_string(); fee = f64::NAN; }
+            _ => {
+                return Box::new(UnknownTransaction::new(
+                    transfer.id,
+                    transfer.created_at,
+                    account.currency.to_string(),
+                    transfer.type_transfer.to_string(),
+                    "Coinbase Pro".to_string()
+                ));
+            }
         }
 
         // Separate fiat transfers to proper table.
-        if account.currency == "USD" {
+        if Util::is_fiat(account.currency.as_str()) {
             Box::new(FiatTransfer::new(
                 transfer.id,
                 transfer.created_at,
                 origin,
                 destination,
-                transfer.amount.parse::<f64>().unwrap()
+                transfer.amount.parse::<f64>().unwrap(),
+                account.currency.to_string()
             ))
         } else {
             Box::new(CoinTransfer::new(
@@ -150,22 +174,48 @@ impl SyncClient for CoinbasePro {
 
     async fn sync(&self) -> Result<Vec<Box<dyn DatabaseEntry + Send>>, CryptfolioError> {
         let mut result = Vec::<Box<dyn DatabaseEntry + Send>>::new();
+        let synced_at = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
         match self.client.fetch_accounts().await {
             Ok(accounts) => {
+                // Fills are fetched by product, so each coin is paired with every fiat currency held.
+                let mut quotes: Vec<String> = accounts
+                    .iter()
+                    .filter(|x| Util::is_fiat(x.currency.as_str()) && x.currency != "USD")
+                    .map(|x| x.currency.to_string())
+                    .collect();
+                quotes.insert(0, "USD".to_string());
+
                 for account in accounts {
                     result.push(Box::new(CoinAccount::new(
                         account.id.to_string(),
                         account.currency.to_string(),
                         "Coinbase Pro".to_string()
                     )));
-                    match self.client.fetch_filled_orders_pag(&format!("{}-USD", account.currency)).await {
-                        Ok(fills) => {
-                            for fill in fills {
-                                result.push(self.process_fill(fill));
+                    result.push(Box::new(AccountBalance::new(
+                        account.id.to_string(),
+                        synced_at.to_string(),
+                        account.currency.to_string(),
+                        "Coinbase Pro".to_string(),
+                        account.balance.parse::<f64>().unwrap_or(0.0)
+                    )));
+                    for quote in &quotes {
+                        if Util::is_fiat(account.currency.as_str()) {
+                            break;
+                        }
+                        match self.client.fetch_filled_orders_pag(&format!("{}-{}", account.currency, quote)).await {
+                            Ok(fills) => {
+                                for fill in fills {
+                                    result.push(self.process_fill(fill));
+                                }
+                            },
+                            Err(e) => {
+                                // Not every coin is listed against every fiat currency, but any other
+                                // error (e.g. authentication or rate limiting) still fails the sync.
+                                if quote != "USD" && Self::is_unknown_product(e.to_string().as_str()) {
+                                    continue;
+                                }
+                                return Err(CryptfolioError::CoinbaseProAPIError(e.to_string()));
                             }
-                        },
-                        Err(e) => {
-                            return Err(CryptfolioError::CoinbaseProAPIError(e.to_string()));
                         }
                     }
                     match self.client.fetch_transfers(&account.id).await {
@@ -0,0 +1,446 @@
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// kraken.rs
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Description:
+///     Kraken (Exchange) implementation for syncing transaction history.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Usage:
+///     let kraken = Kraken::new("<api key>", "<api secret>").unwrap();
+///     let handle = app.add_platform("Kraken", kraken);
+///     app.sync_platform(handle).await.unwrap();
+/// 
+///     // Recorded API responses (the 'result' of each endpoint) can be processed directly.
+///     let entries = kraken.process_history(&asset_pairs, &balances, &ledger, &trades).unwrap();
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Notes:
+///     The API key needs the 'Query Funds' and 'Query Closed Orders & Trades' permissions.
+/// 
+///     Trades come from the trades history, and everything else from the ledger. The ledger's
+///     own 'trade' entries are skipped, as they only repeat the trades. Instant buys made with
+///     the 'Buy Crypto' button show up in the ledger as a 'spend' and a 'receive' sharing a
+///     reference id, and are paired into an order.
+/// 
+///     Kraken's legacy asset codes (e.g. XXBT, XETH, ZUSD) are mapped to standard symbols, and
+///     staked or opted-in balances (e.g. DOT.S, ETH2.S, USDC.M) are treated as the underlying
+///     coin, so moving coins in and out of staking nets out.
+/// 
+///     Trades on pairs missing from AssetPairs (e.g. delisted) take their base and quote from the
+///     trade's ledger legs, and are left as unknown transactions if these are missing too. Trades
+///     quoted in a coin (e.g. DOT-BTC) are valued through the price source of the app.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+
+use crate::database::entry::{
+    AccountBalance, CoinAccount, CoinOrder, CoinReward, CoinTransfer, DatabaseEntry, FiatTransfer, PlatformConnection,
+    PlatformConnectionData, UnknownTransaction,
+};
+use crate::error::CryptfolioError;
+use crate::platform::SyncClient;
+use crate::util::Util;
+use async_trait::async_trait;
+use chrono::DateTime;
+use hmac::{Hmac, Mac};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256, Sha512};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+const API_URL: &str = "https://api.kraken.com";
+
+// Legacy asset codes still used by the API for the oldest listings.
+const ASSET_CODES: [(&str, &str); 24] = [
+    ("XXBT", "BTC"), ("XBT", "BTC"), ("XXDG", "DOGE"), ("XDG", "DOGE"), ("XETH", "ETH"), ("XETC", "ETC"),
+    ("XLTC", "LTC"), ("XXRP", "XRP"), ("XXLM", "XLM"), ("XXMR", "XMR"), ("XZEC", "ZEC"), ("XREP", "REP"),
+    ("XMLN", "MLN"), ("XICN", "ICN"), ("XXTZ", "XTZ"), ("ETH2", "ETH"), ("ZUSD", "USD"), ("ZEUR", "EUR"),
+    ("ZGBP", "GBP"), ("ZCAD", "CAD"), ("ZJPY", "JPY"), ("ZAUD", "AUD"), ("ZCHF", "CHF"), ("KFEE", "KFEE"),
+];
+
+pub struct Kraken {
+    api_key: String,
+    api_secret: String,
+    client: reqwest::Client,
+    nonce: AtomicU64,
+}
+
+impl Kraken {
+    pub fn new(api_key: &str, api_secret: &str) -> Result<Kraken, CryptfolioError> {
+        Ok(
+            Kraken {
+                api_key: api_key.to_string(),
+                api_secret: api_secret.to_string(),
+                client: reqwest::Client::new(),
+                nonce: AtomicU64::new(0),
+            }
+        )
+    }
+
+    pub fn get_api_key(&self) -> String {
+        self.api_key.to_string()
+    }
+
+    pub fn get_api_secret(&self) -> String {
+        self.api_secret.to_string()
+    }
+
+    /// Maps a Kraken asset code (e.g. 'XXBT', 'ZUSD', 'DOT.S') to its standard symbol.
+    pub fn normalize_asset(asset: &str) -> String {
+        let asset = asset.split('.').next().unwrap_or(asset);
+        match ASSET_CODES.iter().find(|x| x.0 == asset) {
+            Some((_, symbol)) => { symbol.to_string() },
+            None => { asset.to_string() }
+        }
+    }
+
+    fn get_account(asset: &str) -> String {
+        format!("kraken:{}", asset)
+    }
+
+    fn format_timestamp(time: &Value) -> String {
+        let time = time.as_f64().unwrap_or(0.0);
+        DateTime::from_timestamp(time.trunc() as i64, 0).unwrap_or_default().format("%Y-%m-%dT%H:%M:%SZ").to_string()
+    }
+
+    fn parse_amount(value: &Value) -> f64 {
+        value.as_str().and_then(|x| x.parse::<f64>().ok()).unwrap_or(0.0)
+    }
+
+    /// Nonces must always increase, even for requests made within the same millisecond.
+    fn next_nonce(&self) -> u64 {
+        let now = chrono::Utc::now().timestamp_millis() as u64;
+        let last = self.nonce.fetch_max(now, Ordering::SeqCst);
+        if last >= now {
+            return self.nonce.fetch_add(1, Ordering::SeqCst) + 1;
+        }
+        now
+    }
+
+    /// API-Sign is HMAC-SHA512 of the URI path and SHA256(nonce + POST data), keyed with the
+    /// base64-decoded secret.
+    fn sign(&self, path: &str, nonce: u64, body: &str) -> Result<String, CryptfolioError> {
+        let secret = match base64::decode(self.api_secret.as_str()) {
+            Ok(secret) => { secret },
+            Err(_) => { return Err(CryptfolioError::InvalidConnectionData("API secret is not valid base64".to_string())); }
+        };
+        let mut message = path.as_bytes().to_vec();
+        message.extend(Sha256::digest(format!("{}{}", nonce, body).as_bytes()));
+        let mut mac = match Hmac::<Sha512>::new_from_slice(&secret) {
+            Ok(mac) => { mac },
+            Err(e) => { return Err(CryptfolioError::KrakenAPIError(e.to_string())); }
+        };
+        mac.update(&message);
+        Ok(base64::encode(mac.finalize().into_bytes()))
+    }
+
+    fn read_result(response: Value) -> Result<Value, CryptfolioError> {
+        let errors: Vec<String> = response["error"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|x| x.as_str().map(|x| x.to_string()))
+            .collect();
+        if !errors.is_empty() {
+            return Err(CryptfolioError::KrakenAPIError(errors.join(", ")));
+        }
+        Ok(response["result"].clone())
+    }
+
+    async fn public_request(&self, method: &str) -> Result<Value, CryptfolioError> {
+        let response = match self.client.get(format!("{}/0/public/{}", API_URL, method)).send().await {
+            Ok(response) => { response },
+            Err(e) => { return Err(CryptfolioError::KrakenAPIError(e.to_string())); }
+        };
+        match response.json::<Value>().await {
+            Ok(response) => { Self::read_result(response) },
+            Err(e) => { Err(CryptfolioError::KrakenAPIError(e.to_string())) }
+        }
+    }
+
+    async fn private_request(&self, method: &str, params: &str) -> Result<Value, CryptfolioError> {
+        let path = format!("/0/private/{}", method);
+        let nonce = self.next_nonce();
+        let body = if params.is_empty() { format!("nonce={}", nonce) } else { format!("nonce={}&{}", nonce, params) };
+        let response = self.client
+            .post(format!("{}{}", API_URL, path))
+            .header("API-Key", self.api_key.as_str())
+            .header("API-Sign", self.sign(path.as_str(), nonce, body.as_str())?)
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .body(body)
+            .send()
+            .await;
+        let response = match response {
+            Ok(response) => { response },
+            Err(e) => { return Err(CryptfolioError::KrakenAPIError(e.to_string())); }
+        };
+        match response.json::<Value>().await {
+            Ok(response) => { Self::read_result(response) },
+            Err(e) => { Err(CryptfolioError::KrakenAPIError(e.to_string())) }
+        }
+    }
+
+    /// Ledgers and TradesHistory return 50 entries at a time, keyed by id, with the total count.
+    async fn fetch_paged(&self, method: &str, key: &str) -> Result<Value, CryptfolioError> {
+        let mut entries = Map::<String, Value>::new();
+        loop {
+            let page = self.private_request(method, format!("ofs={}", entries.len()).as_str()).await?;
+            let count = page["count"].as_u64().unwrap_or(0) as usize;
+            let page = page[key].as_object().cloned().unwrap_or_default();
+            if page.is_empty() {
+                break;
+            }
+            entries.extend(page);
+            if entries.len() >= count {
+                break;
+            }
+        }
+        Ok(Value::Object(entries))
+    }
+
+    /// Maps each pair's name (e.g. 'XXBTZUSD') and alternate name (e.g. 'XBTUSD') to its base and
+    /// quote symbols.
+    fn parse_asset_pairs(asset_pairs: &Value) -> HashMap<String, (String, String)> {
+        let mut pairs = HashMap::<String, (String, String)>::new();
+        for (name, pair) in asset_pairs.as_object().cloned().unwrap_or_default() {
+            let assets = (
+                Self::normalize_asset(pair["base"].as_str().unwrap_or("")),
+                Self::normalize_asset(pair["quote"].as_str().unwrap_or(""))
+            );
+            if let Some(altname) = pair["altname"].as_str() {
+                pairs.insert(altname.to_string(), assets.clone());
+            }
+            pairs.insert(name, assets);
+        }
+        pairs
+    }
+
+    /// Processes the 'result' of the AssetPairs, Balance, Ledgers ('ledger') and TradesHistory
+    /// ('trades') endpoints, whether fetched or recorded.
+    pub fn process_history(&self, asset_pairs: &Value, balances: &Value, ledger: &Value, trades: &Value) -> Result<Vec<Box<dyn DatabaseEntry + Send>>, CryptfolioError> {
+        let mut result = Vec::<Box<dyn DatabaseEntry + Send>>::new();
+        let pairs = Self::parse_asset_pairs(asset_pairs);
+        let synced_at = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+
+        // Staked and spot balances of the same coin are summed.
+        let mut assets = BTreeSet::<String>::new();
+        let mut totals = HashMap::<String, f64>::new();
+        for (asset, balance) in balances.as_object().cloned().unwrap_or_default() {
+            let asset = Self::normalize_asset(asset.as_str());
+            *totals.entry(asset.to_string()).or_insert(0.0) += Self::parse_amount(&balance);
+            assets.insert(asset);
+        }
+
+        let mut entries: Vec<(String, Value)> = ledger.as_object().cloned().unwrap_or_default().into_iter().collect();
+        entries.sort_by(|a, b| a.1["time"].as_f64().unwrap_or(0.0).partial_cmp(&b.1["time"].as_f64().unwrap_or(0.0)).unwrap());
+        let mut instant_buys = Vec::<(String, Vec<(String, Value)>)>::new();
+        let mut trade_legs = HashMap::<String, Vec<(String, f64)>>::new();
+        for (id, entry) in entries {
+            let asset = Self::normalize_asset(entry["asset"].as_str().unwrap_or(""));
+            assets.insert(asset.to_string());
+            let date = Self::format_timestamp(&entry["time"]);
+            let amount = Self::parse_amount(&entry["amount"]);
+            let fee = Self::parse_amount(&entry["fee"]);
+
+            match (entry["type"].as_str().unwrap_or(""), entry["subtype"].as_str().unwrap_or("")) {
+                ("trade", _) => {
+                    // Imported from the trades history instead, the legs only being kept to split
+                    // pairs which are no longer listed.
+                    let refid = entry["refid"].as_str().unwrap_or("").to_string();
+                    trade_legs.entry(refid).or_default().push((asset, amount));
+                },
+                ("deposit", _) => {
+                    // Deposit fees are taken from the amount received.
+                    if Util::is_fiat(asset.as_str()) {
+                        result.push(Box::new(FiatTransfer::new(id, date, String::new(), Self::get_account(asset.as_str()), amount - fee, asset)));
+                    } else {
+                        result.push(Box::new(CoinTransfer::new(id, date, String::new(), Self::get_account(asset.as_str()), asset, amount - fee, 0.0)));
+                    }
+                },
+                ("withdrawal", _) => {
+                    // Fiat transfers have no fee, so the fee is included in the amount withdrawn.
+                    if Util::is_fiat(asset.as_str()) {
+                        result.push(Box::new(FiatTransfer::new(id, date, Self::get_account(asset.as_str()), String::new(), amount.abs() + fee, asset)));
+                    } else {
+                        result.push(Box::new(CoinTransfer::new(id, date, Self::get_account(asset.as_str()), String::new(), asset, amount.abs(), fee)));
+                    }
+                },
+                ("staking", _) | ("earn", "reward") => {
+                    result.push(Box::new(CoinReward::new(
+                        id,
+                        date,
+                        asset,
+                        0.0,
+                        amount - fee,
+                        "Staking".to_string(),
+                        "Kraken Staking".to_string(),
+                        "Kraken".to_string(),
+                        "USD".to_string()
+                    )));
+                },
+                ("transfer", "spottostaking") | ("transfer", "stakingfromspot") | ("transfer", "stakingtospot") |
+                ("transfer", "spotfromstaking") | ("transfer", "spottofutures") | ("transfer", "spotfromfutures") |
+                ("earn", "allocation") | ("earn", "deallocation") | ("earn", "autoallocation") | ("earn", "migration") => {
+                    // Moves between spot and staking (or futures) of the same coin net out.
+                },
+                ("spend", _) | ("receive", _) => {
+                    let refid = entry["refid"].as_str().unwrap_or("").to_string();
+                    match instant_buys.iter_mut().find(|x| x.0 == refid) {
+                        Some(buy) => { buy.1.push((id, entry)); },
+                        None => { instant_buys.push((refid, vec![(id, entry)])); }
+                    }
+                },
+                (r#type, _) => {
+                    result.push(Box::new(UnknownTransaction::new(id, date, asset, r#type.to_string(), "Kraken".to_string())));
+                }
+            }
+        }
+
+        for (refid, legs) in instant_buys {
+            let spend = legs.iter().find(|x| x.1["type"] == "spend");
+            let receive = legs.iter().find(|x| x.1["type"] == "receive");
+            match (spend, receive, legs.len()) {
+                (Some(spend), Some(receive), 2) => {
+                    result.push(Self::process_instant_buy(refid, &spend.1, &receive.1));
+                },
+                _ => {
+                    for (id, entry) in legs {
+                        result.push(Box::new(UnknownTransaction::new(
+                            id,
+                            Self::format_timestamp(&entry["time"]),
+                            Self::normalize_asset(entry["asset"].as_str().unwrap_or("")),
+                            entry["type"].as_str().unwrap_or("").to_string(),
+                            "Kraken".to_string()
+                        )));
+                    }
+                }
+            }
+        }
+
+        for (id, trade) in trades.as_object().cloned().unwrap_or_default() {
+            let name = trade["pair"].as_str().unwrap_or("");
+            let side = trade["type"].as_str().unwrap_or("");
+            let (base, quote) = match pairs.get(name).cloned().or_else(|| Self::split_pair(trade_legs.get(&id), side)) {
+                Some(pair) => { pair },
+                None => {
+                    result.push(Box::new(UnknownTransaction::new(
+                        id,
+                        Self::format_timestamp(&trade["time"]),
+                        name.to_string(),
+                        "trade".to_string(),
+                        "Kraken".to_string()
+                    )));
+                    continue;
+                }
+            };
+            assets.insert(base.to_string());
+            assets.insert(quote.to_string());
+            result.push(Box::new(CoinOrder::new(
+                id,
+                Self::format_timestamp(&trade["time"]),
+                format!("{}-{}", base, quote),
+                Self::parse_amount(&trade["price"]),
+                Self::parse_amount(&trade["vol"]),
+                Self::parse_amount(&trade["fee"]),
+                side.to_string(),
+                "Kraken".to_string()
+            )));
+        }
+
+        // One account per asset, so transfers and orders on Kraken resolve to the platform.
+        for asset in assets {
+            if asset.is_empty() || asset == "KFEE" {
+                continue;
+            }
+            result.push(Box::new(CoinAccount::new(Self::get_account(asset.as_str()), asset.to_string(), "Kraken".to_string())));
+            result.push(Box::new(AccountBalance::new(
+                Self::get_account(asset.as_str()),
+                synced_at.to_string(),
+                asset.to_string(),
+                "Kraken".to_string(),
+                totals.get(&asset).cloned().unwrap_or(0.0)
+            )));
+        }
+        Ok(result)
+    }
+
+    /// Pairs which are no longer listed (e.g. delisted coins) are split from the trade's ledger
+    /// legs instead: the base is received on a buy, and spent on a sell.
+    fn split_pair(legs: Option<&Vec<(String, f64)>>, side: &str) -> Option<(String, String)> {
+        let legs = legs?;
+        let spent = legs.iter().find(|x| x.1 < 0.0)?.0.to_string();
+        let received = legs.iter().find(|x| x.1 > 0.0)?.0.to_string();
+        match side {
+            "buy" => { Some((received, spent)) },
+            "sell" => { Some((spent, received)) },
+            _ => { None }
+        }
+    }
+
+    /// An instant buy (or sell) spends one asset to receive another. The fee is charged on the
+    /// spent side for a buy, and on the received side for a sale to fiat.
+    fn process_instant_buy(refid: String, spend: &Value, receive: &Value) -> Box<dyn DatabaseEntry + Send> {
+        let spent_asset = Self::normalize_asset(spend["asset"].as_str().unwrap_or(""));
+        let received_asset = Self::normalize_asset(receive["asset"].as_str().unwrap_or(""));
+        let spent = Self::parse_amount(&spend["amount"]).abs();
+        let received = Self::parse_amount(&receive["amount"]).abs();
+        let fee = Self::parse_amount(&spend["fee"]) + Self::parse_amount(&receive["fee"]);
+
+        let mut order = if Util::is_fiat(received_asset.as_str()) && !Util::is_fiat(spent_asset.as_str()) {
+            CoinOrder::new(
+                refid,
+                Self::format_timestamp(&spend["time"]),
+                format!("{}-{}", spent_asset, received_asset),
+                if spent > 0.0 { received / spent } else { 0.0 },
+                spent,
+                fee,
+                "sell".to_string(),
+                "Kraken".to_string()
+            )
+        } else {
+            CoinOrder::new(
+                refid,
+                Self::format_timestamp(&receive["time"]),
+                format!("{}-{}", received_asset, spent_asset),
+                if received > 0.0 { spent / received } else { 0.0 },
+                received,
+                fee,
+                "buy".to_string(),
+                "Kraken".to_string()
+            )
+        };
+        order.set_description("Kraken instant buy".to_string());
+        Box::new(order)
+    }
+}
+
+#[async_trait]
+impl SyncClient for Kraken {
+    fn get_name(&self) -> &str {
+        "Kraken"
+    }
+
+    fn get_connection(&self, nickname: &String) -> PlatformConnection {
+        return PlatformConnection::new(
+            nickname.to_string(),
+            "Kraken".to_string(),
+            vec![
+                PlatformConnectionData { key: "API Key".to_string(), value: self.get_api_key() },
+                PlatformConnectionData { key: "API Secret".to_string(), value: self.get_api_secret() },
+            ]
+        );
+    }
+
+    async fn sync(&self) -> Result<Vec<Box<dyn DatabaseEntry + Send>>, CryptfolioError> {
+        let asset_pairs = self.public_request("AssetPairs").await?;
+        let balances = self.private_request("Balance", "").await?;
+        let ledger = self.fetch_paged("Ledgers", "ledger").await?;
+        let trades = self.fetch_paged("TradesHistory", "trades").await?;
+        self.process_history(&asset_pairs, &balances, &ledger, &trades)
+    }
+}
\ No newline at end of file
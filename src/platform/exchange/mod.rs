@@ -1,4 +1,10 @@
+mod binance;
 mod coinbase;
+mod coinbase_advanced;
 mod coinbase_pro;
+mod kraken;
+pub use self::binance::{Binance, BinanceHistory, BinanceRegion};
 pub use self::coinbase::Coinbase;
-pub use self::coinbase_pro::CoinbasePro;
\ No newline at end of file
+pub use self::coinbase_advanced::CoinbaseAdvanced;
+pub use self::coinbase_pro::CoinbasePro;
+pub use self::kraken::Kraken;
\ No newline at end of file
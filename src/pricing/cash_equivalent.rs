@@ -0,0 +1,91 @@
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// cash_equivalent.rs
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Description:
+///     An asset (e.g. a stablecoin) pegged to a fiat currency, and the policy used to account for
+///     it.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Usage:
+///     app.set_cash_equivalent(CashEquivalent::new("USDC", CashEquivalentPolicy::Cash, "USD")).unwrap();
+///     app.set_cash_equivalent(CashEquivalent::new("USDT", CashEquivalentPolicy::Property, "USD")).unwrap();
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Notes:
+///     Cash: the asset is valued at 1:1 with its currency and held as cash. Converting between
+///     the two is not a buy or sell, and transfers of the asset into or out of the portfolio
+///     are contributions.
+/// 
+///     Property: the asset is a coin like any other, with a cost basis of its own. Spending it
+///     on another coin disposes of it at the rate of the day of the currency it is pegged to.
+/// 
+///     Stored in the settings table as 'cash_equivalent:<ASSET>' = '<policy>:<currency>'.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+
+use crate::error::CryptfolioError;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CashEquivalentPolicy {
+    Cash,
+    Property,
+}
+
+impl fmt::Display for CashEquivalentPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Cash => { write!(f, "cash") },
+            Self::Property => { write!(f, "property") },
+        }
+    }
+}
+
+impl FromStr for CashEquivalentPolicy {
+    type Err = CryptfolioError;
+
+    fn from_str(policy: &str) -> Result<CashEquivalentPolicy, CryptfolioError> {
+        match policy {
+            "cash" => { Ok(Self::Cash) },
+            "property" => { Ok(Self::Property) },
+            _ => { Err(CryptfolioError::InvalidSetting(format!("Unknown cash equivalent policy '{}'", policy))) }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CashEquivalent {
+    pub asset: String,
+    pub policy: CashEquivalentPolicy,
+    pub currency: String,
+}
+
+impl CashEquivalent {
+    pub fn new(asset: &str, policy: CashEquivalentPolicy, currency: &str) -> CashEquivalent {
+        CashEquivalent {
+            asset: asset.to_uppercase(),
+            policy: policy,
+            currency: currency.to_uppercase(),
+        }
+    }
+
+    pub fn get_key(&self) -> String {
+        format!("cash_equivalent:{}", self.asset)
+    }
+
+    pub fn get_value(&self) -> String {
+        format!("{}:{}", self.policy, self.currency)
+    }
+
+    pub fn from_setting(key: &str, value: &str) -> Option<CashEquivalent> {
+        let asset = key.strip_prefix("cash_equivalent:")?;
+        let (policy, currency) = value.split_once(':').unwrap_or((value, "USD"));
+        Some(CashEquivalent::new(asset, policy.parse::<CashEquivalentPolicy>().ok()?, currency))
+    }
+}
@@ -0,0 +1,137 @@
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// currency_converter.rs
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Description:
+///     Converts fiat amounts (and amounts in coins used as a currency) into the portfolio's
+///     reporting currency, caching every rate used.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Usage:
+///     let converter = CurrencyConverter::new("USD", Some(Rc::new(rates)));
+///     let amount = converter.convert(100.0, "EUR", NaiveDate::from_ymd_opt(2022, 1, 3).unwrap());
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Notes:
+///     Rates previously stored in the database are loaded into the cache with add_cached_rate()
+///     so that the FX source is only asked for rates which have never been seen. Rates fetched
+///     from the source are returned by get_new_rates() to be stored in turn.
+/// 
+///     When no rate can be found the converted amount is NaN, rather than silently treating the
+///     amount as though it were already in the reporting currency.
+/// 
+///     Cash equivalents (see CashEquivalent) are converted at the rate of the currency they are
+///     pegged to, whether they are held as cash or as property. Any other coin used as a currency
+///     (e.g. BTC on DOT-BTC) is converted at its price from the price source, if one is set.
+///     Conversions which could not be made are kept (see get_missing_rates()) so that they are
+///     reported as diagnostics, rather than only surfacing as NaN.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+
+use crate::pricing::{CashEquivalent, CashEquivalentPolicy, FxRateSource, PriceSource};
+use crate::util::Util;
+use chrono::NaiveDate;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+pub struct CurrencyConverter {
+    reporting_currency: String,
+    source: Option<Rc<dyn FxRateSource>>,
+    cache: RefCell<HashMap<(String, NaiveDate), f64>>,
+    new_rates: RefCell<Vec<(String, NaiveDate, f64)>>,
+    missing_rates: RefCell<Vec<(String, NaiveDate)>>,
+    pegs: HashMap<String, String>,
+    cash: HashSet<String>,
+    prices: Option<Rc<dyn PriceSource>>,
+}
+
+impl CurrencyConverter {
+    pub fn new(reporting_currency: &str, source: Option<Rc<dyn FxRateSource>>) -> CurrencyConverter {
+        CurrencyConverter {
+            reporting_currency: reporting_currency.to_string(),
+            source: source,
+            cache: RefCell::new(HashMap::<(String, NaiveDate), f64>::new()),
+            new_rates: RefCell::new(Vec::<(String, NaiveDate, f64)>::new()),
+            missing_rates: RefCell::new(Vec::<(String, NaiveDate)>::new()),
+            pegs: HashMap::<String, String>::new(),
+            cash: HashSet::<String>::new(),
+            prices: None,
+        }
+    }
+
+    /// Prices coins used as a currency, in the reporting currency.
+    pub fn set_price_source(&mut self, prices: Option<Rc<dyn PriceSource>>) {
+        self.prices = prices;
+    }
+
+    pub fn get_reporting_currency(&self) -> &str {
+        return self.reporting_currency.as_str();
+    }
+
+    pub fn add_cached_rate(&self, currency: &str, date: NaiveDate, rate: f64) {
+        self.cache.borrow_mut().insert((currency.to_string(), date), rate);
+    }
+
+    pub fn add_cash_equivalent(&mut self, cash_equivalent: &CashEquivalent) {
+        self.pegs.insert(cash_equivalent.asset.to_string(), cash_equivalent.currency.to_string());
+        if cash_equivalent.policy == CashEquivalentPolicy::Cash {
+            self.cash.insert(cash_equivalent.asset.to_string());
+        }
+    }
+
+    /// Whether an asset is held as cash: either a fiat currency, or an asset pegged to one.
+    pub fn is_cash(&self, asset: &str) -> bool {
+        return Util::is_fiat(asset) || self.cash.contains(asset);
+    }
+
+    pub fn get_new_rates(&self) -> Vec<(String, NaiveDate, f64)> {
+        self.new_rates.borrow().clone()
+    }
+
+    /// Every currency and date which could not be converted, once each.
+    pub fn get_missing_rates(&self) -> Vec<(String, NaiveDate)> {
+        self.missing_rates.borrow().clone()
+    }
+
+    pub fn get_rate(&self, currency: &str, date: NaiveDate) -> Option<f64> {
+        // Entries stored before currencies were recorded are always in USD.
+        let currency = if currency.is_empty() { "USD" } else { currency };
+        let currency = self.pegs.get(currency).map(|x| x.as_str()).unwrap_or(currency);
+        if currency == self.reporting_currency {
+            return Some(1.0);
+        }
+        if let Some(rate) = self.cache.borrow().get(&(currency.to_string(), date)) {
+            return Some(*rate);
+        }
+
+        // Coin prices are not FX rates, so they are left to the price source to cache.
+        if !Util::is_fiat(currency) {
+            return self.prices.as_ref()?.get_price(currency, date);
+        }
+        let rate = self.source.as_ref()?.get_rate(currency, self.reporting_currency.as_str(), date)?;
+        self.cache.borrow_mut().insert((currency.to_string(), date), rate);
+        self.new_rates.borrow_mut().push((currency.to_string(), date, rate));
+        Some(rate)
+    }
+
+    pub fn convert(&self, amount: f64, currency: &str, date: NaiveDate) -> f64 {
+        if amount == 0.0 {
+            return 0.0;
+        }
+        match self.get_rate(currency, date) {
+            Some(rate) => { amount * rate },
+            None => {
+                let missing = (if currency.is_empty() { "USD" } else { currency }.to_string(), date);
+                if !self.missing_rates.borrow().contains(&missing) {
+                    self.missing_rates.borrow_mut().push(missing);
+                }
+                f64::NAN
+            }
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,74 @@
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// fx_rate_source.rs
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Description:
+///     Trait for anything capable of supplying the exchange rate between two fiat currencies on
+///     a given date, along with an in-memory implementation of daily rates.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Usage:
+///     let mut rates = FxRateTable::new();
+///     rates.add_rate("EUR", "USD", NaiveDate::from_ymd_opt(2022, 1, 3).unwrap(), 1.1300);
+///     app.set_fx_source(Rc::new(rates));
+///     app.set_reporting_currency("USD").unwrap();
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Notes:
+///     A rate is the amount of the quote currency equal to one unit of the base currency.
+/// 
+///     FxRateTable answers for the inverse pair when only one direction has been supplied, and
+///     uses the most recent prior rate when none exists for the requested date.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, HashMap};
+
+pub trait FxRateSource {
+    fn get_rate(&self, base: &str, quote: &str, date: NaiveDate) -> Option<f64>;
+}
+
+pub struct FxRateTable {
+    rates: HashMap<(String, String), BTreeMap<NaiveDate, f64>>,
+}
+
+impl FxRateTable {
+    pub fn new() -> FxRateTable {
+        FxRateTable {
+            rates: HashMap::<(String, String), BTreeMap<NaiveDate, f64>>::new(),
+        }
+    }
+
+    pub fn add_rate(&mut self, base: &str, quote: &str, date: NaiveDate, rate: f64) {
+        self.rates
+            .entry((base.to_string(), quote.to_string()))
+            .or_insert(BTreeMap::<NaiveDate, f64>::new())
+            .insert(date, rate);
+    }
+
+    fn find_rate(&self, base: &str, quote: &str, date: NaiveDate) -> Option<f64> {
+        self.rates
+            .get(&(base.to_string(), quote.to_string()))
+            .and_then(|x| x.range(..=date).next_back())
+            .map(|x| *x.1)
+    }
+}
+
+impl FxRateSource for FxRateTable {
+    fn get_rate(&self, base: &str, quote: &str, date: NaiveDate) -> Option<f64> {
+        if base == quote {
+            return Some(1.0);
+        }
+        if let Some(rate) = self.find_rate(base, quote, date) {
+            return Some(rate);
+        }
+        self.find_rate(quote, base, date)
+            .filter(|x| *x != 0.0)
+            .map(|x| 1.0 / x)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,11 @@
+pub mod cash_equivalent;
+pub mod currency_converter;
+pub mod fx_rate_source;
+pub mod price_history;
+pub mod price_source;
+
+pub use cash_equivalent::{CashEquivalent, CashEquivalentPolicy};
+pub use currency_converter::CurrencyConverter;
+pub use fx_rate_source::{FxRateSource, FxRateTable};
+pub use price_history::PriceHistory;
+pub use price_source::PriceSource;
\ No newline at end of file
@@ -0,0 +1,106 @@
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// price_history.rs
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Description:
+///     In-memory PriceSource of daily closing prices for any number of coins.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Usage:
+///     let mut prices = PriceHistory::new();
+///     prices.add_price("BTC", NaiveDate::from_ymd_opt(2022, 1, 1).unwrap(), 47000.0);
+///     prices.add_price("BTC", NaiveDate::from_ymd_opt(2022, 1, 2).unwrap(), 47300.0);
+///     app.rebuild_history(&prices).unwrap();
+/// 
+///     // or, from a CSV file of 'date,price' rows
+///     let index = PriceHistory::from_csv("INDEX", "local/index.csv").unwrap();
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Notes:
+///     When no price exists for the requested date, the most recent prior price is used. This
+///     covers gaps such as weekends for index data.
+/// 
+///     A CSV header row is optional. When present, the 'close' or 'price' column is used, and
+///     otherwise the second column.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+
+use crate::error::CryptfolioError;
+use crate::pricing::PriceSource;
+use crate::util::Util;
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, HashMap};
+
+pub struct PriceHistory {
+    prices: HashMap<String, BTreeMap<NaiveDate, f64>>,
+}
+
+impl PriceHistory {
+    pub fn new() -> PriceHistory {
+        PriceHistory {
+            prices: HashMap::<String, BTreeMap<NaiveDate, f64>>::new(),
+        }
+    }
+
+    pub fn from_csv(coin: &str, path: &str) -> Result<PriceHistory, CryptfolioError> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => { contents },
+            Err(e) => { return Err(CryptfolioError::PriceHistoryError(format!("{} ({})", path, e))); }
+        };
+        let mut history = PriceHistory::new();
+        let mut column: usize = 1;
+        for (i, line) in contents.lines().enumerate() {
+            let fields: Vec<&str> = line.split(',').map(|x| x.trim().trim_matches('"')).collect();
+            if fields.len() < 2 {
+                continue;
+            }
+            let date = match NaiveDate::parse_from_str(fields[0], "%Y-%m-%d") {
+                Ok(date) => { date },
+                Err(_) => {
+                    match Util::parse_date(fields[0]) {
+                        Ok(date) => { date.date() },
+                        Err(_) => {
+                            // The first row may be a header naming the price column.
+                            if i == 0 {
+                                if let Some(index) = fields.iter().position(|x| x.eq_ignore_ascii_case("close") || x.eq_ignore_ascii_case("price")) {
+                                    column = index;
+                                }
+                                continue;
+                            }
+                            return Err(CryptfolioError::PriceHistoryError(format!("invalid date '{}' on line {}", fields[0], i + 1)));
+                        }
+                    }
+                }
+            };
+            match fields.get(column).and_then(|x| x.parse::<f64>().ok()) {
+                Some(price) => { history.add_price(coin, date, price); },
+                None => { return Err(CryptfolioError::PriceHistoryError(format!("invalid price on line {}", i + 1))); }
+            }
+        }
+        Ok(history)
+    }
+
+    pub fn add_price(&mut self, coin: &str, date: NaiveDate, price: f64) {
+        self.prices
+            .entry(coin.to_string())
+            .or_insert(BTreeMap::<NaiveDate, f64>::new())
+            .insert(date, price);
+    }
+
+    pub fn get_coins(&self) -> Vec<String> {
+        self.prices.keys().map(|x| x.to_string()).collect()
+    }
+}
+
+impl PriceSource for PriceHistory {
+    fn get_price(&self, coin: &str, date: NaiveDate) -> Option<f64> {
+        self.prices
+            .get(coin)
+            .and_then(|x| x.range(..=date).next_back())
+            .map(|x| *x.1)
+    }
+}
@@ -0,0 +1,38 @@
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// price_source.rs
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Description:
+///     Trait for anything capable of supplying the fiat price of a coin on a given date.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Usage:
+///     A HashMap<String, f64> of coin -> spot price may be supplied wherever a PriceSource
+///     is expected. The same price is returned regardless of the requested date.
+/// 
+///     let mut prices = HashMap::<String, f64>::new();
+///     prices.insert("BTC".to_string(), 20000.0);
+///     let portfolio = app.get_portfolio(&prices, None);
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Notes:
+///     Prices are expected in the portfolio's reporting currency.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+
+use chrono::NaiveDate;
+use std::collections::HashMap;
+
+pub trait PriceSource {
+    fn get_price(&self, coin: &str, date: NaiveDate) -> Option<f64>;
+}
+
+impl PriceSource for HashMap<String, f64> {
+    fn get_price(&self, coin: &str, _date: NaiveDate) -> Option<f64> {
+        self.get(coin).map(|x| *x)
+    }
+}
@@ -0,0 +1,134 @@
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// benchmark_record.rs
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Description:
+///     A structure that compares the performance of the portfolio against a benchmark series
+///     (e.g. BTC, ETH or an index) over a period.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Usage:
+///     Retrieve a BenchmarkRecord from a CryptfolioApp after the history has been rebuilt.
+/// 
+///     let benchmark: BenchmarkRecord = app.get_benchmark("BTC", &prices, Period::YearToDate);
+///     for point in benchmark.get_points() {
+///         println!("{} | portfolio: {} | {}: {} | tracking difference: {}%",
+///             point.date, point.portfolio_value, benchmark.get_name(), point.benchmark_value,
+///             point.tracking_difference * 100.0
+///         );
+///     }
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Notes:
+///     The benchmark starts with the value of the portfolio on the first day, and every fiat
+///     cash flow into or out of the portfolio is replayed as a buy or sell of the benchmark at
+///     that day's price. The benchmark value is what the portfolio would be worth had all of its
+///     money been held in the benchmark instead.
+/// 
+///     Returns are cumulative time-weighted returns since the start of the period. The relative
+///     return is the geometric excess of the portfolio over the benchmark, (1 + rp) / (1 + rb) - 1,
+///     and the tracking difference is the arithmetic excess, rp - rb.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+
+use crate::pricing::PriceSource;
+use chrono::NaiveDate;
+
+#[derive(Debug, Clone)]
+pub struct BenchmarkPoint {
+    pub date: NaiveDate,
+    pub portfolio_value: f64,
+    pub benchmark_value: f64,
+    pub portfolio_return: f64,
+    pub benchmark_return: f64,
+    pub relative_return: f64,
+    pub tracking_difference: f64,
+}
+
+pub struct BenchmarkRecord {
+    name: String,
+    points: Vec<BenchmarkPoint>,
+}
+
+impl BenchmarkRecord {
+    pub fn new(name: &str, values: &Vec<(NaiveDate, f64)>, flows: &Vec<(NaiveDate, f64)>, prices: &dyn PriceSource) -> BenchmarkRecord {
+        let mut points = Vec::<BenchmarkPoint>::new();
+        let mut units = 0.0;
+        let mut portfolio_growth = 1.0;
+        let mut start_price: Option<f64> = None;
+        let mut previous: Option<(NaiveDate, f64)> = None;
+
+        for (date, value) in values {
+            let price = match prices.get_price(name, *date) {
+                Some(price) if price > 0.0 => { price },
+                _ => { continue; }
+            };
+
+            match previous {
+                None => {
+                    // Flows on the first day are already a part of the starting value.
+                    units = value / price;
+                    start_price = Some(price);
+                },
+                Some((previous_date, previous_value)) => {
+                    let flow: f64 = flows.iter().filter(|x| x.0 > previous_date && x.0 <= *date).map(|x| x.1).sum();
+                    units += flow / price;
+                    if previous_value > 0.0 {
+                        portfolio_growth *= (value - flow) / previous_value;
+                    }
+                }
+            }
+            previous = Some((*date, *value));
+
+            let portfolio_return = portfolio_growth - 1.0;
+            let benchmark_return = price / start_price.unwrap_or(price) - 1.0;
+            points.push(BenchmarkPoint {
+                date: *date,
+                portfolio_value: *value,
+                benchmark_value: units * price,
+                portfolio_return: portfolio_return,
+                benchmark_return: benchmark_return,
+                relative_return: (1.0 + portfolio_return) / (1.0 + benchmark_return) - 1.0,
+                tracking_difference: portfolio_return - benchmark_return,
+            });
+        }
+
+        BenchmarkRecord {
+            name: name.to_string(),
+            points: points,
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        return self.name.as_str();
+    }
+
+    pub fn get_points(&self) -> &Vec<BenchmarkPoint> {
+        return &self.points;
+    }
+
+    pub fn get_portfolio_return(&self) -> f64 {
+        self.points.last().map(|x| x.portfolio_return).unwrap_or(0.0)
+    }
+
+    pub fn get_benchmark_return(&self) -> f64 {
+        self.points.last().map(|x| x.benchmark_return).unwrap_or(0.0)
+    }
+
+    pub fn get_relative_return(&self) -> f64 {
+        self.points.last().map(|x| x.relative_return).unwrap_or(0.0)
+    }
+
+    pub fn get_tracking_difference(&self) -> f64 {
+        self.points.last().map(|x| x.tracking_difference).unwrap_or(0.0)
+    }
+
+    /// How much more (or less) the portfolio is worth than the replayed benchmark.
+    pub fn get_excess_value(&self) -> f64 {
+        self.points.last().map(|x| x.portfolio_value - x.benchmark_value).unwrap_or(0.0)
+    }
+}
@@ -0,0 +1,126 @@
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// cash_ledger.rs
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Description:
+///     A chronological record of every entry which changes the fiat cash held on a platform.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Usage:
+///     A CashLedger is built from the database and replayed to determine cash at any date.
+/// 
+///     let cash: CashLedger = app.get_cash_ledger(None);
+///     for (account, platform, currency, balance) in cash.get_account_balances(None) {
+///         println!("{} ({}): {} {}", platform, account, balance, currency);
+///     }
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Notes:
+///     Each entry is one side of a movement of cash, signed from the perspective of the account:
+///     deposits, rewards and sell proceeds are positive, withdrawals, buy costs and fees are
+///     negative. A transfer between two known accounts produces an entry for each, so it nets to
+///     zero portfolio-wide.
+/// 
+///     Assets held as cash (see CashEquivalent) are tracked alongside fiat, in their own accounts.
+/// 
+///     Amounts are in the currency of the account and are not converted.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+
+use chrono::{NaiveDate, NaiveDateTime};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CashLedgerEntryType {
+    Deposit,
+    Withdrawal,
+    Buy,
+    Sell,
+    Fee,
+    Reward,
+}
+
+#[derive(Debug, Clone)]
+pub struct CashLedgerEntry {
+    pub id: String,
+    pub date: NaiveDateTime,
+    pub account: String,
+    pub platform: String,
+    pub currency: String,
+    pub r#type: CashLedgerEntryType,
+    pub amount: f64,
+}
+
+pub struct CashLedger {
+    entries: Vec<CashLedgerEntry>,
+}
+
+impl CashLedger {
+    pub fn new(mut entries: Vec<CashLedgerEntry>) -> CashLedger {
+        entries.sort_by(|a, b| a.date.cmp(&b.date));
+        CashLedger { entries: entries }
+    }
+
+    pub fn get_entries(&self) -> &Vec<CashLedgerEntry> {
+        return &self.entries;
+    }
+
+    pub fn get_platforms(&self) -> Vec<String> {
+        let mut platforms = Vec::<String>::new();
+        for entry in &self.entries {
+            if !entry.platform.is_empty() && !platforms.contains(&entry.platform) {
+                platforms.push(entry.platform.to_string());
+            }
+        }
+        platforms.sort();
+        return platforms;
+    }
+
+    pub fn get_account_balances(&self, date: Option<NaiveDateTime>) -> Vec<(String, String, String, f64)> {
+        let mut balances = Vec::<(String, String, String, f64)>::new();
+        for entry in self.entries.iter().take_while(|x| date.map(|date| x.date <= date).unwrap_or(true)) {
+            match balances.iter_mut().find(|x| x.0 == entry.account && x.1 == entry.platform && x.2 == entry.currency) {
+                Some(balance) => { balance.3 += entry.amount; },
+                None => {
+                    balances.push((entry.account.to_string(), entry.platform.to_string(), entry.currency.to_string(), entry.amount));
+                }
+            }
+        }
+        return balances;
+    }
+
+    pub fn get_balances(&self, date: NaiveDateTime, platform: Option<&str>) -> HashMap<String, f64> {
+        let mut balances = HashMap::<String, f64>::new();
+        for entry in self.entries.iter().take_while(|x| x.date <= date) {
+            if platform.map(|x| x == entry.platform).unwrap_or(true) {
+                *balances.entry(entry.currency.to_string()).or_insert(0.0) += entry.amount;
+            }
+        }
+        return balances;
+    }
+
+    pub fn get_daily_balances(&self, start: NaiveDate, end: NaiveDate, platform: Option<&str>) -> Vec<(NaiveDate, HashMap<String, f64>)> {
+        let mut result = Vec::<(NaiveDate, HashMap<String, f64>)>::new();
+        let mut balances = HashMap::<String, f64>::new();
+        let mut i: usize = 0;
+        let mut date = start;
+
+        while date <= end {
+            let end_of_day = date.and_hms_opt(23, 59, 59).unwrap();
+            while i < self.entries.len() && self.entries[i].date <= end_of_day {
+                let entry = &self.entries[i];
+                if platform.map(|x| x == entry.platform).unwrap_or(true) {
+                    *balances.entry(entry.currency.to_string()).or_insert(0.0) += entry.amount;
+                }
+                i += 1;
+            }
+            result.push((date, balances.clone()));
+            date = date.succ_opt().unwrap();
+        }
+        return result;
+    }
+}
@@ -28,16 +28,27 @@
 ///     If any further data is to be added for an asset, a new CoinRecord should be created
 ///     to reflect changes.
 /// 
+///     Sells take shares from lots in the order of the cost-basis method (FIFO by default).
+///     MinimizeTax takes losses first, then long-term lots, then short-term lots.
+/// 
 /// ///////////////////////////////////////////////////////////////////////////////////////////////
 
+use crate::error::CryptfolioError;
 use crate::recording::TaxRecord;
+use crate::util::Util;
 use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
 
-struct TransactionRecord {
-    date: String,
-    price: f64,
-    shares: f64,
-    fee: f64,
+// Shares left over from floating point error are not worth another pass over the lots.
+const SHARE_EPSILON: f64 = 1e-12;
+
+#[derive(Debug, Clone)]
+pub struct TransactionRecord {
+    pub date: String,
+    pub price: f64,
+    pub shares: f64,
+    pub fee: f64,
 }
 
 pub struct CoinRecord {
@@ -49,7 +60,9 @@ pub struct CoinRecord {
     total_fees: f64,
     buys: VecDeque<TransactionRecord>,
     sells: VecDeque<TransactionRecord>,
+    unmatched_sells: Vec<TransactionRecord>,
     tax_records: Vec<TaxRecord>,
+    cost_basis_method: CostBasisMethod,
 }
 
 impl CoinRecord {
@@ -63,7 +76,9 @@ impl CoinRecord {
             total_fees: 0.0,
             buys: VecDeque::<TransactionRecord>::new(),
             sells: VecDeque::<TransactionRecord>::new(),
+            unmatched_sells: Vec::<TransactionRecord>::new(),
             tax_records: Vec::<TaxRecord>::new(),
+            cost_basis_method: CostBasisMethod::Fifo,
         }
     }
 
@@ -95,10 +110,29 @@ impl CoinRecord {
         return self.total_fees;
     }
 
+    pub fn get_cost_basis_method(&self) -> &CostBasisMethod {
+        return &self.cost_basis_method;
+    }
+
+    /// Must be set before update() is called.
+    pub fn set_cost_basis_method(&mut self, method: CostBasisMethod) {
+        self.cost_basis_method = method;
+    }
+
     pub fn get_tax_records(&self) -> &Vec<TaxRecord> {
         return &self.tax_records;
     }
 
+    /// Portions of sells for which no buy existed to provide a cost basis.
+    pub fn get_unmatched_sells(&self) -> &Vec<TransactionRecord> {
+        return &self.unmatched_sells;
+    }
+
+    /// Remaining (unsold) buys, oldest first. Only valid after update() has been called.
+    pub fn get_lots(&self) -> &VecDeque<TransactionRecord> {
+        return &self.buys;
+    }
+
     pub fn add_buy(&mut self, date: String, price: f64, shares: f64, fee: f64) {
         self.buys.push_back(TransactionRecord{date: date, price: price, shares: shares, fee: fee});
     }
@@ -139,48 +173,148 @@ impl CoinRecord {
         self.total_invested += total_price;
     }
 
+    /// Taxable outcome of selling shares from the remaining lots under the cost-basis method,
+    /// without altering the record.
+    pub fn estimate_sell(&self, date: String, price: f64, shares: f64) -> Vec<TaxRecord> {
+        let mut lots = self.buys.clone();
+        let mut records = Vec::<TaxRecord>::new();
+        let transaction = TransactionRecord { date: date, price: price, shares: shares, fee: 0.0 };
+        let mut remaining = shares;
+        while remaining > SHARE_EPSILON && !lots.is_empty() {
+            let lot = self.cost_basis_method.select_lot(&lots, &transaction);
+            let sold = lots[lot].shares.min(remaining);
+            records.push(TaxRecord::new(
+                lots[lot].date.to_string(),
+                transaction.date.to_string(),
+                lots[lot].price,
+                price,
+                sold,
+                sold * price - sold * lots[lot].price
+            ));
+            lots[lot].shares -= sold;
+            if lots[lot].shares <= SHARE_EPSILON {
+                lots.remove(lot);
+            }
+            remaining -= sold;
+        }
+        records
+    }
+
     fn process_sell(&mut self, index: usize) {
-        let transaction = self.sells.get(index).unwrap();
+        let transaction = self.sells.get(index).unwrap().clone();
         let mut remaining_sell_shares: f64 = transaction.shares;
-        let mut i: usize = 0;
-        let num_buys: usize = self.buys.len();
-    
-        while i < num_buys {
+
+        while !self.buys.is_empty() && remaining_sell_shares > SHARE_EPSILON {
             let profit: f64;
-            let current_buy_shares = self.buys.front().unwrap().shares;
-            let current_buy_price = self.buys.front().unwrap().price;
-            if current_buy_shares - remaining_sell_shares > 0.0 {
+            let lot = self.cost_basis_method.select_lot(&self.buys, &transaction);
+            let current_buy_shares = self.buys[lot].shares;
+            let current_buy_price = self.buys[lot].price;
+            if current_buy_shares - remaining_sell_shares > SHARE_EPSILON {
                 // This particular buy order will still have shares remaining AFTER taking those
                 // from this sell 
                 profit = (remaining_sell_shares * transaction.price) - (remaining_sell_shares * current_buy_price);
                 self.gross_profit += profit;
                 self.total_invested += remaining_sell_shares * current_buy_price;
-                self.buys.front_mut().unwrap().shares -= remaining_sell_shares;
+                self.buys[lot].shares -= remaining_sell_shares;
                 self.tax_records.push(TaxRecord::new(
-                    self.buys.front().unwrap().date.to_string(),
+                    self.buys[lot].date.to_string(),
                     transaction.date.to_string(),
                     current_buy_price,
                     transaction.price,
                     remaining_sell_shares,
                     profit
                 ));
-                break;
+                remaining_sell_shares = 0.0;
             } else {
                 profit = (current_buy_shares * transaction.price) - (current_buy_shares * current_buy_price);
                 self.gross_profit += profit;
                 self.total_invested += current_buy_shares * current_buy_price;
                 self.tax_records.push(TaxRecord::new(
-                    self.buys.front().unwrap().date.to_string(),
+                    self.buys[lot].date.to_string(),
                     transaction.date.to_string(),
                     current_buy_price,
                     transaction.price,
                     current_buy_shares,
                     profit
                 ));
-                self.buys.pop_front();
+                self.buys.remove(lot);
                 remaining_sell_shares -= current_buy_shares;
             }
-            i += 1;
+        }
+
+        // Every buy was exhausted before the sell was satisfied, leaving shares without a basis.
+        if self.buys.is_empty() && remaining_sell_shares > SHARE_EPSILON {
+            self.unmatched_sells.push(TransactionRecord {
+                date: transaction.date.to_string(),
+                price: transaction.price,
+                shares: remaining_sell_shares,
+                fee: 0.0,
+            });
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CostBasisMethod {
+    Fifo,
+    Lifo,
+    Hifo,
+    MinimizeTax,
+}
+
+impl fmt::Display for CostBasisMethod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Fifo => { write!(f, "fifo") },
+            Self::Lifo => { write!(f, "lifo") },
+            Self::Hifo => { write!(f, "hifo") },
+            Self::MinimizeTax => { write!(f, "minimize_tax") },
+        }
+    }
+}
+
+impl FromStr for CostBasisMethod {
+    type Err = CryptfolioError;
+
+    fn from_str(method: &str) -> Result<CostBasisMethod, CryptfolioError> {
+        match method.to_lowercase().as_str() {
+            "fifo" => { Ok(Self::Fifo) },
+            "lifo" => { Ok(Self::Lifo) },
+            "hifo" => { Ok(Self::Hifo) },
+            "minimize_tax" => { Ok(Self::MinimizeTax) },
+            _ => { Err(CryptfolioError::InvalidSetting(format!("Unknown cost basis method '{}'", method))) }
+        }
+    }
+}
+
+impl CostBasisMethod {
+    /// Index of the lot the next shares of a sell are taken from.
+    pub fn select_lot(&self, lots: &VecDeque<TransactionRecord>, sell: &TransactionRecord) -> usize {
+        let highest_cost = |a: &(usize, &TransactionRecord), b: &(usize, &TransactionRecord)| {
+            a.1.price.partial_cmp(&b.1.price).unwrap_or(std::cmp::Ordering::Equal)
+        };
+        match self {
+            Self::Fifo => { 0 },
+            Self::Lifo => { lots.len() - 1 },
+            Self::Hifo => { lots.iter().enumerate().max_by(highest_cost).map(|x| x.0).unwrap_or(0) },
+            Self::MinimizeTax => {
+                // Losses first (largest first), then long-term lots, then short-term lots, taking
+                // the highest cost lot of each group.
+                let rank = |lot: &TransactionRecord| -> u8 {
+                    if lot.price > sell.price {
+                        return 0;
+                    }
+                    match (Util::parse_date(lot.date.as_str()), Util::parse_date(sell.date.as_str())) {
+                        (Ok(bought), Ok(sold)) if (sold - bought).num_days() >= 365 => { 1 },
+                        _ => { 2 }
+                    }
+                };
+                lots.iter()
+                    .enumerate()
+                    .min_by(|a, b| rank(a.1).cmp(&rank(b.1)).then(highest_cost(b, a)))
+                    .map(|x| x.0)
+                    .unwrap_or(0)
+            }
         }
     }
 }
@@ -0,0 +1,187 @@
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// contribution_record.rs
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Description:
+///     A structure that represents the net fiat contributed to the portfolio, grouped by month or
+///     year, for each platform and in total.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Usage:
+///     Retrieve a ContributionRecord from a CryptfolioApp after syncing platforms.
+/// 
+///     let contributions: ContributionRecord = app.get_contributions();
+///     for entry in contributions.get_contributions(Interval::Monthly, None) {
+///         println!("{} | in: {} | out: {} | net: {}",
+///             entry.period, entry.deposits, entry.withdrawals, entry.net
+///         );
+///     }
+///     println!("Cash left: {}", contributions.get_total_cash());
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Notes:
+///     A fiat transfer between two known accounts (e.g. Coinbase -> Coinbase Pro) is an internal
+///     move, and is not a contribution. Every other transfer is attributed to the platform of the
+///     known side, so the platform totals add up to the portfolio total.
+/// 
+///     Amounts are in the reporting currency, converted on the date of each transfer. Cash left
+///     is the latest fiat balance reported by each platform, converted at today's rate.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+
+use chrono::NaiveDateTime;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone)]
+pub struct FiatTransferRecord {
+    pub id: String,
+    pub date: NaiveDateTime,
+    pub amount: f64,
+    pub currency: String,
+    pub origin_platform: String,
+    pub destination_platform: String,
+}
+
+impl FiatTransferRecord {
+    pub fn is_internal(&self) -> bool {
+        return !self.origin_platform.is_empty() && !self.destination_platform.is_empty();
+    }
+
+    pub fn is_deposit(&self) -> bool {
+        return self.origin_platform.is_empty() && !self.destination_platform.is_empty();
+    }
+
+    pub fn is_withdrawal(&self) -> bool {
+        return !self.origin_platform.is_empty() && self.destination_platform.is_empty();
+    }
+
+    pub fn get_platform(&self) -> &str {
+        if self.is_deposit() {
+            return self.destination_platform.as_str();
+        }
+        self.origin_platform.as_str()
+    }
+}
+
+pub enum Interval {
+    Monthly,
+    Yearly,
+}
+
+impl Interval {
+    pub fn get_format(&self) -> &str {
+        match self {
+            Self::Monthly => { "%Y-%m" },
+            Self::Yearly => { "%Y" },
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ContributionEntry {
+    pub period: String,
+    pub deposits: f64,
+    pub withdrawals: f64,
+    pub net: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct CashBalance {
+    pub platform: String,
+    pub currency: String,
+    pub balance: f64,
+    pub value: f64,
+}
+
+pub struct ContributionRecord {
+    transfers: Vec<FiatTransferRecord>,
+    cash: Vec<CashBalance>,
+}
+
+impl ContributionRecord {
+    pub fn new(transfers: Vec<FiatTransferRecord>) -> ContributionRecord {
+        ContributionRecord {
+            transfers: transfers,
+            cash: Vec::<CashBalance>::new(),
+        }
+    }
+
+    pub fn get_transfers(&self) -> &Vec<FiatTransferRecord> {
+        return &self.transfers;
+    }
+
+    pub fn get_external_transfers(&self, platform: Option<&str>) -> Vec<&FiatTransferRecord> {
+        self.transfers
+            .iter()
+            .filter(|x| x.is_deposit() || x.is_withdrawal())
+            .filter(|x| platform.map(|p| x.get_platform() == p).unwrap_or(true))
+            .collect()
+    }
+
+    pub fn get_platforms(&self) -> Vec<String> {
+        let mut platforms = Vec::<String>::new();
+        for transfer in self.get_external_transfers(None) {
+            if !platforms.iter().any(|x| x == transfer.get_platform()) {
+                platforms.push(transfer.get_platform().to_string());
+            }
+        }
+        platforms.sort();
+        platforms
+    }
+
+    pub fn get_contributions(&self, interval: Interval, platform: Option<&str>) -> Vec<ContributionEntry> {
+        let mut periods = BTreeMap::<String, (f64, f64)>::new();
+        for transfer in self.get_external_transfers(platform) {
+            let period = periods
+                .entry(transfer.date.format(interval.get_format()).to_string())
+                .or_insert((0.0, 0.0));
+            if transfer.is_deposit() {
+                period.0 += transfer.amount;
+            } else {
+                period.1 += transfer.amount;
+            }
+        }
+        periods
+            .into_iter()
+            .map(|(period, (deposits, withdrawals))| ContributionEntry {
+                period: period,
+                deposits: deposits,
+                withdrawals: withdrawals,
+                net: deposits - withdrawals,
+            })
+            .collect()
+    }
+
+    pub fn get_total_deposits(&self, platform: Option<&str>) -> f64 {
+        self.get_external_transfers(platform).iter().filter(|x| x.is_deposit()).map(|x| x.amount).sum()
+    }
+
+    pub fn get_total_withdrawals(&self, platform: Option<&str>) -> f64 {
+        self.get_external_transfers(platform).iter().filter(|x| x.is_withdrawal()).map(|x| x.amount).sum()
+    }
+
+    pub fn get_net_contributions(&self, platform: Option<&str>) -> f64 {
+        self.get_total_deposits(platform) - self.get_total_withdrawals(platform)
+    }
+
+    pub fn get_cash_balances(&self) -> &Vec<CashBalance> {
+        return &self.cash;
+    }
+
+    pub fn get_total_cash(&self) -> f64 {
+        self.cash.iter().map(|x| x.value).sum()
+    }
+
+    pub fn add_cash_balance(&mut self, platform: String, currency: String, balance: f64, value: f64) {
+        self.cash.push(CashBalance {
+            platform: platform,
+            currency: currency,
+            balance: balance,
+            value: value,
+        });
+    }
+}
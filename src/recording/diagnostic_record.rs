@@ -0,0 +1,130 @@
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// diagnostic_record.rs
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Description:
+///     A structure that represents every data-quality problem which affects cost basis.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Usage:
+///     Retrieve a DiagnosticRecord from a CryptfolioApp to find entries in need of attention.
+/// 
+///     for diagnostic in app.get_diagnostics().get_diagnostics() {
+///         println!("[{}] {} {}: {}\n    -> {}",
+///             diagnostic.r#type,
+///             diagnostic.date,
+///             diagnostic.coin,
+///             diagnostic.message,
+///             diagnostic.suggestion
+///         );
+///     }
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Notes:
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiagnosticType {
+    NegativeBalance,
+    SellWithoutBasis,
+    MissingPrice,
+    MissingFee,
+    OrphanTransfer,
+    UnknownTransaction,
+    DuplicateId,
+}
+
+impl fmt::Display for DiagnosticType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NegativeBalance => { write!(f, "Negative Balance") },
+            Self::SellWithoutBasis => { write!(f, "Sell Without Basis") },
+            Self::MissingPrice => { write!(f, "Missing Price") },
+            Self::MissingFee => { write!(f, "Missing Fee") },
+            Self::OrphanTransfer => { write!(f, "Orphan Transfer") },
+            Self::UnknownTransaction => { write!(f, "Unknown Transaction") },
+            Self::DuplicateId => { write!(f, "Duplicate ID") },
+        }
+    }
+}
+
+impl DiagnosticType {
+    pub fn get_suggestion(&self) -> String {
+        match self {
+            Self::NegativeBalance => {
+                "A buy, reward or incoming transfer is missing. Sync the platform the coin came from, or add the missing transaction manually.".to_string()
+            },
+            Self::SellWithoutBasis => {
+                "Add the buy (or transfer in) which these shares were acquired by so a cost basis can be calculated.".to_string()
+            },
+            Self::MissingPrice => {
+                "Set the price from the market price on the date of the entry (see CryptfolioApp::fill_reward_prices for rewards), or set an FX rate or price source (see CryptfolioApp::set_price_source) which covers the date.".to_string()
+            },
+            Self::MissingFee => {
+                "Set the fee from the transaction on the blockchain, or 0.0 if none was paid by this portfolio.".to_string()
+            },
+            Self::OrphanTransfer => {
+                "Sync the platform on the other side of the transfer, or link it manually if it was recorded with a different amount or date.".to_string()
+            },
+            Self::UnknownTransaction => {
+                "Add the transaction manually, as it could not be imported into the ledger.".to_string()
+            },
+            Self::DuplicateId => {
+                "Remove the duplicate entries, which are usually the result of syncing the same platform more than once.".to_string()
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub r#type: DiagnosticType,
+    pub id: String,
+    pub coin: String,
+    pub date: String,
+    pub message: String,
+    pub suggestion: String,
+}
+
+pub struct DiagnosticRecord {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl DiagnosticRecord {
+    pub fn new() -> DiagnosticRecord {
+        DiagnosticRecord {
+            diagnostics: Vec::<Diagnostic>::new(),
+        }
+    }
+
+    pub fn get_diagnostics(&self) -> &Vec<Diagnostic> {
+        return &self.diagnostics;
+    }
+
+    pub fn get_diagnostics_of_type(&self, r#type: DiagnosticType) -> Vec<&Diagnostic> {
+        self.diagnostics.iter().filter(|x| x.r#type == r#type).collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.diagnostics.is_empty();
+    }
+
+    pub fn add_diagnostic(&mut self, r#type: DiagnosticType, id: String, coin: String, date: String, message: String) {
+        let suggestion = r#type.get_suggestion();
+        self.diagnostics.push(Diagnostic {
+            r#type: r#type,
+            id: id,
+            coin: coin,
+            date: date,
+            message: message,
+            suggestion: suggestion,
+        });
+    }
+}
@@ -0,0 +1,82 @@
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// history_record.rs
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Description:
+///     A structure that represents the daily value of a coin, or the entire portfolio, over time.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Usage:
+///     Retrieve a HistoryRecord from a CryptfolioApp after the history has been rebuilt.
+/// 
+///     app.rebuild_history(&prices).unwrap();
+///     let history: HistoryRecord = app.get_portfolio_history(start, end);
+///     for point in history.get_points() {
+///         println!("{}: ${}", point.date, point.value);
+///     }
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Notes:
+///     Points for the portfolio as a whole do not carry shares or unit price, only value.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+
+use chrono::NaiveDate;
+
+#[derive(Debug, Clone)]
+pub struct HistoryPoint {
+    pub date: NaiveDate,
+    pub shares: f64,
+    pub unit_price: f64,
+    pub value: f64,
+}
+
+pub struct HistoryRecord {
+    coin: Option<String>,
+    points: Vec<HistoryPoint>,
+}
+
+impl HistoryRecord {
+    pub fn new(coin: Option<String>) -> HistoryRecord {
+        HistoryRecord {
+            coin: coin,
+            points: Vec::<HistoryPoint>::new(),
+        }
+    }
+
+    pub fn get_coin(&self) -> Option<&String> {
+        return self.coin.as_ref();
+    }
+
+    pub fn get_points(&self) -> &Vec<HistoryPoint> {
+        return &self.points;
+    }
+
+    pub fn get_values(&self) -> Vec<(NaiveDate, f64)> {
+        self.points.iter().map(|x| (x.date, x.value)).collect()
+    }
+
+    pub fn get_prices(&self) -> Vec<(NaiveDate, f64)> {
+        self.points.iter().map(|x| (x.date, x.unit_price)).collect()
+    }
+
+    pub fn get_value(&self, date: NaiveDate) -> Option<f64> {
+        self.points.iter().find(|x| x.date == date).map(|x| x.value)
+    }
+
+    pub fn get_start_value(&self) -> f64 {
+        self.points.first().map(|x| x.value).unwrap_or(0.0)
+    }
+
+    pub fn get_end_value(&self) -> f64 {
+        self.points.last().map(|x| x.value).unwrap_or(0.0)
+    }
+
+    pub fn add_point(&mut self, date: NaiveDate, shares: f64, unit_price: f64, value: f64) {
+        self.points.push(HistoryPoint { date: date, shares: shares, unit_price: unit_price, value: value });
+    }
+}
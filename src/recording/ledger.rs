@@ -0,0 +1,188 @@
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// ledger.rs
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Description:
+///     A chronological record of every entry which changes the number of shares held.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Usage:
+///     A Ledger is built from the database and replayed to determine balances at any date.
+/// 
+///     let ledger: Ledger = DatabaseScript::fetch_ledger(dbh, None);
+///     for (date, balances) in ledger.get_daily_balances(start, end, None) {
+///         println!("{}: {} BTC", date, balances.get("BTC").unwrap_or(&0.0));
+///     }
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Notes:
+///     Portfolio-wide balances follow the same rules as CoinRecord: buys and rewards add shares,
+///     sells remove shares, and transfers only remove the fee paid, as the coins are still owned.
+/// 
+///     Platform balances additionally move shares between platforms when a transfer occurs.
+///     Entries whose platform is unknown (external addresses, rewards) are not attributed.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+
+use chrono::{NaiveDate, NaiveDateTime};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LedgerEntryType {
+    Buy,
+    Sell,
+    Reward,
+    Transfer,
+}
+
+#[derive(Debug, Clone)]
+pub struct LedgerEntry {
+    pub id: String,
+    pub date: NaiveDateTime,
+    pub coin: String,
+    pub r#type: LedgerEntryType,
+    pub unit_price: f64,
+    pub unit_size: f64,
+    pub fee: f64,
+    pub origin: String,
+    pub destination: String,
+}
+
+impl LedgerEntry {
+    pub fn get_share_change(&self, platform: Option<&str>) -> f64 {
+        let size = self.unit_size.abs();
+        let fee = if self.fee.is_finite() { self.fee.abs() } else { 0.0 };
+        match platform {
+            None => {
+                match self.r#type {
+                    LedgerEntryType::Buy | LedgerEntryType::Reward => { size },
+                    LedgerEntryType::Sell => { -size },
+                    LedgerEntryType::Transfer => { -fee },
+                }
+            },
+            Some(platform) => {
+                let mut change = 0.0;
+                if self.destination == platform {
+                    change += match self.r#type {
+                        LedgerEntryType::Sell => { 0.0 },
+                        _ => { size },
+                    };
+                }
+                if self.origin == platform {
+                    change -= match self.r#type {
+                        LedgerEntryType::Sell => { size },
+                        LedgerEntryType::Transfer => { size + fee },
+                        _ => { 0.0 },
+                    };
+                }
+                change
+            }
+        }
+    }
+}
+
+pub struct Ledger {
+    entries: Vec<LedgerEntry>,
+}
+
+impl Ledger {
+    pub fn new(mut entries: Vec<LedgerEntry>) -> Ledger {
+        entries.sort_by(|a, b| a.date.cmp(&b.date));
+        Ledger { entries: entries }
+    }
+
+    pub fn get_entries(&self) -> &Vec<LedgerEntry> {
+        return &self.entries;
+    }
+
+    pub fn get_start_date(&self) -> Option<NaiveDate> {
+        self.entries.first().map(|x| x.date.date())
+    }
+
+    pub fn get_coins(&self) -> Vec<String> {
+        let mut coins = Vec::<String>::new();
+        for entry in &self.entries {
+            if !coins.contains(&entry.coin) {
+                coins.push(entry.coin.to_string());
+            }
+        }
+        coins.sort();
+        return coins;
+    }
+
+    pub fn get_platforms(&self) -> Vec<String> {
+        let mut platforms = Vec::<String>::new();
+        for entry in &self.entries {
+            for platform in [&entry.origin, &entry.destination] {
+                if !platform.is_empty() && !platforms.contains(platform) {
+                    platforms.push(platform.to_string());
+                }
+            }
+        }
+        platforms.sort();
+        return platforms;
+    }
+
+    pub fn get_coin_flows(&self, coin: &str) -> Vec<(NaiveDate, f64)> {
+        let mut flows = Vec::<(NaiveDate, f64)>::new();
+        for entry in self.entries.iter().filter(|x| x.coin == coin) {
+            let fee = if entry.fee.is_finite() { entry.fee.abs() } else { 0.0 };
+            let amount = entry.unit_price * entry.unit_size.abs();
+            match entry.r#type {
+                LedgerEntryType::Buy => { flows.push((entry.date.date(), amount + fee)); },
+                LedgerEntryType::Sell => { flows.push((entry.date.date(), -(amount - fee))); },
+                _ => {}
+            }
+        }
+        return flows;
+    }
+
+    /// Coins transferred into (positive) or out of (negative) a platform, from or to anywhere else,
+    /// valued at the price of the coin on the day. The fee of a transfer out is not a flow, but a
+    /// cost to the platform.
+    pub fn get_transfer_flows(&self, platform: &str, prices: &HashMap<(String, NaiveDate), f64>) -> Vec<(NaiveDate, f64)> {
+        let mut flows = Vec::<(NaiveDate, f64)>::new();
+        for entry in self.entries.iter().filter(|x| x.r#type == LedgerEntryType::Transfer) {
+            let date = entry.date.date();
+            let price = prices.get(&(entry.coin.to_string(), date)).cloned().unwrap_or(0.0);
+            if entry.destination == platform && entry.origin != platform {
+                flows.push((date, entry.unit_size.abs() * price));
+            } else if entry.origin == platform && entry.destination != platform {
+                flows.push((date, -entry.unit_size.abs() * price));
+            }
+        }
+        return flows;
+    }
+
+    pub fn get_balances(&self, date: NaiveDateTime, platform: Option<&str>) -> HashMap<String, f64> {
+        let mut balances = HashMap::<String, f64>::new();
+        for entry in self.entries.iter().take_while(|x| x.date <= date) {
+            *balances.entry(entry.coin.to_string()).or_insert(0.0) += entry.get_share_change(platform);
+        }
+        return balances;
+    }
+
+    pub fn get_daily_balances(&self, start: NaiveDate, end: NaiveDate, platform: Option<&str>) -> Vec<(NaiveDate, HashMap<String, f64>)> {
+        let mut result = Vec::<(NaiveDate, HashMap<String, f64>)>::new();
+        let mut balances = HashMap::<String, f64>::new();
+        let mut i: usize = 0;
+        let mut date = start;
+
+        while date <= end {
+            // apply every entry which occurred on or before the end of this day
+            let end_of_day = date.and_hms_opt(23, 59, 59).unwrap();
+            while i < self.entries.len() && self.entries[i].date <= end_of_day {
+                let entry = &self.entries[i];
+                *balances.entry(entry.coin.to_string()).or_insert(0.0) += entry.get_share_change(platform);
+                i += 1;
+            }
+            result.push((date, balances.clone()));
+            date = date.succ_opt().unwrap();
+        }
+        return result;
+    }
+}
@@ -1,5 +1,31 @@
+pub mod benchmark_record;
+pub mod cash_ledger;
 pub mod coin_record;
+pub mod contribution_record;
+pub mod diagnostic_record;
+pub mod history_record;
+pub mod ledger;
+pub mod portfolio_record;
+pub mod rebalance_record;
+pub mod reconciliation_record;
+pub mod recurring_buy_record;
+pub mod return_record;
+pub mod risk_record;
 pub mod tax_record;
+pub mod transfer_record;
 
-pub use coin_record::CoinRecord;
-pub use tax_record::TaxRecord;
\ No newline at end of file
+pub use benchmark_record::{BenchmarkPoint, BenchmarkRecord};
+pub use cash_ledger::{CashLedger, CashLedgerEntry, CashLedgerEntryType};
+pub use coin_record::{CoinRecord, CostBasisMethod, TransactionRecord};
+pub use contribution_record::{CashBalance, ContributionEntry, ContributionRecord, FiatTransferRecord, Interval};
+pub use diagnostic_record::{Diagnostic, DiagnosticRecord, DiagnosticType};
+pub use history_record::{HistoryPoint, HistoryRecord};
+pub use ledger::{Ledger, LedgerEntry, LedgerEntryType};
+pub use portfolio_record::{PortfolioEntry, PortfolioRecord};
+pub use rebalance_record::{RebalanceAction, RebalanceRecord, TargetAllocation};
+pub use reconciliation_record::{ReconciliationEntry, ReconciliationRecord};
+pub use recurring_buy_record::{Cadence, RecurringBuy, RecurringBuyRecord, RecurringSchedule};
+pub use return_record::{Period, ReturnRecord};
+pub use risk_record::RiskRecord;
+pub use tax_record::TaxRecord;
+pub use transfer_record::{TransferMatch, TransferMatcher, TransferRecord};
\ No newline at end of file
@@ -0,0 +1,168 @@
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// portfolio_record.rs
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Description:
+///     A structure that represents a record of every coin held accross all platforms.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Usage:
+///     Retrieve a PortfolioRecord from a CryptfolioApp, supplying current prices for valuation.
+/// 
+///     let portfolio: PortfolioRecord = app.get_portfolio(&prices, None);
+///     for entry in portfolio.get_entries() {
+///         println!("{}: {} ({}%)", entry.coin, entry.market_value, entry.allocation);
+///     }
+///     println!("Total market value: ${}", portfolio.get_market_value());
+///     println!("Uninvested cash: ${}", portfolio.get_cash_value());
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Notes:
+///     Like CoinRecord, a PortfolioRecord is a snapshot of the data at the time of creation.
+/// 
+///     Coins without a supplied price are valued at 0.0 and therefore hold no allocation.
+/// 
+///     Cash held on each platform is a part of the total value, and so of every allocation, but
+///     not of the market value, which only covers coins.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+
+use crate::recording::{CashBalance, CoinRecord};
+
+pub struct PortfolioEntry {
+    pub coin: String,
+    pub record: CoinRecord,
+    pub price: f64,
+    pub market_value: f64,
+    pub allocation: f64,
+}
+
+pub struct PortfolioRecord {
+    total_invested: f64,
+    current_invested: f64,
+    realized_profit: f64,
+    total_fees: f64,
+    market_value: f64,
+    cash_value: f64,
+    entries: Vec<PortfolioEntry>,
+    cash: Vec<CashBalance>,
+}
+
+impl PortfolioRecord {
+    pub fn new() -> PortfolioRecord {
+        PortfolioRecord {
+            total_invested: 0.0,
+            current_invested: 0.0,
+            realized_profit: 0.0,
+            total_fees: 0.0,
+            market_value: 0.0,
+            cash_value: 0.0,
+            entries: Vec::<PortfolioEntry>::new(),
+            cash: Vec::<CashBalance>::new(),
+        }
+    }
+
+    pub fn get_total_invested(&self) -> f64 {
+        return self.total_invested;
+    }
+
+    pub fn get_current_invested(&self) -> f64 {
+        return self.current_invested;
+    }
+
+    pub fn get_realized_profit(&self) -> f64 {
+        return self.realized_profit;
+    }
+
+    pub fn get_total_fees(&self) -> f64 {
+        return self.total_fees;
+    }
+
+    pub fn get_market_value(&self) -> f64 {
+        return self.market_value;
+    }
+
+    pub fn get_cash_value(&self) -> f64 {
+        return self.cash_value;
+    }
+
+    pub fn get_total_value(&self) -> f64 {
+        return self.market_value + self.cash_value;
+    }
+
+    pub fn get_cash_allocation(&self) -> f64 {
+        if self.get_total_value() > 0.0 {
+            return self.cash_value / self.get_total_value() * 100.0;
+        }
+        0.0
+    }
+
+    pub fn get_cash_balances(&self) -> &Vec<CashBalance> {
+        return &self.cash;
+    }
+
+    pub fn get_unrealized_profit(&self) -> f64 {
+        return self.market_value - self.current_invested;
+    }
+
+    pub fn get_entries(&self) -> &Vec<PortfolioEntry> {
+        return &self.entries;
+    }
+
+    pub fn get_entry(&self, coin: &str) -> Option<&PortfolioEntry> {
+        self.entries.iter().find(|x| x.coin == coin)
+    }
+
+    pub fn add_coin(&mut self, coin: String, record: CoinRecord, price: Option<f64>) {
+        let price = price.unwrap_or(0.0);
+        self.entries.push(PortfolioEntry {
+            coin: coin,
+            market_value: record.get_shares() * price,
+            record: record,
+            price: price,
+            allocation: 0.0,
+        });
+    }
+
+    pub fn add_cash(&mut self, platform: String, currency: String, balance: f64, value: f64) {
+        self.cash.push(CashBalance {
+            platform: platform,
+            currency: currency,
+            balance: balance,
+            value: value,
+        });
+    }
+
+    pub fn update(&mut self) {
+        self.total_invested = 0.0;
+        self.current_invested = 0.0;
+        self.realized_profit = 0.0;
+        self.total_fees = 0.0;
+        self.market_value = 0.0;
+        self.cash_value = self.cash.iter().map(|x| x.value).sum();
+
+        for entry in &self.entries {
+            self.total_invested += entry.record.get_total_invested();
+            self.current_invested += entry.record.get_current_invested();
+            self.realized_profit += entry.record.get_gross_profit();
+            self.total_fees += entry.record.get_total_fees();
+            self.market_value += entry.market_value;
+        }
+
+        // calculate allocation percentages now that the total value is known
+        let total_value = self.market_value + self.cash_value;
+        for entry in &mut self.entries {
+            if total_value > 0.0 {
+                entry.allocation = entry.market_value / total_value * 100.0;
+            } else {
+                entry.allocation = 0.0;
+            }
+        }
+
+        self.entries.sort_by(|a, b| b.market_value.partial_cmp(&a.market_value).unwrap_or(std::cmp::Ordering::Equal));
+    }
+}
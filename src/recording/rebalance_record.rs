@@ -0,0 +1,238 @@
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// rebalance_record.rs
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Description:
+///     A structure that compares the current allocation of the portfolio with its target
+///     weights, and the buys and sells needed to bring the two back in line.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Usage:
+///     app.set_allocation_target("BTC", 50.0).unwrap();
+///     app.set_allocation_target("Layer 1", 40.0).unwrap();
+///     app.set_coin_category("ETH", "Layer 1").unwrap();
+///     app.set_coin_category("SOL", "Layer 1").unwrap();
+/// 
+///     let plan: RebalanceRecord = app.get_rebalance_plan(&prices);
+///     for action in plan.get_actions() {
+///         println!("{} {} {} (${}) | est. gain: {}",
+///             action.side, action.shares, action.coin, action.value, action.estimated_gain
+///         );
+///     }
+///     println!("Estimated realized gains: {}", plan.get_estimated_realized_gains());
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Notes:
+///     Targets are percentages of the total portfolio value, cash included. Coins without a
+///     target (directly or through their category) are left as they are. A coin's own target
+///     takes precedence over the target of its category, which then covers its other coins.
+/// 
+///     Sells take shares from lots under the configured cost-basis method. When a category is
+///     overweight, its coins are sold in order of the least tax per dollar sold, so that lots
+///     with losses and long-term lots are used before short-term gains. Buys into a category are
+///     split accross its coins in proportion to their current value, or evenly if none are held.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+
+use crate::recording::{PortfolioRecord, TaxRecord, tax_record::TaxObligationType};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone)]
+pub struct TargetAllocation {
+    pub target: String,
+    pub coins: Vec<String>,
+    pub target_weight: f64,
+    pub current_weight: f64,
+    pub target_value: f64,
+    pub current_value: f64,
+    pub difference: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct RebalanceAction {
+    pub target: String,
+    pub coin: String,
+    pub side: String,
+    pub price: f64,
+    pub shares: f64,
+    pub value: f64,
+    pub estimated_gain: f64,
+    pub short_term_gain: f64,
+    pub long_term_gain: f64,
+    pub estimated_tax: f64,
+}
+
+pub struct RebalanceRecord {
+    total_value: f64,
+    targets: Vec<TargetAllocation>,
+    actions: Vec<RebalanceAction>,
+}
+
+impl RebalanceRecord {
+    pub fn new(portfolio: &PortfolioRecord, targets: &Vec<(String, f64)>, categories: &HashMap<String, String>, date: String) -> RebalanceRecord {
+        let mut record = RebalanceRecord {
+            total_value: portfolio.get_total_value(),
+            targets: Vec::<TargetAllocation>::new(),
+            actions: Vec::<RebalanceAction>::new(),
+        };
+
+        // A target names either a coin, or a category of coins. A coin with a target of its own is
+        // only counted towards that target, and not towards its category as well.
+        let is_category = |target: &String| categories.values().any(|x| x == target);
+        let coin_targets: Vec<&String> = targets.iter().map(|x| &x.0).filter(|x| !is_category(x)).collect();
+        for (target, weight) in targets {
+            let mut coins: Vec<String> = if is_category(target) {
+                categories
+                    .iter()
+                    .filter(|x| x.1 == target && !coin_targets.contains(&x.0))
+                    .map(|x| x.0.to_string())
+                    .collect()
+            } else {
+                vec![target.to_string()]
+            };
+            coins.sort();
+
+            let current_value: f64 = coins
+                .iter()
+                .filter_map(|x| portfolio.get_entry(x))
+                .map(|x| x.market_value)
+                .sum();
+            let target_value = record.total_value * weight / 100.0;
+            record.targets.push(TargetAllocation {
+                target: target.to_string(),
+                coins: coins,
+                target_weight: *weight,
+                current_weight: if record.total_value > 0.0 { current_value / record.total_value * 100.0 } else { 0.0 },
+                target_value: target_value,
+                current_value: current_value,
+                difference: target_value - current_value,
+            });
+        }
+
+        for i in 0..record.targets.len() {
+            let target = record.targets[i].clone();
+            if target.difference < 0.0 {
+                record.plan_sells(portfolio, &target, -target.difference, date.as_str());
+            } else if target.difference > 0.0 {
+                record.plan_buys(portfolio, &target, target.difference);
+            }
+        }
+        record
+    }
+
+    pub fn get_total_value(&self) -> f64 {
+        return self.total_value;
+    }
+
+    pub fn get_targets(&self) -> &Vec<TargetAllocation> {
+        return &self.targets;
+    }
+
+    pub fn get_actions(&self) -> &Vec<RebalanceAction> {
+        return &self.actions;
+    }
+
+    pub fn get_estimated_realized_gains(&self) -> f64 {
+        self.actions.iter().map(|x| x.estimated_gain).sum()
+    }
+
+    pub fn get_estimated_tax(&self) -> f64 {
+        self.actions.iter().map(|x| x.estimated_tax).sum()
+    }
+
+    fn plan_sells(&mut self, portfolio: &PortfolioRecord, target: &TargetAllocation, amount: f64, date: &str) {
+        // Rank coins by the tax owed per dollar sold should the whole amount come from them.
+        let mut candidates = Vec::<(f64, String)>::new();
+        for coin in &target.coins {
+            if let Some(entry) = portfolio.get_entry(coin) {
+                if entry.price <= 0.0 || entry.market_value <= 0.0 {
+                    continue;
+                }
+                let value = amount.min(entry.market_value);
+                let tax: f64 = entry.record
+                    .estimate_sell(date.to_string(), entry.price, value / entry.price)
+                    .iter()
+                    .map(|x| x.tax_obligation)
+                    .sum();
+                candidates.push((tax / value, coin.to_string()));
+            }
+        }
+        candidates.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        let mut remaining = amount;
+        for (_rate, coin) in candidates {
+            if remaining <= 0.0 {
+                break;
+            }
+            let entry = portfolio.get_entry(coin.as_str()).unwrap();
+            let value = remaining.min(entry.market_value);
+            let shares = value / entry.price;
+            let tax_records = entry.record.estimate_sell(date.to_string(), entry.price, shares);
+            self.add_action(target.target.to_string(), coin, "sell", entry.price, shares, &tax_records);
+            remaining -= value;
+        }
+    }
+
+    fn plan_buys(&mut self, portfolio: &PortfolioRecord, target: &TargetAllocation, amount: f64) {
+        let held: f64 = target.coins
+            .iter()
+            .filter_map(|x| portfolio.get_entry(x))
+            .map(|x| x.market_value)
+            .sum();
+        for coin in &target.coins {
+            let entry = portfolio.get_entry(coin);
+            let share = match entry {
+                Some(entry) if held > 0.0 => { entry.market_value / held },
+                _ if held > 0.0 => { 0.0 },
+                _ => { 1.0 / target.coins.len() as f64 }
+            };
+            if share <= 0.0 {
+                continue;
+            }
+
+            // Without a price the buy can only be expressed as a value.
+            let price = entry.map(|x| x.price).unwrap_or(0.0);
+            let value = amount * share;
+            let shares = if price > 0.0 { value / price } else { 0.0 };
+            self.actions.push(RebalanceAction {
+                target: target.target.to_string(),
+                coin: coin.to_string(),
+                side: "buy".to_string(),
+                price: price,
+                shares: shares,
+                value: value,
+                estimated_gain: 0.0,
+                short_term_gain: 0.0,
+                long_term_gain: 0.0,
+                estimated_tax: 0.0,
+            });
+        }
+    }
+
+    fn add_action(&mut self, target: String, coin: String, side: &str, price: f64, shares: f64, tax_records: &Vec<TaxRecord>) {
+        let mut short_term_gain = 0.0;
+        let mut long_term_gain = 0.0;
+        for record in tax_records {
+            match record.tax_obligation_type {
+                TaxObligationType::ShortTermCapitalGains => { short_term_gain += record.profit; },
+                TaxObligationType::LongTermCapitalGains => { long_term_gain += record.profit; }
+            }
+        }
+        self.actions.push(RebalanceAction {
+            target: target,
+            coin: coin,
+            side: side.to_string(),
+            price: price,
+            shares: shares,
+            value: price * shares,
+            estimated_gain: short_term_gain + long_term_gain,
+            short_term_gain: short_term_gain,
+            long_term_gain: long_term_gain,
+            estimated_tax: tax_records.iter().map(|x| x.tax_obligation).sum(),
+        });
+    }
+}
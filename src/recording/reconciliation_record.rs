@@ -0,0 +1,82 @@
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// reconciliation_record.rs
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Description:
+///     A structure that compares the balances reported by each platform against the ledger.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Usage:
+///     Retrieve a ReconciliationRecord from a CryptfolioApp after syncing platforms.
+/// 
+///     let reconciliation: ReconciliationRecord = app.get_reconciliation(0.00000001);
+///     for entry in reconciliation.get_mismatches() {
+///         println!("{} {}: reported {} | ledger {}",
+///             entry.platform, entry.coin, entry.reported_balance, entry.ledger_balance
+///         );
+///     }
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Notes:
+///     Balances are compared per platform and coin, summing every account (e.g. multiple
+///     Coinbase Pro profiles) of that coin on the platform. A mismatch generally indicates a
+///     missing or misparsed transaction.
+/// 
+///     The tolerance is an absolute number of coins.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, Clone)]
+pub struct ReconciliationEntry {
+    pub platform: String,
+    pub coin: String,
+    pub reported_date: String,
+    pub reported_balance: f64,
+    pub ledger_balance: f64,
+    pub difference: f64,
+}
+
+pub struct ReconciliationRecord {
+    tolerance: f64,
+    entries: Vec<ReconciliationEntry>,
+}
+
+impl ReconciliationRecord {
+    pub fn new(tolerance: f64) -> ReconciliationRecord {
+        ReconciliationRecord {
+            tolerance: tolerance,
+            entries: Vec::<ReconciliationEntry>::new(),
+        }
+    }
+
+    pub fn get_tolerance(&self) -> f64 {
+        return self.tolerance;
+    }
+
+    pub fn get_entries(&self) -> &Vec<ReconciliationEntry> {
+        return &self.entries;
+    }
+
+    pub fn get_mismatches(&self) -> Vec<&ReconciliationEntry> {
+        self.entries.iter().filter(|x| x.difference.abs() > self.tolerance).collect()
+    }
+
+    pub fn is_reconciled(&self) -> bool {
+        return self.get_mismatches().is_empty();
+    }
+
+    pub fn add_balance(&mut self, platform: String, coin: String, reported_date: String, reported_balance: f64, ledger_balance: f64) {
+        self.entries.push(ReconciliationEntry {
+            platform: platform,
+            coin: coin,
+            reported_date: reported_date,
+            reported_balance: reported_balance,
+            ledger_balance: ledger_balance,
+            difference: reported_balance - ledger_balance,
+        });
+    }
+}
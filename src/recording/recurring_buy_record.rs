@@ -0,0 +1,298 @@
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// recurring_buy_record.rs
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Description:
+///     A structure that represents every recurring buy (dollar-cost averaging) schedule found
+///     among the buys of each coin on each platform.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Usage:
+///     Retrieve a RecurringBuyRecord from a CryptfolioApp, supplying daily prices.
+/// 
+///     let recurring: RecurringBuyRecord = app.get_recurring_buys(&prices);
+///     for schedule in recurring.get_schedules() {
+///         println!("{} on {}: ${} {} | deployed: {} | avg entry: {} vs period avg: {}",
+///             schedule.coin, schedule.platform, schedule.amount, schedule.cadence.to_string(),
+///             schedule.total_deployed, schedule.average_entry_price, schedule.average_period_price
+///         );
+///         println!("Next buys: {:?}", schedule.get_projected_buys(3));
+///     }
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Notes:
+///     A schedule is at least three buys of the same coin on the same platform, for about the
+///     same amount (within 5%), at a regular interval. The amount is the total cost of each buy
+///     in the reporting currency, fees included.
+/// 
+///     The average period price is the mean daily price from the first to the last buy of the
+///     schedule. An average entry price below it means the schedule bought in cheaper than a
+///     buyer spreading purchases evenly over every day of the period.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+
+use crate::pricing::PriceSource;
+use crate::recording::{LedgerEntry, LedgerEntryType};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
+use std::fmt;
+
+const MINIMUM_BUYS: usize = 3;
+const AMOUNT_TOLERANCE: f64 = 0.05;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cadence {
+    Daily,
+    Weekly,
+    Biweekly,
+    Monthly,
+}
+
+impl fmt::Display for Cadence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Daily => { write!(f, "daily") },
+            Self::Weekly => { write!(f, "weekly") },
+            Self::Biweekly => { write!(f, "every two weeks") },
+            Self::Monthly => { write!(f, "monthly") },
+        }
+    }
+}
+
+impl Cadence {
+    pub fn get_days(&self) -> i64 {
+        match self {
+            Self::Daily => { 1 },
+            Self::Weekly => { 7 },
+            Self::Biweekly => { 14 },
+            Self::Monthly => { 30 },
+        }
+    }
+
+    /// The cadence an interval between two buys fits, if any.
+    pub fn from_interval(days: i64) -> Option<Cadence> {
+        match days {
+            1 => { Some(Self::Daily) },
+            6..=8 => { Some(Self::Weekly) },
+            13..=15 => { Some(Self::Biweekly) },
+            27..=32 => { Some(Self::Monthly) },
+            _ => { None }
+        }
+    }
+
+    pub fn get_next_date(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Self::Monthly => {
+                let (year, month) = if date.month() == 12 { (date.year() + 1, 1) } else { (date.year(), date.month() + 1) };
+
+                // Clamp to the end of shorter months (e.g. the 31st -> the 30th).
+                let mut day = date.day();
+                while NaiveDate::from_ymd_opt(year, month, day).is_none() {
+                    day -= 1;
+                }
+                NaiveDate::from_ymd_opt(year, month, day).unwrap()
+            },
+            _ => { date + Duration::days(self.get_days()) }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RecurringBuy {
+    pub id: String,
+    pub date: NaiveDateTime,
+    pub price: f64,
+    pub shares: f64,
+    pub cost: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct RecurringSchedule {
+    pub coin: String,
+    pub platform: String,
+    pub cadence: Cadence,
+    pub amount: f64,
+    pub buys: Vec<RecurringBuy>,
+    pub total_deployed: f64,
+    pub total_shares: f64,
+    pub average_entry_price: f64,
+    pub average_period_price: f64,
+}
+
+impl RecurringSchedule {
+    pub fn get_start_date(&self) -> NaiveDate {
+        self.buys.first().unwrap().date.date()
+    }
+
+    pub fn get_end_date(&self) -> NaiveDate {
+        self.buys.last().unwrap().date.date()
+    }
+
+    /// Whether the next buy is due, or was due less than one interval ago.
+    pub fn is_active(&self, today: NaiveDate) -> bool {
+        let next = self.cadence.get_next_date(self.get_end_date());
+        (today - next).num_days() < self.cadence.get_days()
+    }
+
+    pub fn get_projected_buys(&self, count: usize) -> Vec<NaiveDate> {
+        let mut dates = Vec::<NaiveDate>::new();
+        let mut date = self.get_end_date();
+        while dates.len() < count {
+            date = self.cadence.get_next_date(date);
+            dates.push(date);
+        }
+        dates
+    }
+
+    /// How much cheaper (positive) or more expensive (negative) the schedule bought in than the
+    /// average price over the period, as a fraction.
+    pub fn get_entry_advantage(&self) -> f64 {
+        if self.average_entry_price <= 0.0 || !self.average_period_price.is_finite() {
+            return f64::NAN;
+        }
+        self.average_period_price / self.average_entry_price - 1.0
+    }
+}
+
+pub struct RecurringBuyRecord {
+    schedules: Vec<RecurringSchedule>,
+}
+
+impl RecurringBuyRecord {
+    pub fn new(entries: &Vec<LedgerEntry>, prices: &dyn PriceSource) -> RecurringBuyRecord {
+        let mut record = RecurringBuyRecord {
+            schedules: Vec::<RecurringSchedule>::new(),
+        };
+
+        // Group the buys of each coin on each platform, which are already in date order.
+        let mut groups = Vec::<((String, String), Vec<RecurringBuy>)>::new();
+        for entry in entries.iter().filter(|x| x.r#type == LedgerEntryType::Buy) {
+            let fee = if entry.fee.is_finite() { entry.fee.abs() } else { 0.0 };
+            let buy = RecurringBuy {
+                id: entry.id.to_string(),
+                date: entry.date,
+                price: entry.unit_price,
+                shares: entry.unit_size.abs(),
+                cost: entry.unit_price * entry.unit_size.abs() + fee,
+            };
+            if !buy.cost.is_finite() || buy.cost <= 0.0 {
+                continue;
+            }
+            let key = (entry.coin.to_string(), entry.destination.to_string());
+            match groups.iter_mut().find(|x| x.0 == key) {
+                Some(group) => { group.1.push(buy); },
+                None => { groups.push((key, vec![buy])); }
+            }
+        }
+
+        for ((coin, platform), buys) in groups {
+            for run in Self::find_runs(buys) {
+                record.add_schedule(coin.to_string(), platform.to_string(), run, prices);
+            }
+        }
+        record.schedules.sort_by(|a, b| a.get_start_date().cmp(&b.get_start_date()));
+        record
+    }
+
+    pub fn get_schedules(&self) -> &Vec<RecurringSchedule> {
+        return &self.schedules;
+    }
+
+    pub fn get_active_schedules(&self, today: NaiveDate) -> Vec<&RecurringSchedule> {
+        self.schedules.iter().filter(|x| x.is_active(today)).collect()
+    }
+
+    pub fn get_total_deployed(&self) -> f64 {
+        self.schedules.iter().map(|x| x.total_deployed).sum()
+    }
+
+    /// Splits buys into runs of a similar amount at a regular interval.
+    fn find_runs(buys: Vec<RecurringBuy>) -> Vec<(Cadence, Vec<RecurringBuy>)> {
+        let mut runs = Vec::<(Cadence, Vec<RecurringBuy>)>::new();
+
+        // Cluster by amount first, as one-off buys may fall between those of a schedule.
+        let mut clusters = Vec::<Vec<RecurringBuy>>::new();
+        for buy in buys {
+            match clusters.iter_mut().find(|x| (x[0].cost - buy.cost).abs() <= x[0].cost * AMOUNT_TOLERANCE) {
+                Some(cluster) => { cluster.push(buy); },
+                None => { clusters.push(vec![buy]); }
+            }
+        }
+
+        for cluster in clusters {
+            let mut run = Vec::<RecurringBuy>::new();
+            let mut cadence: Option<Cadence> = None;
+            for buy in cluster {
+                let interval = run.last().map(|x: &RecurringBuy| (buy.date.date() - x.date.date()).num_days());
+                let fits = match (interval, &cadence) {
+                    (None, _) => { true },
+                    (Some(days), None) => {
+                        cadence = Cadence::from_interval(days);
+                        cadence.is_some()
+                    },
+                    (Some(days), Some(current)) => { Cadence::from_interval(days).as_ref() == Some(current) }
+                };
+                if fits {
+                    run.push(buy);
+                    continue;
+                }
+
+                if run.len() >= MINIMUM_BUYS {
+                    runs.push((cadence.clone().unwrap(), run));
+                    run = Vec::<RecurringBuy>::new();
+                } else {
+                    // The last buy of a failed run may yet start the next one.
+                    run = run.split_off(run.len() - 1);
+                }
+                cadence = run.last().and_then(|x| Cadence::from_interval((buy.date.date() - x.date.date()).num_days()));
+                if run.is_empty() || cadence.is_none() {
+                    run.clear();
+                }
+                run.push(buy);
+            }
+            if run.len() >= MINIMUM_BUYS {
+                if let Some(cadence) = cadence {
+                    runs.push((cadence, run));
+                }
+            }
+        }
+        runs
+    }
+
+    fn add_schedule(&mut self, coin: String, platform: String, run: (Cadence, Vec<RecurringBuy>), prices: &dyn PriceSource) {
+        let (cadence, buys) = run;
+        let total_deployed: f64 = buys.iter().map(|x| x.cost).sum();
+        let total_shares: f64 = buys.iter().map(|x| x.shares).sum();
+
+        let start = buys.first().unwrap().date.date();
+        let end = buys.last().unwrap().date.date();
+        let mut period_prices = Vec::<f64>::new();
+        let mut date = start;
+        while date <= end {
+            if let Some(price) = prices.get_price(coin.as_str(), date) {
+                period_prices.push(price);
+            }
+            date = date.succ_opt().unwrap();
+        }
+        let average_period_price = if period_prices.is_empty() {
+            f64::NAN
+        } else {
+            period_prices.iter().sum::<f64>() / period_prices.len() as f64
+        };
+
+        self.schedules.push(RecurringSchedule {
+            coin: coin,
+            platform: platform,
+            cadence: cadence,
+            amount: total_deployed / buys.len() as f64,
+            total_deployed: total_deployed,
+            total_shares: total_shares,
+            average_entry_price: if total_shares > 0.0 { total_deployed / total_shares } else { 0.0 },
+            average_period_price: average_period_price,
+            buys: buys,
+        });
+    }
+}
@@ -0,0 +1,206 @@
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// return_record.rs
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Description:
+///     A structure that represents the time-weighted and money-weighted return over a period.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Usage:
+///     Retrieve a ReturnRecord from a CryptfolioApp after the history has been rebuilt.
+/// 
+///     let ytd: ReturnRecord = app.get_portfolio_returns(Period::YearToDate);
+///     println!("TWR: {}% | MWR: {}%",
+///         ytd.get_time_weighted_return() * 100.0,
+///         ytd.get_money_weighted_return().unwrap_or(f64::NAN) * 100.0
+///     );
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Notes:
+///     Cash flows are signed from the perspective of the portfolio: deposits (money in) are
+///     positive and withdrawals (money out) are negative. Flows are assumed to take place at the
+///     start of the day they are dated, and so are included in that day's closing value.
+/// 
+///     The time-weighted return is cumulative for the period. The money-weighted return is the
+///     annualized internal rate of return (XIRR) of the period's cash flows.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+
+use chrono::{Datelike, Duration, NaiveDate};
+
+pub enum Period {
+    YearToDate,
+    OneYear,
+    SinceInception,
+    Custom(NaiveDate, NaiveDate),
+}
+
+impl Period {
+    pub fn to_range(&self, inception: NaiveDate, today: NaiveDate) -> (NaiveDate, NaiveDate) {
+        let (start, end) = match self {
+            Self::YearToDate => { (NaiveDate::from_ymd_opt(today.year(), 1, 1).unwrap(), today) },
+            Self::OneYear => { (today - Duration::days(365), today) },
+            Self::SinceInception => { (inception, today) },
+            Self::Custom(start, end) => { (*start, *end) },
+        };
+
+        // Nothing can be measured before the first entry.
+        if start < inception {
+            return (inception, end);
+        }
+        (start, end)
+    }
+}
+
+pub struct ReturnRecord {
+    start: NaiveDate,
+    end: NaiveDate,
+    start_value: f64,
+    end_value: f64,
+    net_flows: f64,
+    time_weighted_return: f64,
+    money_weighted_return: Option<f64>,
+}
+
+impl ReturnRecord {
+    pub fn new(values: &Vec<(NaiveDate, f64)>, flows: &Vec<(NaiveDate, f64)>) -> ReturnRecord {
+        let start = values.first().map(|x| x.0).unwrap_or_default();
+        let end = values.last().map(|x| x.0).unwrap_or(start);
+
+        // Flows on the first day are already a part of the starting value.
+        let period_flows: Vec<(NaiveDate, f64)> = flows
+            .iter()
+            .filter(|x| x.0 > start && x.0 <= end)
+            .map(|x| *x)
+            .collect();
+
+        ReturnRecord {
+            start: start,
+            end: end,
+            start_value: values.first().map(|x| x.1).unwrap_or(0.0),
+            end_value: values.last().map(|x| x.1).unwrap_or(0.0),
+            net_flows: period_flows.iter().map(|x| x.1).sum(),
+            time_weighted_return: Self::calculate_time_weighted_return(values, &period_flows),
+            money_weighted_return: Self::calculate_money_weighted_return(values, &period_flows),
+        }
+    }
+
+    pub fn get_start_date(&self) -> NaiveDate {
+        return self.start;
+    }
+
+    pub fn get_end_date(&self) -> NaiveDate {
+        return self.end;
+    }
+
+    pub fn get_start_value(&self) -> f64 {
+        return self.start_value;
+    }
+
+    pub fn get_end_value(&self) -> f64 {
+        return self.end_value;
+    }
+
+    pub fn get_net_flows(&self) -> f64 {
+        return self.net_flows;
+    }
+
+    pub fn get_time_weighted_return(&self) -> f64 {
+        return self.time_weighted_return;
+    }
+
+    pub fn get_annualized_time_weighted_return(&self) -> f64 {
+        let years = (self.end - self.start).num_days() as f64 / 365.0;
+        if years < 1.0 {
+            return self.time_weighted_return;
+        }
+        (1.0 + self.time_weighted_return).powf(1.0 / years) - 1.0
+    }
+
+    pub fn get_money_weighted_return(&self) -> Option<f64> {
+        return self.money_weighted_return;
+    }
+
+    pub fn get_daily_returns(values: &Vec<(NaiveDate, f64)>, flows: &Vec<(NaiveDate, f64)>) -> Vec<(NaiveDate, f64)> {
+        let mut returns = Vec::<(NaiveDate, f64)>::new();
+        let mut i: usize = 1;
+        while i < values.len() {
+            let previous = values[i - 1].1;
+            let (date, value) = values[i];
+            let flow: f64 = flows.iter().filter(|x| x.0 > values[i - 1].0 && x.0 <= date).map(|x| x.1).sum();
+
+            // A period starting from nothing has no measurable return.
+            if previous > 0.0 {
+                returns.push((date, (value - flow) / previous - 1.0));
+            } else {
+                returns.push((date, 0.0));
+            }
+            i += 1;
+        }
+        return returns;
+    }
+
+    fn calculate_time_weighted_return(values: &Vec<(NaiveDate, f64)>, flows: &Vec<(NaiveDate, f64)>) -> f64 {
+        let mut growth = 1.0;
+        for (_date, daily_return) in Self::get_daily_returns(values, flows) {
+            growth *= 1.0 + daily_return;
+        }
+        growth - 1.0
+    }
+
+    fn calculate_money_weighted_return(values: &Vec<(NaiveDate, f64)>, flows: &Vec<(NaiveDate, f64)>) -> Option<f64> {
+        let (start, start_value) = *values.first()?;
+        let (end, end_value) = *values.last()?;
+
+        // Cash flows from the investor's point of view: money put in is negative.
+        let mut cash_flows = Vec::<(f64, f64)>::new();
+        cash_flows.push((0.0, -start_value));
+        for (date, amount) in flows {
+            cash_flows.push(((*date - start).num_days() as f64 / 365.0, -amount));
+        }
+        cash_flows.push(((end - start).num_days() as f64 / 365.0, end_value));
+
+        Self::xirr(&cash_flows)
+    }
+
+    fn xirr(cash_flows: &Vec<(f64, f64)>) -> Option<f64> {
+        let npv = |rate: f64| -> f64 {
+            cash_flows.iter().map(|(years, amount)| amount / (1.0 + rate).powf(*years)).sum()
+        };
+
+        // An IRR only exists when money flows both in and out.
+        if !cash_flows.iter().any(|x| x.1 > 0.0) || !cash_flows.iter().any(|x| x.1 < 0.0) {
+            return None;
+        }
+
+        // Bisection is slower than Newton's method, but cannot diverge.
+        let mut low = -0.999999;
+        let mut high = 1.0;
+        while npv(high) > 0.0 && high < 1e9 {
+            high *= 2.0;
+        }
+        if npv(low).signum() == npv(high).signum() {
+            return None;
+        }
+
+        let mut i = 0;
+        while i < 200 {
+            let mid = (low + high) / 2.0;
+            let value = npv(mid);
+            if value.abs() < 1e-9 || (high - low) / 2.0 < 1e-12 {
+                return Some(mid);
+            }
+            if value.signum() == npv(low).signum() {
+                low = mid;
+            } else {
+                high = mid;
+            }
+            i += 1;
+        }
+        Some((low + high) / 2.0)
+    }
+}
@@ -0,0 +1,218 @@
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// risk_record.rs
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Description:
+///     A structure that represents the risk of the portfolio over a period: volatility, maximum
+///     drawdown, Sharpe and Sortino ratios, and the correlation between held coins.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Usage:
+///     Retrieve a RiskRecord from a CryptfolioApp after the history has been rebuilt.
+/// 
+///     let risk: RiskRecord = app.get_risk(Period::OneYear, 0.04);
+///     println!("Volatility: {}% | Max drawdown: {}% | Sharpe: {} | Sortino: {}",
+///         risk.get_volatility() * 100.0,
+///         risk.get_max_drawdown() * 100.0,
+///         risk.get_sharpe_ratio(),
+///         risk.get_sortino_ratio()
+///     );
+///     for (a, b, correlation) in risk.get_correlations() {
+///         println!("{} / {}: {}", a, b, correlation);
+///     }
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Notes:
+///     Portfolio metrics are calculated from daily time-weighted returns, so deposits and
+///     withdrawals do not register as gains or losses. Coin correlations are calculated from
+///     daily price returns over the days both coins were held.
+/// 
+///     Coins trade every day of the year, so daily figures are annualized over 365 days. The
+///     risk-free rate is annual (e.g. 0.04 for 4%).
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+
+use crate::recording::ReturnRecord;
+use chrono::NaiveDate;
+use std::collections::HashMap;
+
+pub struct RiskRecord {
+    start: NaiveDate,
+    end: NaiveDate,
+    risk_free_rate: f64,
+    returns: Vec<(NaiveDate, f64)>,
+    coin_returns: Vec<(String, HashMap<NaiveDate, f64>)>,
+}
+
+impl RiskRecord {
+    pub fn new(values: &Vec<(NaiveDate, f64)>, flows: &Vec<(NaiveDate, f64)>, risk_free_rate: f64) -> RiskRecord {
+        let start = values.first().map(|x| x.0).unwrap_or_default();
+        RiskRecord {
+            start: start,
+            end: values.last().map(|x| x.0).unwrap_or(start),
+            risk_free_rate: risk_free_rate,
+            returns: ReturnRecord::get_daily_returns(values, flows),
+            coin_returns: Vec::<(String, HashMap<NaiveDate, f64>)>::new(),
+        }
+    }
+
+    pub fn get_start_date(&self) -> NaiveDate {
+        return self.start;
+    }
+
+    pub fn get_end_date(&self) -> NaiveDate {
+        return self.end;
+    }
+
+    pub fn get_risk_free_rate(&self) -> f64 {
+        return self.risk_free_rate;
+    }
+
+    pub fn get_daily_returns(&self) -> &Vec<(NaiveDate, f64)> {
+        return &self.returns;
+    }
+
+    pub fn get_coins(&self) -> Vec<String> {
+        self.coin_returns.iter().map(|x| x.0.to_string()).collect()
+    }
+
+    /// Annualized standard deviation of daily returns.
+    pub fn get_volatility(&self) -> f64 {
+        let returns: Vec<f64> = self.returns.iter().map(|x| x.1).collect();
+        Self::standard_deviation(&returns) * 365f64.sqrt()
+    }
+
+    /// Largest peak to trough decline of the time-weighted growth of the portfolio, as a
+    /// positive fraction.
+    pub fn get_max_drawdown(&self) -> f64 {
+        let mut growth = 1.0;
+        let mut peak = 1.0;
+        let mut drawdown: f64 = 0.0;
+        for (_date, daily_return) in &self.returns {
+            growth *= 1.0 + daily_return;
+            if growth > peak {
+                peak = growth;
+            }
+            drawdown = drawdown.max((peak - growth) / peak);
+        }
+        drawdown
+    }
+
+    pub fn get_sharpe_ratio(&self) -> f64 {
+        let volatility = self.get_volatility();
+        if volatility == 0.0 {
+            return f64::NAN;
+        }
+        (self.get_annualized_mean_return() - self.risk_free_rate) / volatility
+    }
+
+    pub fn get_sortino_ratio(&self) -> f64 {
+        // Only returns below the daily risk-free rate count towards downside deviation.
+        let target = self.risk_free_rate / 365.0;
+        let downside: f64 = self.returns
+            .iter()
+            .map(|x| (x.1 - target).min(0.0).powi(2))
+            .sum();
+        if self.returns.is_empty() || downside == 0.0 {
+            return f64::NAN;
+        }
+        let deviation = (downside / self.returns.len() as f64).sqrt() * 365f64.sqrt();
+        (self.get_annualized_mean_return() - self.risk_free_rate) / deviation
+    }
+
+    pub fn get_correlation(&self, a: &str, b: &str) -> Option<f64> {
+        let a = &self.coin_returns.iter().find(|x| x.0 == a)?.1;
+        let b = &self.coin_returns.iter().find(|x| x.0 == b)?.1;
+        let mut x = Vec::<f64>::new();
+        let mut y = Vec::<f64>::new();
+        for (date, value) in a {
+            if let Some(other) = b.get(date) {
+                x.push(*value);
+                y.push(*other);
+            }
+        }
+        if x.len() < 2 {
+            return None;
+        }
+
+        let mean_x = x.iter().sum::<f64>() / x.len() as f64;
+        let mean_y = y.iter().sum::<f64>() / y.len() as f64;
+        let mut covariance = 0.0;
+        let mut variance_x = 0.0;
+        let mut variance_y = 0.0;
+        for i in 0..x.len() {
+            covariance += (x[i] - mean_x) * (y[i] - mean_y);
+            variance_x += (x[i] - mean_x).powi(2);
+            variance_y += (y[i] - mean_y).powi(2);
+        }
+        if variance_x == 0.0 || variance_y == 0.0 {
+            return None;
+        }
+        Some(covariance / (variance_x * variance_y).sqrt())
+    }
+
+    /// Every pair of coins along with their correlation, skipping pairs which never overlap.
+    pub fn get_correlations(&self) -> Vec<(String, String, f64)> {
+        let coins = self.get_coins();
+        let mut correlations = Vec::<(String, String, f64)>::new();
+        for (i, a) in coins.iter().enumerate() {
+            for b in coins.iter().skip(i + 1) {
+                if let Some(correlation) = self.get_correlation(a, b) {
+                    correlations.push((a.to_string(), b.to_string(), correlation));
+                }
+            }
+        }
+        correlations
+    }
+
+    /// The full correlation matrix, in the order of get_coins(). Pairs without enough overlap
+    /// are NaN.
+    pub fn get_correlation_matrix(&self) -> Vec<Vec<f64>> {
+        let coins = self.get_coins();
+        coins
+            .iter()
+            .map(|a| {
+                coins
+                    .iter()
+                    .map(|b| if a == b { 1.0 } else { self.get_correlation(a, b).unwrap_or(f64::NAN) })
+                    .collect()
+            })
+            .collect()
+    }
+
+    pub fn add_coin(&mut self, coin: String, prices: &Vec<(NaiveDate, f64)>) {
+        let mut returns = HashMap::<NaiveDate, f64>::new();
+        let mut i: usize = 1;
+        while i < prices.len() {
+            let (previous_date, previous) = prices[i - 1];
+            let (date, price) = prices[i];
+
+            // Only consecutive days are comparable, as a coin may be sold and bought back later.
+            if previous > 0.0 && price > 0.0 && previous_date.succ_opt() == Some(date) {
+                returns.insert(date, price / previous - 1.0);
+            }
+            i += 1;
+        }
+        self.coin_returns.push((coin, returns));
+    }
+
+    fn get_annualized_mean_return(&self) -> f64 {
+        if self.returns.is_empty() {
+            return 0.0;
+        }
+        self.returns.iter().map(|x| x.1).sum::<f64>() / self.returns.len() as f64 * 365.0
+    }
+
+    fn standard_deviation(values: &Vec<f64>) -> f64 {
+        if values.len() < 2 {
+            return 0.0;
+        }
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        let variance = values.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (values.len() - 1) as f64;
+        variance.sqrt()
+    }
+}
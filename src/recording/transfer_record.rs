@@ -0,0 +1,162 @@
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// transfer_record.rs
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Description:
+///     Structures that represent coin transfers and the pairing of each side of a transfer that
+///     was recorded by two different platforms.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Usage:
+///     Transfers are matched automatically after every sync. Anything left unmatched can be
+///     reviewed manually.
+/// 
+///     for transfer in app.get_transfer_review_queue() {
+///         println!("{} | {} {} | {} -> {}",
+///             transfer.date, transfer.unit_size, transfer.coin, transfer.origin, transfer.destination
+///         );
+///     }
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+/// 
+/// Notes:
+///     A transfer is outgoing when it leaves a known account, and incoming when it arrives at one.
+///     A transfer between two known accounts (e.g. Coinbase -> Coinbase Pro) is both, which is
+///     how the copy recorded by each platform is found.
+/// 
+///     The incoming side may be smaller than the outgoing side by the fee paid, plus a relative
+///     tolerance for network fees which the sending platform does not report.
+/// 
+/// ///////////////////////////////////////////////////////////////////////////////////////////////
+
+use chrono::{Duration, NaiveDateTime};
+use std::collections::HashSet;
+
+#[derive(Debug, Clone)]
+pub struct TransferRecord {
+    pub id: String,
+    pub date: NaiveDateTime,
+    pub coin: String,
+    pub unit_size: f64,
+    pub fee: f64,
+    pub origin: String,
+    pub destination: String,
+    pub origin_platform: String,
+    pub destination_platform: String,
+}
+
+impl TransferRecord {
+    pub fn is_outgoing(&self) -> bool {
+        return !self.origin_platform.is_empty();
+    }
+
+    pub fn is_incoming(&self) -> bool {
+        return !self.destination_platform.is_empty();
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TransferMatch {
+    pub outgoing: TransferRecord,
+    pub incoming: TransferRecord,
+}
+
+pub struct TransferMatcher {
+    window: Duration,
+    fee_tolerance: f64,
+}
+
+impl TransferMatcher {
+    pub fn new(window_hours: i64, fee_tolerance: f64) -> TransferMatcher {
+        TransferMatcher {
+            window: Duration::hours(window_hours),
+            fee_tolerance: fee_tolerance,
+        }
+    }
+
+    pub fn get_window(&self) -> Duration {
+        return self.window;
+    }
+
+    pub fn get_fee_tolerance(&self) -> f64 {
+        return self.fee_tolerance;
+    }
+
+    pub fn is_match(&self, outgoing: &TransferRecord, incoming: &TransferRecord) -> bool {
+        if outgoing.id == incoming.id || outgoing.coin != incoming.coin {
+            return false;
+        }
+        if !outgoing.is_outgoing() || !incoming.is_incoming() {
+            return false;
+        }
+
+        // A transfer which leaves and arrives at the same platform is not a cross-platform pair.
+        if outgoing.origin_platform == incoming.destination_platform {
+            return false;
+        }
+        if (incoming.date - outgoing.date).num_seconds().abs() > self.window.num_seconds() {
+            return false;
+        }
+
+        let sent = outgoing.unit_size.abs();
+        let received = incoming.unit_size.abs();
+        let fee = if outgoing.fee.is_finite() { outgoing.fee.abs() } else { 0.0 };
+        let difference = sent - received;
+        difference >= -(sent * 1e-9) && difference <= fee + sent * self.fee_tolerance
+    }
+
+    /// Pairs outgoing and incoming transfers, closest in time and amount first. Returns the new
+    /// matches along with every transfer which could not be matched.
+    pub fn match_transfers(&self, transfers: &Vec<TransferRecord>, linked: &Vec<String>) -> (Vec<TransferMatch>, Vec<TransferRecord>) {
+        let linked: HashSet<&str> = linked.iter().map(|x| x.as_str()).collect();
+        let mut candidates = Vec::<(i64, f64, usize, usize)>::new();
+        for (i, outgoing) in transfers.iter().enumerate() {
+            if linked.contains(outgoing.id.as_str()) {
+                continue;
+            }
+            for (j, incoming) in transfers.iter().enumerate() {
+                if linked.contains(incoming.id.as_str()) || !self.is_match(outgoing, incoming) {
+                    continue;
+                }
+                candidates.push((
+                    (incoming.date - outgoing.date).num_seconds().abs(),
+                    (outgoing.unit_size.abs() - incoming.unit_size.abs()).abs(),
+                    i,
+                    j
+                ));
+            }
+        }
+        candidates.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal)));
+
+        let mut used = vec![false; transfers.len()];
+        let mut matches = Vec::<TransferMatch>::new();
+        for (_, _, i, j) in candidates {
+            if used[i] || used[j] {
+                continue;
+            }
+            used[i] = true;
+            used[j] = true;
+            matches.push(TransferMatch {
+                outgoing: transfers[i].clone(),
+                incoming: transfers[j].clone(),
+            });
+        }
+
+        let unmatched = transfers
+            .iter()
+            .enumerate()
+            .filter(|(i, x)| !used[*i] && !linked.contains(x.id.as_str()))
+            .map(|(_, x)| x.clone())
+            .collect();
+        (matches, unmatched)
+    }
+}
+
+impl Default for TransferMatcher {
+    fn default() -> TransferMatcher {
+        TransferMatcher::new(48, 0.01)
+    }
+}
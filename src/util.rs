@@ -22,7 +22,23 @@ use chrono::NaiveDateTime;
 
 pub struct Util {}
 
+// Fiat currencies supported by the synced exchanges. Accounts in these currencies hold cash, not coins.
+const FIAT_CURRENCIES: [&str; 7] = ["USD", "EUR", "GBP", "CAD", "AUD", "CHF", "JPY"];
+
 impl Util {
+    pub fn is_fiat(currency: &str) -> bool {
+        FIAT_CURRENCIES.contains(&currency)
+    }
+
+    /// Splits a trading pair (e.g. 'BTC-EUR') into its coin and quote currency.
+    pub fn split_pair(pair: &str) -> (String, String) {
+        let mut split = pair.splitn(2, '-');
+        (
+            split.next().unwrap_or("").to_string(),
+            split.next().unwrap_or("USD").to_string()
+        )
+    }
+
     pub fn parse_date(date: &str) -> Result<NaiveDateTime, CryptfolioError> {
         match NaiveDateTime::parse_from_str(date, "%Y-%m-%dT%H:%M:%SZ") {
             Ok(dt) => {
@@ -1,4 +1,6 @@
 use cryptfolio::app::CryptfolioApp;
+use cryptfolio::database::entry::{AccountBalance, CoinAccount, CoinTransfer, DatabaseEntry, FiatTransfer, PlatformConnection};
+use cryptfolio::platform::SyncClient;
 
 // #[tokio::test]
 // async fn coinbase_sync() {
@@ -47,7 +49,7 @@ use cryptfolio::app::CryptfolioApp;
 // #[test]
 // fn coin_record() {
 //     let app = CryptfolioApp::new("local/test.db").unwrap();
-//     let coin = app.get_coin_record("SOL".to_string());
+//     let coin = app.get_coin_record("SOL".to_string(), None);
 
 //     println!("Balance: {} | Avg: {} | Gross: {} | Net: {} | Current Invested: {} | Total Invested: {} | Fees: {}",
 //         coin.get_shares(),
@@ -70,7 +72,7 @@ use cryptfolio::app::CryptfolioApp;
 //     let coins = vec!["BTC", "SOL", "JASMY", "MNDE", "FORT", "00", "ROSE", "ADA", "VGX", "TONE", "JUP", "XCN", "AERGO", "MKR", "BOND"];
 
 //     for coin in coins {
-//         let c = app.get_coin_record(coin.to_string());
+//         let c = app.get_coin_record(coin.to_string(), None);
 //         println!("{}\n-----------------------------------------------------------------------------------------------------------------", coin);
 //         println!("Balance: {} | Avg: {} | Gross: {} | Net: {} | Current Invested: {} | Total Invested: {} | Fees: {}",
 //             c.get_shares(),
@@ -101,4 +103,1237 @@ use cryptfolio::app::CryptfolioApp;
 //     for connection in app.get_connections() {
 //         println!("Connection: {} [{}]", connection.1.get_name(), connection.0);
 //     }
-// }
\ No newline at end of file
+// }
+
+#[test]
+fn portfolio_overview() {
+    use cryptfolio::recording::{CoinRecord, PortfolioRecord};
+
+    let mut btc = CoinRecord::new();
+    btc.add_buy("2021-01-01T00:00:00Z".to_string(), 10000.0, 2.0, 10.0);
+    btc.add_sell("2022-01-01T00:00:00Z".to_string(), 20000.0, 1.0, 10.0);
+    btc.update();
+    let mut eth = CoinRecord::new();
+    eth.add_buy("2021-01-01T00:00:00Z".to_string(), 1000.0, 1.0, 0.0);
+    eth.update();
+
+    let mut portfolio = PortfolioRecord::new();
+    portfolio.add_coin("ETH".to_string(), eth, None);
+    portfolio.add_coin("BTC".to_string(), btc, Some(30000.0));
+    portfolio.add_cash("Coinbase".to_string(), "USD".to_string(), 10000.0, 10000.0);
+    portfolio.update();
+
+    assert_eq!(portfolio.get_market_value(), 30000.0);
+    assert_eq!(portfolio.get_cash_value(), 10000.0);
+    assert_eq!(portfolio.get_total_value(), 40000.0);
+    assert_eq!(portfolio.get_cash_allocation(), 25.0);
+    assert_eq!(portfolio.get_realized_profit(), 10000.0);
+    assert_eq!(portfolio.get_total_fees(), 20.0);
+    assert_eq!(portfolio.get_current_invested(), 11000.0);
+    assert_eq!(portfolio.get_unrealized_profit(), 19000.0);
+
+    // Entries are ordered by value, and a coin without a price holds no allocation.
+    let entries = portfolio.get_entries();
+    assert_eq!(entries[0].coin, "BTC");
+    assert_eq!(entries[0].allocation, 75.0);
+    assert_eq!(entries[1].market_value, 0.0);
+    assert_eq!(entries[1].allocation, 0.0);
+    let allocated: f64 = entries.iter().map(|x| x.allocation).sum::<f64>() + portfolio.get_cash_allocation();
+    assert!((allocated - 100.0).abs() < 1e-9);
+}
+
+// #[test]
+// fn portfolio_history() {
+//     let app = CryptfolioApp::new("local/test.db").unwrap();
+//     let mut prices = cryptfolio::pricing::PriceHistory::new();
+//     prices.add_price("SOL", chrono::NaiveDate::from_ymd_opt(2022, 1, 1).unwrap(), 170.0);
+//     prices.add_price("SOL", chrono::NaiveDate::from_ymd_opt(2022, 6, 1).unwrap(), 40.0);
+//     app.rebuild_history(&prices).unwrap();
+
+//     let history = app.get_coin_history("SOL", chrono::NaiveDate::from_ymd_opt(2022, 1, 1).unwrap(), chrono::NaiveDate::from_ymd_opt(2022, 12, 31).unwrap());
+//     for point in history.get_points() {
+//         println!("{} | Shares: {} | Price: {} | Value: {}", point.date, point.shares, point.unit_price, point.value);
+//     }
+// }
+
+#[test]
+fn return_calculations() {
+    let date = |y: i32, m: u32, d: u32| chrono::NaiveDate::from_ymd_opt(y, m, d).unwrap();
+
+    // 10% in each half of the year, with a deposit of 1000 included in the mid-year value.
+    let values = vec![(date(2022, 1, 1), 1000.0), (date(2022, 7, 2), 2100.0), (date(2023, 1, 1), 2310.0)];
+    let flows = vec![(date(2022, 1, 1), 500.0), (date(2022, 7, 2), 1000.0)];
+    let returns = cryptfolio::recording::ReturnRecord::new(&values, &flows);
+    assert!((returns.get_time_weighted_return() - 0.21).abs() < 1e-9);
+    assert_eq!(returns.get_net_flows(), 1000.0);
+    assert_eq!(returns.get_start_value(), 1000.0);
+    assert_eq!(returns.get_end_value(), 2310.0);
+
+    // The money-weighted return solves the cash flows to a net present value of zero.
+    let mwr = returns.get_money_weighted_return().unwrap();
+    let years = (date(2022, 7, 2) - date(2022, 1, 1)).num_days() as f64 / 365.0;
+    let npv = -1000.0 - 1000.0 / (1.0 + mwr).powf(years) + 2310.0 / (1.0 + mwr);
+    assert!(npv.abs() < 1e-6);
+    assert!(mwr > 0.0 && mwr < 0.21);
+
+    // Without flows over exactly one year, both returns are the same.
+    let values = vec![(date(2022, 1, 1), 1000.0), (date(2023, 1, 1), 1100.0)];
+    let returns = cryptfolio::recording::ReturnRecord::new(&values, &Vec::new());
+    assert!((returns.get_time_weighted_return() - 0.1).abs() < 1e-9);
+    assert!((returns.get_money_weighted_return().unwrap() - 0.1).abs() < 1e-6);
+
+    // Nothing ever flowed out, so there is no rate of return to solve for.
+    let values = vec![(date(2022, 1, 1), 0.0), (date(2023, 1, 1), 0.0)];
+    assert!(cryptfolio::recording::ReturnRecord::new(&values, &Vec::new()).get_money_weighted_return().is_none());
+}
+
+/// Syncs a fixed set of entries, so that a database is seeded through the same path as a platform.
+struct FixtureClient {
+    name: &'static str,
+    entries: std::sync::Mutex<Vec<Box<dyn DatabaseEntry + Send>>>,
+}
+
+impl FixtureClient {
+    fn new(name: &'static str, entries: Vec<Box<dyn DatabaseEntry + Send>>) -> FixtureClient {
+        FixtureClient { name: name, entries: std::sync::Mutex::new(entries) }
+    }
+}
+
+#[async_trait::async_trait]
+impl SyncClient for FixtureClient {
+    fn get_name(&self) -> &str {
+        self.name
+    }
+
+    fn get_connection(&self, nickname: &String) -> PlatformConnection {
+        PlatformConnection::new(nickname.to_string(), self.name.to_string(), Vec::new())
+    }
+
+    async fn sync(&self) -> Result<Vec<Box<dyn DatabaseEntry + Send>>, cryptfolio::error::CryptfolioError> {
+        Ok(std::mem::take(&mut *self.entries.lock().unwrap()))
+    }
+}
+
+async fn seed(app: &CryptfolioApp, platform: &'static str, entries: Vec<Box<dyn DatabaseEntry + Send>>) {
+    let handle = app.add_platform("Fixture", FixtureClient::new(platform, entries));
+    app.sync_platform(handle).await.unwrap();
+}
+
+fn order(id: &str, date: &str, pair: &str, unit_price: f64, unit_size: f64, fee: f64, side: &str, platform: &str) -> Box<dyn DatabaseEntry + Send> {
+    Box::new(cryptfolio::database::entry::CoinOrder::new(
+        id.to_string(), date.to_string(), pair.to_string(), unit_price, unit_size, fee, side.to_string(), platform.to_string()
+    ))
+}
+
+#[tokio::test]
+async fn portfolio_as_of() {
+    let app = CryptfolioApp::new(":memory:").unwrap();
+    seed(&app, "Coinbase", vec![
+        order("1", "2021-06-01T00:00:00Z", "BTC-USD", 30000.0, 1.0, 0.0, "buy", "Coinbase"),
+        order("2", "2022-06-01T00:00:00Z", "BTC-USD", 20000.0, 0.5, 0.0, "buy", "Coinbase"),
+        order("3", "2022-03-01T00:00:00Z", "ETH-USD", 3000.0, 2.0, 0.0, "buy", "Coinbase"),
+    ]).await;
+    let year_end = chrono::NaiveDate::from_ymd_opt(2021, 12, 31).unwrap().and_hms_opt(23, 59, 59).unwrap();
+    let mut prices = std::collections::HashMap::<String, f64>::new();
+    prices.insert("BTC".to_string(), 40000.0);
+    prices.insert("ETH".to_string(), 2000.0);
+
+    // Entries after the as-of date are left out of every read.
+    assert_eq!(app.get_coin_record("BTC".to_string(), Some(year_end)).get_shares(), 1.0);
+    assert_eq!(app.get_lots("BTC".to_string(), Some(year_end)).len(), 1);
+    let balances = app.get_balances(None, Some(year_end));
+    assert_eq!(balances.get("BTC"), Some(&1.0));
+    assert!(balances.get("ETH").map_or(true, |x| *x == 0.0));
+    let portfolio = app.get_portfolio(&prices, Some(year_end));
+    let coins: Vec<&str> = portfolio.get_entries().iter().map(|x| x.coin.as_str()).collect();
+    assert_eq!(coins, vec!["BTC"]);
+    assert_eq!(portfolio.get_market_value(), 40000.0);
+
+    // Without one, the full history is read.
+    assert_eq!(app.get_coin_record("BTC".to_string(), None).get_shares(), 1.5);
+    assert_eq!(app.get_lots("BTC".to_string(), None).len(), 2);
+    assert_eq!(app.get_balances(None, None).get("ETH"), Some(&2.0));
+    assert_eq!(app.get_portfolio(&prices, None).get_entries().len(), 2);
+}
+
+#[tokio::test]
+async fn reconciliation() {
+    let app = CryptfolioApp::new(":memory:").unwrap();
+    let account = |id: &str, coin: &str| -> Box<dyn DatabaseEntry + Send> {
+        Box::new(CoinAccount::new(id.to_string(), coin.to_string(), "Kraken".to_string()))
+    };
+    let balance = |id: &str, coin: &str, balance: f64| -> Box<dyn DatabaseEntry + Send> {
+        Box::new(AccountBalance::new(id.to_string(), "2023-01-01T00:00:00Z".to_string(), coin.to_string(), "Kraken".to_string(), balance))
+    };
+    seed(&app, "Kraken", vec![
+        account("kr-usd", "USD"),
+        account("kr-btc", "BTC"),
+        account("kr-eth", "ETH"),
+        Box::new(FiatTransfer::new("d1".to_string(), "2022-01-01T00:00:00Z".to_string(), String::new(), "kr-usd".to_string(), 1000.0, "USD".to_string())),
+        order("o1", "2022-01-02T00:00:00Z", "BTC-USD", 25000.0, 0.02, 0.0, "buy", "Kraken"),
+        order("o2", "2022-01-03T00:00:00Z", "ETH-USD", 290.0, 1.0, 10.0, "buy", "Kraken"),
+        balance("kr-usd", "USD", 200.0),
+        balance("kr-btc", "BTC", 0.02),
+        balance("kr-eth", "ETH", 0.9),
+    ]).await;
+
+    let reconciliation = app.get_reconciliation(0.000001);
+    assert_eq!(reconciliation.get_entries().len(), 3);
+    assert!(!reconciliation.is_reconciled());
+    let mismatches = reconciliation.get_mismatches();
+    assert_eq!(mismatches.len(), 1);
+    assert_eq!((mismatches[0].platform.as_str(), mismatches[0].coin.as_str()), ("Kraken", "ETH"));
+    assert!((mismatches[0].ledger_balance - 1.0).abs() < 1e-9);
+    assert!((mismatches[0].difference.abs() - 0.1).abs() < 1e-9);
+
+    // Fiat is compared against the cash ledger: deposited, less both orders and the fee.
+    let usd = reconciliation.get_entries().iter().find(|x| x.coin == "USD").unwrap();
+    assert!((usd.ledger_balance - 200.0).abs() < 1e-9);
+}
+
+#[test]
+fn transfer_matching_rules() {
+    let transfer = |id: &str, hour: u32, coin: &str, unit_size: f64, fee: f64, origin_platform: &str, destination_platform: &str| {
+        cryptfolio::recording::TransferRecord {
+            id: id.to_string(),
+            date: chrono::NaiveDate::from_ymd_opt(2022, 1, 1).unwrap().and_hms_opt(hour, 0, 0).unwrap(),
+            coin: coin.to_string(),
+            unit_size: unit_size,
+            fee: fee,
+            origin: String::new(),
+            destination: String::new(),
+            origin_platform: origin_platform.to_string(),
+            destination_platform: destination_platform.to_string(),
+        }
+    };
+    let transfers = vec![
+        // Coinbase -> Kraken, arriving less the network fee.
+        transfer("cb-out", 1, "BTC", 1.0, 0.0005, "Coinbase", ""),
+        transfer("kr-in", 2, "BTC", 0.9995, f64::NAN, "", "Kraken"),
+        // Arrives with far less than was sent.
+        transfer("cb-eth-out", 3, "ETH", 2.0, 0.001, "Coinbase", ""),
+        transfer("kr-eth-in", 4, "ETH", 1.5, f64::NAN, "", "Kraken"),
+        // Leaves and arrives on the same platform.
+        transfer("kr-sol-out", 5, "SOL", 10.0, 0.0, "Kraken", ""),
+        transfer("kr-sol-in", 6, "SOL", 10.0, f64::NAN, "", "Kraken"),
+        // Already linked.
+        transfer("cb-dot-out", 7, "DOT", 5.0, 0.0, "Coinbase", ""),
+        transfer("kr-dot-in", 8, "DOT", 5.0, f64::NAN, "", "Kraken"),
+    ];
+    let linked = vec!["cb-dot-out".to_string(), "kr-dot-in".to_string()];
+
+    let matcher = cryptfolio::recording::TransferMatcher::new(24, 0.0);
+    let (matches, unmatched) = matcher.match_transfers(&transfers, &linked);
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].outgoing.id, "cb-out");
+    assert_eq!(matches[0].incoming.id, "kr-in");
+    let unmatched: Vec<&str> = unmatched.iter().map(|x| x.id.as_str()).collect();
+    assert_eq!(unmatched, vec!["cb-eth-out", "kr-eth-in", "kr-sol-out", "kr-sol-in"]);
+
+    // Outside of the window, the same pair no longer matches.
+    let late = transfer("kr-in", 2, "BTC", 0.9995, f64::NAN, "", "Kraken");
+    let mut early = transfer("cb-out", 1, "BTC", 1.0, 0.0005, "Coinbase", "");
+    early.date = early.date - chrono::Duration::days(3);
+    assert!(!matcher.is_match(&early, &late));
+}
+
+#[tokio::test]
+async fn reporting_currency() {
+    let app = CryptfolioApp::new(":memory:").unwrap();
+    let deposit = |id: &str, date: &str, amount: f64, currency: &str| -> Box<dyn DatabaseEntry + Send> {
+        Box::new(FiatTransfer::new(id.to_string(), date.to_string(), String::new(), "Coinbase Pro".to_string(), amount, currency.to_string()))
+    };
+    seed(&app, "Coinbase Pro", vec![
+        Box::new(CoinAccount::new("pro-usd".to_string(), "USD".to_string(), "Coinbase Pro".to_string())),
+        Box::new(CoinAccount::new("pro-eur".to_string(), "EUR".to_string(), "Coinbase Pro".to_string())),
+        deposit("d1", "2022-01-01T00:00:00Z", 25000.0, "EUR"),
+        deposit("d2", "2022-01-01T00:00:00Z", 40000.0, "USD"),
+        order("o1", "2022-01-03T00:00:00Z", "BTC-EUR", 20000.0, 1.0, 0.0, "buy", "Coinbase Pro"),
+        order("o2", "2022-01-04T00:00:00Z", "BTC-USD", 30000.0, 1.0, 0.0, "buy", "Coinbase Pro"),
+    ]).await;
+
+    // Without a rate, an amount in EUR cannot be converted.
+    assert!(app.get_coin_record("BTC".to_string(), None).get_average_cost().is_nan());
+
+    let mut rates = cryptfolio::pricing::FxRateTable::new();
+    rates.add_rate("EUR", "USD", chrono::NaiveDate::from_ymd_opt(2022, 1, 3).unwrap(), 1.13);
+    let rates = std::rc::Rc::new(rates);
+    app.set_fx_source(rates.clone());
+    let average = (20000.0 * 1.13 + 30000.0) / 2.0;
+    assert!((app.get_coin_record("BTC".to_string(), None).get_average_cost() - average).abs() < 1e-6);
+
+    // Rates are cached in the database, so the source is only asked once.
+    app.set_fx_source(std::rc::Rc::new(cryptfolio::pricing::FxRateTable::new()));
+    assert!((app.get_coin_record("BTC".to_string(), None).get_average_cost() - average).abs() < 1e-6);
+
+    // The inverse of a rate is used when reporting in EUR.
+    app.set_fx_source(rates);
+    app.set_reporting_currency("eur").unwrap();
+    assert_eq!(app.get_reporting_currency(), "EUR");
+    let average = (20000.0 + 30000.0 / 1.13) / 2.0;
+    assert!((app.get_coin_record("BTC".to_string(), None).get_average_cost() - average).abs() < 1e-6);
+
+    // Deposits to 'Coinbase Pro' land in the account of their own currency.
+    let mut accounts = app.get_cash_ledger(None).get_account_balances(None);
+    accounts.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(accounts, vec![
+        ("pro-eur".to_string(), "Coinbase Pro".to_string(), "EUR".to_string(), 5000.0),
+        ("pro-usd".to_string(), "Coinbase Pro".to_string(), "USD".to_string(), 10000.0),
+    ]);
+}
+
+#[tokio::test]
+async fn diagnostics() {
+    use cryptfolio::recording::DiagnosticType;
+
+    let app = CryptfolioApp::new(":memory:").unwrap();
+    seed(&app, "Kraken", vec![
+        Box::new(CoinAccount::new("kr-sol".to_string(), "SOL".to_string(), "Kraken".to_string())),
+        // Deposited from outside the portfolio, so the sender paid the unknown fee.
+        Box::new(CoinTransfer::new("t1".to_string(), "2022-01-01T00:00:00Z".to_string(), "address".to_string(), "kr-sol".to_string(), "SOL".to_string(), 1.0, f64::NAN)),
+        order("o1", "2022-01-02T00:00:00Z", "SOL-USD", 100.0, 1.0, 0.0, "buy", "Kraken"),
+        order("o1", "2022-01-02T00:00:00Z", "SOL-USD", 100.0, 1.0, 0.0, "buy", "Kraken"),
+        order("o2", "2022-02-01T00:00:00Z", "SOL-USD", 120.0, 2.5, 0.0, "sell", "Kraken"),
+        // Withdrawn without a known fee.
+        Box::new(CoinTransfer::new("t2".to_string(), "2022-03-01T00:00:00Z".to_string(), "kr-sol".to_string(), "address".to_string(), "SOL".to_string(), 1.0, f64::NAN)),
+    ]).await;
+
+    let diagnostics = app.get_diagnostics();
+    let sells = diagnostics.get_diagnostics_of_type(DiagnosticType::SellWithoutBasis);
+    assert_eq!(sells.len(), 1);
+    assert_eq!(sells[0].coin, "SOL");
+    let fees = diagnostics.get_diagnostics_of_type(DiagnosticType::MissingFee);
+    assert_eq!(fees.len(), 1);
+    assert_eq!(fees[0].id, "t2");
+    let duplicates = diagnostics.get_diagnostics_of_type(DiagnosticType::DuplicateId);
+    assert_eq!(duplicates.len(), 1);
+    assert_eq!(duplicates[0].id, "o1");
+
+    // Even with the duplicate buy, more is sold than was bought (transfers only move coins).
+    let negative = diagnostics.get_diagnostics_of_type(DiagnosticType::NegativeBalance);
+    assert_eq!(negative.len(), 1);
+    assert_eq!(negative[0].id, "o2");
+    assert!(diagnostics.get_diagnostics_of_type(DiagnosticType::UnknownTransaction).is_empty());
+}
+
+#[test]
+fn contributions() {
+    use cryptfolio::recording::{ContributionRecord, FiatTransferRecord, Interval};
+
+    let transfer = |id: &str, date: (i32, u32, u32), amount: f64, origin_platform: &str, destination_platform: &str| FiatTransferRecord {
+        id: id.to_string(),
+        date: chrono::NaiveDate::from_ymd_opt(date.0, date.1, date.2).unwrap().and_hms_opt(0, 0, 0).unwrap(),
+        amount: amount,
+        currency: "USD".to_string(),
+        origin_platform: origin_platform.to_string(),
+        destination_platform: destination_platform.to_string(),
+    };
+    let mut contributions = ContributionRecord::new(vec![
+        transfer("1", (2022, 1, 5), 1000.0, "", "Coinbase"),
+        // Moved from Coinbase to Coinbase Pro, so neither put in nor taken out.
+        transfer("2", (2022, 1, 10), 500.0, "Coinbase", "Coinbase Pro"),
+        transfer("3", (2022, 2, 1), 200.0, "", "Coinbase Pro"),
+        transfer("4", (2022, 2, 20), 300.0, "Coinbase Pro", ""),
+        transfer("5", (2023, 3, 1), 100.0, "", "Coinbase"),
+    ]);
+    assert_eq!(contributions.get_external_transfers(None).len(), 4);
+    assert_eq!(contributions.get_platforms(), vec!["Coinbase".to_string(), "Coinbase Pro".to_string()]);
+
+    let monthly: Vec<(String, f64, f64, f64)> = contributions
+        .get_contributions(Interval::Monthly, None)
+        .into_iter()
+        .map(|x| (x.period, x.deposits, x.withdrawals, x.net))
+        .collect();
+    assert_eq!(monthly, vec![
+        ("2022-01".to_string(), 1000.0, 0.0, 1000.0),
+        ("2022-02".to_string(), 200.0, 300.0, -100.0),
+        ("2023-03".to_string(), 100.0, 0.0, 100.0),
+    ]);
+    let yearly: Vec<(String, f64)> = contributions
+        .get_contributions(Interval::Yearly, None)
+        .into_iter()
+        .map(|x| (x.period, x.net))
+        .collect();
+    assert_eq!(yearly, vec![("2022".to_string(), 900.0), ("2023".to_string(), 100.0)]);
+
+    assert_eq!(contributions.get_net_contributions(Some("Coinbase")), 1100.0);
+    assert_eq!(contributions.get_net_contributions(Some("Coinbase Pro")), -100.0);
+    assert_eq!(contributions.get_contributions(Interval::Yearly, Some("Coinbase Pro"))[0].withdrawals, 300.0);
+    assert_eq!(contributions.get_net_contributions(None), 1000.0);
+
+    contributions.add_cash_balance("Coinbase".to_string(), "USD".to_string(), 250.0, 250.0);
+    contributions.add_cash_balance("Kraken".to_string(), "EUR".to_string(), 100.0, 110.0);
+    assert_eq!(contributions.get_total_cash(), 360.0);
+}
+
+#[tokio::test]
+async fn cash_balances() {
+    use cryptfolio::recording::{CashLedger, CashLedgerEntry, CashLedgerEntryType};
+
+    let date = |d: u32, h: u32| chrono::NaiveDate::from_ymd_opt(2022, 1, d).unwrap().and_hms_opt(h, 0, 0).unwrap();
+    let entry = |id: &str, day: u32, platform: &str, r#type: CashLedgerEntryType, amount: f64| CashLedgerEntry {
+        id: id.to_string(),
+        date: date(day, 12),
+        account: format!("{}-usd", platform),
+        platform: platform.to_string(),
+        currency: "USD".to_string(),
+        r#type: r#type,
+        amount: amount,
+    };
+    let ledger = CashLedger::new(vec![
+        entry("4", 4, "Kraken", CashLedgerEntryType::Sell, 150.0),
+        entry("1", 1, "Kraken", CashLedgerEntryType::Deposit, 1000.0),
+        entry("2", 2, "Kraken", CashLedgerEntryType::Buy, -400.0),
+        entry("3", 2, "Kraken", CashLedgerEntryType::Fee, -5.0),
+        entry("5", 3, "Coinbase", CashLedgerEntryType::Deposit, 50.0),
+    ]);
+    assert_eq!(ledger.get_platforms(), vec!["Coinbase".to_string(), "Kraken".to_string()]);
+    assert_eq!(ledger.get_balances(date(2, 23), None).get("USD"), Some(&595.0));
+    assert_eq!(ledger.get_balances(date(4, 23), Some("Kraken")).get("USD"), Some(&745.0));
+
+    let daily = ledger.get_daily_balances(date(1, 0).date(), date(4, 0).date(), Some("Kraken"));
+    let daily: Vec<f64> = daily.iter().map(|x| *x.1.get("USD").unwrap()).collect();
+    assert_eq!(daily, vec![1000.0, 595.0, 595.0, 745.0]);
+
+    // A move between two known accounts is synced from both platforms, but only applied once.
+    let app = CryptfolioApp::new(":memory:").unwrap();
+    let transfer = |id: &str, day: u32, hour: u32, origin: &str, destination: &str, amount: f64| -> Box<dyn DatabaseEntry + Send> {
+        Box::new(FiatTransfer::new(id.to_string(), date(day, hour).format("%Y-%m-%dT%H:%M:%SZ").to_string(), origin.to_string(), destination.to_string(), amount, "USD".to_string()))
+    };
+    seed(&app, "Coinbase", vec![
+        Box::new(CoinAccount::new("cb-usd".to_string(), "USD".to_string(), "Coinbase".to_string())),
+        Box::new(CoinAccount::new("pro-usd".to_string(), "USD".to_string(), "Coinbase Pro".to_string())),
+        transfer("deposit", 1, 0, "", "cb-usd", 1000.0),
+        transfer("cb-withdrawal", 2, 0, "cb-usd", "pro-usd", 400.0),
+        transfer("pro-deposit", 3, 12, "cb-usd", "pro-usd", 400.0),
+        // The same amount again, but a week later.
+        transfer("cb-withdrawal-2", 10, 0, "cb-usd", "pro-usd", 400.0),
+    ]).await;
+    let ledger = app.get_cash_ledger(None);
+    assert_eq!(ledger.get_balances(date(31, 0), Some("Coinbase")).get("USD"), Some(&200.0));
+    assert_eq!(ledger.get_balances(date(31, 0), Some("Coinbase Pro")).get("USD"), Some(&800.0));
+}
+
+#[tokio::test]
+async fn cash_equivalents() {
+    use cryptfolio::pricing::{CashEquivalent, CashEquivalentPolicy};
+
+    let app = CryptfolioApp::new(":memory:").unwrap();
+    app.set_cash_equivalent(CashEquivalent::new("USDC", CashEquivalentPolicy::Cash, "USD")).unwrap();
+    app.set_cash_equivalent(CashEquivalent::new("USDT", CashEquivalentPolicy::Property, "USD")).unwrap();
+    assert_eq!(app.get_cash_equivalents().len(), 2);
+
+    let account = |id: &str, coin: &str| -> Box<dyn DatabaseEntry + Send> {
+        Box::new(CoinAccount::new(id.to_string(), coin.to_string(), "Coinbase".to_string()))
+    };
+    seed(&app, "Coinbase", vec![
+        account("cb-usd", "USD"),
+        account("cb-usdc", "USDC"),
+        account("cb-usdt", "USDT"),
+        Box::new(FiatTransfer::new("d1".to_string(), "2022-01-01T00:00:00Z".to_string(), String::new(), "cb-usd".to_string(), 1000.0, "USD".to_string())),
+        order("o1", "2022-01-02T00:00:00Z", "USDC-USD", 1.0, 300.0, 0.0, "buy", "Coinbase"),
+        order("o2", "2022-01-03T00:00:00Z", "USDT-USD", 1.0, 200.0, 0.0, "buy", "Coinbase"),
+    ]).await;
+
+    // USDC is cash alongside the USD it was converted from.
+    let mut prices = std::collections::HashMap::<String, f64>::new();
+    prices.insert("USDT".to_string(), 1.0);
+    let portfolio = app.get_portfolio(&prices, None);
+    let mut cash: Vec<(String, f64)> = portfolio.get_cash_balances().iter().map(|x| (x.currency.to_string(), x.value)).collect();
+    cash.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(cash, vec![("USD".to_string(), 500.0), ("USDC".to_string(), 300.0)]);
+    assert_eq!(portfolio.get_cash_value(), 800.0);
+
+    // USDT is a coin with a cost basis of its own.
+    let coins: Vec<&str> = portfolio.get_entries().iter().map(|x| x.coin.as_str()).collect();
+    assert_eq!(coins, vec!["USDT"]);
+    let usdt = app.get_coin_record("USDT".to_string(), None);
+    assert_eq!(usdt.get_shares(), 200.0);
+    assert_eq!(usdt.get_average_cost(), 1.0);
+    assert!(app.get_balances(None, None).get("USDC").is_none());
+}
+
+#[test]
+fn risk_calculations() {
+    let date = |d: u32| chrono::NaiveDate::from_ymd_opt(2022, 1, d).unwrap();
+    let values = vec![(date(1), 100.0), (date(2), 120.0), (date(3), 90.0), (date(4), 108.0)];
+    let mut risk = cryptfolio::recording::RiskRecord::new(&values, &Vec::new(), 0.04);
+
+    // From a peak of 120 down to 90.
+    assert!((risk.get_max_drawdown() - 0.25).abs() < 1e-9);
+    assert!(risk.get_volatility() > 0.0);
+    assert!(risk.get_sharpe_ratio().is_finite());
+    assert!(risk.get_sortino_ratio().is_finite());
+
+    // A deposit is not mistaken for a gain.
+    let flows = vec![(date(2), 1000.0)];
+    let values = vec![(date(1), 100.0), (date(2), 1100.0)];
+    let flat = cryptfolio::recording::RiskRecord::new(&values, &flows, 0.0);
+    assert_eq!(flat.get_daily_returns()[0].1, 0.0);
+    assert!(flat.get_sharpe_ratio().is_nan());
+
+    risk.add_coin("BTC".to_string(), &vec![(date(1), 100.0), (date(2), 110.0), (date(3), 99.0), (date(4), 108.9)]);
+    risk.add_coin("ETH".to_string(), &vec![(date(1), 10.0), (date(2), 12.0), (date(3), 9.0), (date(4), 10.8)]);
+    risk.add_coin("SOL".to_string(), &vec![(date(1), 10.0), (date(2), 9.0), (date(3), 9.9), (date(4), 8.91)]);
+    assert!((risk.get_correlation("BTC", "ETH").unwrap() - 1.0).abs() < 1e-9);
+    assert!((risk.get_correlation("BTC", "SOL").unwrap() + 1.0).abs() < 1e-9);
+    assert!(risk.get_correlation("BTC", "DOGE").is_none());
+}
+
+#[test]
+fn benchmark_comparison() {
+    let date = |d: u32| chrono::NaiveDate::from_ymd_opt(2022, 1, d).unwrap();
+    let mut index = cryptfolio::pricing::PriceHistory::new();
+    index.add_price("INDEX", date(2), 100.0);
+    index.add_price("INDEX", date(3), 110.0);
+
+    // The first day has no index price yet, and the deposit on the last day buys into the index too.
+    let values = vec![(date(1), 900.0), (date(2), 1000.0), (date(3), 1650.0)];
+    let flows = vec![(date(3), 500.0)];
+    let benchmark = cryptfolio::recording::BenchmarkRecord::new("INDEX", &values, &flows, &index);
+    assert_eq!(benchmark.get_points().len(), 2);
+    assert!((benchmark.get_portfolio_return() - 0.15).abs() < 1e-9);
+    assert!((benchmark.get_benchmark_return() - 0.10).abs() < 1e-9);
+    assert!((benchmark.get_relative_return() - (1.15 / 1.10 - 1.0)).abs() < 1e-9);
+    assert!((benchmark.get_excess_value() - 50.0).abs() < 1e-9);
+}
+
+#[test]
+fn cost_basis_lot_selection() {
+    use cryptfolio::recording::{CoinRecord, CostBasisMethod, TransactionRecord};
+    use std::str::FromStr;
+
+    let lot = |date: &str, price: f64| TransactionRecord { date: date.to_string(), price: price, shares: 1.0, fee: 0.0 };
+    let mut lots = std::collections::VecDeque::<TransactionRecord>::new();
+    lots.push_back(lot("2020-01-01T00:00:00Z", 100.0));
+    lots.push_back(lot("2021-06-01T00:00:00Z", 300.0));
+    lots.push_back(lot("2021-12-01T00:00:00Z", 200.0));
+    let sell = lot("2022-01-01T00:00:00Z", 250.0);
+
+    assert_eq!(CostBasisMethod::Fifo.select_lot(&lots, &sell), 0);
+    assert_eq!(CostBasisMethod::Lifo.select_lot(&lots, &sell), 2);
+    assert_eq!(CostBasisMethod::Hifo.select_lot(&lots, &sell), 1);
+
+    // Losses first, then long-term gains before short-term gains.
+    assert_eq!(CostBasisMethod::MinimizeTax.select_lot(&lots, &sell), 1);
+    lots.remove(1);
+    assert_eq!(CostBasisMethod::MinimizeTax.select_lot(&lots, &sell), 0);
+
+    assert_eq!(CostBasisMethod::from_str("minimize_tax").unwrap(), CostBasisMethod::MinimizeTax);
+    assert!(CostBasisMethod::from_str("average").is_err());
+
+    let mut record = CoinRecord::new();
+    record.set_cost_basis_method(CostBasisMethod::Hifo);
+    record.add_buy("2020-01-01T00:00:00Z".to_string(), 100.0, 1.0, 0.0);
+    record.add_buy("2021-06-01T00:00:00Z".to_string(), 300.0, 1.0, 0.0);
+    record.add_buy("2021-12-01T00:00:00Z".to_string(), 200.0, 1.0, 0.0);
+    record.add_sell("2022-01-01T00:00:00Z".to_string(), 250.0, 1.5, 0.0);
+    record.update();
+    assert!((record.get_shares() - 1.5).abs() < 1e-9);
+    let taxes: Vec<(f64, f64)> = record.get_tax_records().iter().map(|x| (x.buy_price, x.unit_size)).collect();
+    assert_eq!(taxes, vec![(300.0, 1.0), (200.0, 0.5)]);
+    assert!((record.get_gross_profit() - (-50.0 + 25.0)).abs() < 1e-9);
+    assert!(record.get_unmatched_sells().is_empty());
+
+    // Selling more than was ever bought leaves the rest without a cost basis.
+    let mut record = CoinRecord::new();
+    record.add_buy("2021-01-01T00:00:00Z".to_string(), 100.0, 1.0, 0.0);
+    record.add_sell("2022-01-01T00:00:00Z".to_string(), 150.0, 1.25, 0.0);
+    record.update();
+    assert_eq!(record.get_unmatched_sells().len(), 1);
+    assert!((record.get_unmatched_sells()[0].shares - 0.25).abs() < 1e-9);
+}
+
+#[test]
+fn rebalance_plan() {
+    use cryptfolio::recording::{CoinRecord, PortfolioRecord, RebalanceRecord};
+
+    let mut btc = CoinRecord::new();
+    btc.add_buy("2021-01-01T00:00:00Z".to_string(), 10000.0, 1.0, 0.0);
+    btc.update();
+    let mut eth = CoinRecord::new();
+    eth.add_buy("2022-06-01T00:00:00Z".to_string(), 1000.0, 10.0, 0.0);
+    eth.update();
+
+    let mut portfolio = PortfolioRecord::new();
+    portfolio.add_coin("BTC".to_string(), btc, Some(30000.0));
+    portfolio.add_coin("ETH".to_string(), eth, Some(1000.0));
+    portfolio.update();
+
+    let targets = vec![("BTC".to_string(), 50.0), ("Layer 1".to_string(), 50.0)];
+    let mut categories = std::collections::HashMap::<String, String>::new();
+    categories.insert("ETH".to_string(), "Layer 1".to_string());
+    categories.insert("SOL".to_string(), "Layer 1".to_string());
+    let plan = RebalanceRecord::new(&portfolio, &targets, &categories, "2023-01-01T00:00:00Z".to_string());
+
+    assert_eq!(plan.get_total_value(), 40000.0);
+    assert_eq!(plan.get_targets()[0].current_weight, 75.0);
+    assert_eq!(plan.get_targets()[1].coins, vec!["ETH".to_string(), "SOL".to_string()]);
+
+    // BTC is sold down to half, and the proceeds bought into the held coins of the category.
+    let actions = plan.get_actions();
+    assert_eq!(actions.len(), 2);
+    assert_eq!((actions[0].side.as_str(), actions[0].coin.as_str()), ("sell", "BTC"));
+    assert!((actions[0].value - 10000.0).abs() < 1e-6);
+    assert!((actions[0].long_term_gain - 10000.0 * 2.0 / 3.0).abs() < 1e-6);
+    assert_eq!(actions[0].short_term_gain, 0.0);
+    assert_eq!((actions[1].side.as_str(), actions[1].coin.as_str()), ("buy", "ETH"));
+    assert!((actions[1].shares - 10.0).abs() < 1e-9);
+}
+
+#[test]
+fn allocation_settings() {
+    let app = CryptfolioApp::new(":memory:").unwrap();
+    assert_eq!(app.get_cost_basis_method(), cryptfolio::recording::CostBasisMethod::Fifo);
+
+    app.set_cost_basis_method(cryptfolio::recording::CostBasisMethod::Hifo).unwrap();
+    app.set_allocation_target("BTC", 60.0).unwrap();
+    app.set_allocation_target("BTC", 50.0).unwrap();
+    assert_eq!(app.get_cost_basis_method(), cryptfolio::recording::CostBasisMethod::Hifo);
+    assert_eq!(app.get_allocation_targets(), vec![("BTC".to_string(), 50.0)]);
+
+    // With nothing held, there is nothing to rebalance.
+    let plan = app.get_rebalance_plan(&std::collections::HashMap::<String, f64>::new());
+    assert_eq!(plan.get_total_value(), 0.0);
+    assert!(plan.get_actions().is_empty());
+}
+
+#[test]
+fn recurring_buy_detection() {
+    use cryptfolio::recording::{Cadence, LedgerEntry, LedgerEntryType, RecurringBuyRecord};
+
+    let date = |m: u32, d: u32| chrono::NaiveDate::from_ymd_opt(2022, m, d).unwrap();
+    let buy = |id: &str, day: u32, price: f64, cost: f64| LedgerEntry {
+        id: id.to_string(),
+        date: date(1, day).and_hms_opt(12, 0, 0).unwrap(),
+        coin: "BTC".to_string(),
+        r#type: LedgerEntryType::Buy,
+        unit_price: price,
+        unit_size: cost / price,
+        fee: 0.0,
+        origin: String::new(),
+        destination: "Coinbase".to_string(),
+    };
+
+    // A weekly $100 buy, with a one-off buy in between.
+    let entries = vec![
+        buy("1", 1, 20000.0, 100.0),
+        buy("2", 8, 25000.0, 100.0),
+        buy("one-off", 10, 24000.0, 1000.0),
+        buy("3", 15, 20000.0, 99.0),
+        buy("4", 22, 16000.0, 101.0),
+    ];
+    let mut prices = cryptfolio::pricing::PriceHistory::new();
+    prices.add_price("BTC", date(1, 1), 20000.0);
+
+    let recurring = RecurringBuyRecord::new(&entries, &prices);
+    assert_eq!(recurring.get_schedules().len(), 1);
+    let schedule = &recurring.get_schedules()[0];
+    assert_eq!(schedule.cadence, Cadence::Weekly);
+    assert_eq!(schedule.platform, "Coinbase");
+    assert_eq!(schedule.buys.len(), 4);
+    assert!((schedule.amount - 100.0).abs() < 1e-9);
+    assert!((recurring.get_total_deployed() - 400.0).abs() < 1e-9);
+    assert!(schedule.is_active(date(1, 29)));
+    assert!(!schedule.is_active(date(3, 1)));
+    assert_eq!(schedule.get_projected_buys(2), vec![date(1, 29), date(2, 5)]);
+
+    assert_eq!(Cadence::from_interval(30), Some(Cadence::Monthly));
+    assert_eq!(Cadence::from_interval(10), None);
+}
+
+// #[tokio::test]
+// async fn solana_sync() {
+//     let app = CryptfolioApp::new("local/test.db").unwrap();
+//     let handle = app.add_platform("Main Wallet", cryptfolio::platform::blockchain::Solana::new("<wallet address>".to_string()).unwrap());
+//     app.sync_platform(handle).await.unwrap();
+//
+//     let balances = app.get_balances(Some("Solana"), None);
+//     println!("SOL: {}", balances.get("SOL").unwrap_or(&0.0));
+// }
+
+// #[tokio::test]
+// async fn solana_tokens() {
+//     let app = CryptfolioApp::new("local/test.db").unwrap();
+//     let mut solana = cryptfolio::platform::blockchain::Solana::new("<wallet address>".to_string()).unwrap();
+//     solana.add_token("HZ1JovNiVvGrGNiiYvEozEVgZ58xaU3RKwX8eACQBCt3", "PYTH");
+//     let handle = app.add_platform("Token Wallet", solana);
+//     app.sync_platform(handle).await.unwrap();
+//
+//     for (coin, balance) in app.get_balances(Some("Solana"), None) {
+//         println!("{}: {}", coin, balance);
+//     }
+// }
+
+// #[tokio::test]
+// async fn solana_staking_rewards() {
+//     let app = CryptfolioApp::new("local/test.db").unwrap();
+//     let handle = app.add_platform("Staking Wallet", cryptfolio::platform::blockchain::Solana::new("<wallet address>".to_string()).unwrap());
+//     app.sync_platform(handle).await.unwrap();
+//
+//     // Rewards are synced without a price, and priced afterwards from a price history.
+//     let prices = cryptfolio::pricing::PriceHistory::from_csv("SOL", "local/sol_prices.csv").unwrap();
+//     println!("Priced {} rewards", app.fill_reward_prices(&prices).unwrap());
+//     for diagnostic in app.get_diagnostics().get_diagnostics_of_type(cryptfolio::recording::DiagnosticType::MissingPrice) {
+//         println!("{} | {} | {}", diagnostic.date, diagnostic.coin, diagnostic.message);
+//     }
+// }
+
+// #[tokio::test]
+// async fn solana_swaps() {
+//     let app = CryptfolioApp::new("local/test.db").unwrap();
+//     let handle = app.add_platform("Trading Wallet", cryptfolio::platform::blockchain::Solana::new("<wallet address>".to_string()).unwrap());
+//     app.sync_platform(handle).await.unwrap();
+//
+//     // Swaps are valued through the price of their quote coin, which may yet be missing.
+//     for diagnostic in app.get_diagnostics().get_diagnostics_of_type(cryptfolio::recording::DiagnosticType::MissingPrice) {
+//         println!("{} | {} | {}", diagnostic.date, diagnostic.coin, diagnostic.suggestion);
+//     }
+//     println!("JUP: {:?}", app.get_lots("JUP".to_string(), None).len());
+// }
+
+/// Answers Solana JSON-RPC requests from canned results, one connection at a time.
+fn mock_solana_rpc(results: std::collections::HashMap<&'static str, serde_json::Value>) -> String {
+    mock_json_rpc(move |method, _params| {
+        results.get(method).cloned().unwrap_or_else(|| panic!("Unexpected RPC method '{}'", method))
+    })
+}
+
+/// Answers JSON-RPC requests with the result of a method and its params, one connection at a time.
+fn mock_json_rpc(respond: impl Fn(&str, &serde_json::Value) -> serde_json::Value + Send + 'static) -> String {
+    mock_http(move |_path, body| {
+        let request: serde_json::Value = serde_json::from_slice(body).unwrap();
+        let method = request["method"].as_str().unwrap();
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": request["id"],
+            "result": respond(method, &request["params"]),
+        })
+    })
+}
+
+/// Answers HTTP requests with the JSON for their path and body, one connection at a time.
+fn mock_http(respond: impl Fn(&str, &[u8]) -> serde_json::Value + Send + 'static) -> String {
+    use std::io::{BufRead, BufReader, Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            loop {
+                // A request line, headers, then a body of Content-Length bytes.
+                let mut request_line = String::new();
+                if reader.read_line(&mut request_line).unwrap_or(0) == 0 {
+                    break;
+                }
+                let mut length = 0;
+                let mut line = String::new();
+                loop {
+                    line.clear();
+                    if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                        break;
+                    }
+                    if line == "\r\n" {
+                        break;
+                    }
+                    if let Some(value) = line.to_lowercase().strip_prefix("content-length:") {
+                        length = value.trim().parse::<usize>().unwrap();
+                    }
+                }
+                if line.is_empty() {
+                    break;
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+                let path = request_line.split(' ').nth(1).unwrap_or("/");
+                let response = respond(path, &body).to_string();
+                write!(stream, "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}", response.len(), response).unwrap();
+            }
+        }
+    });
+    url
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn solana_versioned_transaction() {
+    let wallet = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+    let recipient = "4Nd1mBQtrMJVYVfKf2PJy9NZUZdTAsp7D4xWLs4gDB4T";
+    let lookup_table = "2vAyBjaqNk1ugdeMt3BvsbBThbVGxpfrffs6H6h8aYTE";
+    let signature = "5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnbJLgp8uirBgmQpjKhoR4tjF3ZpRzrFmBV6UjKdiSZkQUW";
+
+    let mut results = std::collections::HashMap::new();
+    results.insert("getVersion", serde_json::json!({ "solana-core": "1.18.26", "feature-set": 0 }));
+    results.insert("getTokenAccountsByOwner", serde_json::json!({ "context": { "slot": 100 }, "value": [] }));
+    results.insert("getProgramAccounts", serde_json::json!([]));
+    results.insert("getEpochInfo", serde_json::json!({
+        "absoluteSlot": 100, "blockHeight": 100, "epoch": 1, "slotIndex": 100, "slotsInEpoch": 432000, "transactionCount": null
+    }));
+    results.insert("getSignaturesForAddress", serde_json::json!([{
+        "signature": signature, "slot": 90, "err": null, "memo": null, "blockTime": 1672531200, "confirmationStatus": "confirmed"
+    }]));
+
+    // A v0 transfer whose recipient is only loaded through an address lookup table.
+    results.insert("getTransaction", serde_json::json!({
+        "slot": 90,
+        "blockTime": 1672531200,
+        "version": 0,
+        "transaction": {
+            "signatures": [signature],
+            "message": {
+                "header": { "numRequiredSignatures": 1, "numReadonlySignedAccounts": 0, "numReadonlyUnsignedAccounts": 1 },
+                "accountKeys": [wallet, "11111111111111111111111111111111"],
+                "recentBlockhash": "EkSnNWid2cvwEVnVx9aBqawnmiCNiDgp3gUdkDPTKN1N",
+                "instructions": [{ "programIdIndex": 1, "accounts": [0, 2], "data": "3Bxs4Bc3VYuGVB19", "stackHeight": null }],
+                "addressTableLookups": [{ "accountKey": lookup_table, "writableIndexes": [0], "readonlyIndexes": [] }]
+            }
+        },
+        "meta": {
+            "err": null,
+            "status": { "Ok": null },
+            "fee": 5000,
+            "preBalances": [3000005000u64, 1, 0],
+            "postBalances": [2000000000u64, 1, 1000000000u64],
+            "innerInstructions": [],
+            "logMessages": [],
+            "preTokenBalances": [],
+            "postTokenBalances": [],
+            "rewards": [],
+            "loadedAddresses": { "writable": [recipient], "readonly": [] }
+        }
+    }));
+
+    let app = CryptfolioApp::new(":memory:").unwrap();
+    let solana = cryptfolio::platform::blockchain::Solana::with_config(
+        wallet.to_string(),
+        mock_solana_rpc(results),
+        "confirmed",
+        100
+    ).unwrap();
+    let handle = app.add_platform("Local Wallet", solana);
+    app.sync_platform(handle).await.unwrap();
+
+    let transfers = app.get_transfer_review_queue();
+    assert_eq!(transfers.len(), 1);
+    assert_eq!(transfers[0].origin, wallet);
+    assert_eq!(transfers[0].destination, recipient);
+    assert!((transfers[0].unit_size - 1.0).abs() < 1e-9);
+    assert!((transfers[0].fee - 0.000005).abs() < 1e-12);
+
+    // The next sync stops at the newest signature synced.
+    let (nickname, handle) = app.get_connections().into_iter().next().unwrap();
+    assert_eq!(handle.get_connection(&nickname).get_data("Until Signature"), Some(signature));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn solana_rent_refund() {
+    let wallet = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+    let recipient = "4Nd1mBQtrMJVYVfKf2PJy9NZUZdTAsp7D4xWLs4gDB4T";
+    let token_account = "2vAyBjaqNk1ugdeMt3BvsbBThbVGxpfrffs6H6h8aYTE";
+    let recipient_token_account = "HZ1JovNiVvGrGNiiYvEozEVgZ58xaU3RKwX8eACQBCt3";
+    let bonk = "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263";
+    let signature = "5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnbJLgp8uirBgmQpjKhoR4tjF3ZpRzrFmBV6UjKdiSZkQUW";
+    let token_balance = |index: u8, owner: &str, amount: &str| serde_json::json!({
+        "accountIndex": index, "mint": bonk, "owner": owner, "programId": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+        "uiTokenAmount": { "amount": amount, "decimals": 5, "uiAmount": null, "uiAmountString": amount }
+    });
+
+    let mut results = std::collections::HashMap::new();
+    results.insert("getVersion", serde_json::json!({ "solana-core": "1.18.26", "feature-set": 0 }));
+    results.insert("getTokenAccountsByOwner", serde_json::json!({ "context": { "slot": 100 }, "value": [] }));
+    results.insert("getProgramAccounts", serde_json::json!([]));
+    results.insert("getEpochInfo", serde_json::json!({
+        "absoluteSlot": 100, "blockHeight": 100, "epoch": 1, "slotIndex": 100, "slotsInEpoch": 432000, "transactionCount": null
+    }));
+    results.insert("getSignaturesForAddress", serde_json::json!([{
+        "signature": signature, "slot": 90, "err": null, "memo": null, "blockTime": 1672531200, "confirmationStatus": "confirmed"
+    }]));
+
+    // The whole BONK balance is sent away, and closing the emptied account refunds its rent.
+    results.insert("getTransaction", serde_json::json!({
+        "slot": 90,
+        "blockTime": 1672531200,
+        "version": "legacy",
+        "transaction": {
+            "signatures": [signature],
+            "message": {
+                "header": { "numRequiredSignatures": 1, "numReadonlySignedAccounts": 0, "numReadonlyUnsignedAccounts": 1 },
+                "accountKeys": [wallet, token_account, recipient_token_account, "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"],
+                "recentBlockhash": "EkSnNWid2cvwEVnVx9aBqawnmiCNiDgp3gUdkDPTKN1N",
+                "instructions": [
+                    { "programIdIndex": 3, "accounts": [1, 2, 0], "data": "3Bxs4Bc3VYuGVB19", "stackHeight": null },
+                    { "programIdIndex": 3, "accounts": [1, 0, 0], "data": "A", "stackHeight": null }
+                ]
+            }
+        },
+        "meta": {
+            "err": null,
+            "status": { "Ok": null },
+            "fee": 5000,
+            "preBalances": [1000000000u64, 2039280, 2039280, 1],
+            "postBalances": [1002034280u64, 0, 2039280, 1],
+            "innerInstructions": [],
+            "logMessages": [],
+            "preTokenBalances": [token_balance(1, wallet, "1000000"), token_balance(2, recipient, "0")],
+            "postTokenBalances": [token_balance(2, recipient, "1000000")],
+            "rewards": []
+        }
+    }));
+
+    let app = CryptfolioApp::new(":memory:").unwrap();
+    let solana = cryptfolio::platform::blockchain::Solana::with_config(
+        wallet.to_string(),
+        mock_solana_rpc(results),
+        "confirmed",
+        100
+    ).unwrap();
+    let handle = app.add_platform("Local Wallet", solana);
+    app.sync_platform(handle).await.unwrap();
+
+    // Not a sale of BONK for the refunded rent, but a transfer of BONK and a refund of SOL.
+    let bonk = app.get_coin_record("BONK".to_string(), None);
+    assert!(bonk.get_tax_records().is_empty());
+    assert!(bonk.get_unmatched_sells().is_empty());
+    let transfers = app.get_transfer_review_queue();
+    let sent = transfers.iter().find(|x| x.coin == "BONK").unwrap();
+    assert_eq!((sent.origin.as_str(), sent.destination.as_str()), (token_account, recipient_token_account));
+    assert!((sent.unit_size - 10.0).abs() < 1e-9);
+    let refund = transfers.iter().find(|x| x.coin == "SOL" && x.destination == wallet).unwrap();
+    assert!((refund.unit_size - 0.00203928).abs() < 1e-12);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn solana_staking_rewards_resume() {
+    use solana_program::stake::stake_flags::StakeFlags;
+    use solana_program::stake::state::{Delegation, Meta, Stake, StakeStateV2};
+
+    let wallet = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+    let stake_account = "4Nd1mBQtrMJVYVfKf2PJy9NZUZdTAsp7D4xWLs4gDB4T";
+    let stake = StakeStateV2::Stake(
+        Meta::default(),
+        Stake { delegation: Delegation { activation_epoch: 1, ..Delegation::default() }, ..Stake::default() },
+        StakeFlags::empty()
+    );
+
+    let mut results = std::collections::HashMap::new();
+    results.insert("getVersion", serde_json::json!({ "solana-core": "1.18.26", "feature-set": 0 }));
+    results.insert("getTokenAccountsByOwner", serde_json::json!({ "context": { "slot": 100 }, "value": [] }));
+    results.insert("getSignaturesForAddress", serde_json::json!([]));
+    results.insert("getProgramAccounts", serde_json::json!([{
+        "pubkey": stake_account,
+        "account": {
+            "lamports": 1000000000u64,
+            "data": [base64::encode(bincode::serialize(&stake).unwrap()), "base64"],
+            "owner": "Stake11111111111111111111111111111111111111",
+            "executable": false,
+            "rentEpoch": 0
+        }
+    }]));
+    results.insert("getEpochInfo", serde_json::json!({
+        "absoluteSlot": 1300, "blockHeight": 1300, "epoch": 3, "slotIndex": 4, "slotsInEpoch": 432, "transactionCount": null
+    }));
+    results.insert("getInflationReward", serde_json::json!([{
+        "epoch": 1, "effectiveSlot": 433, "amount": 1000000, "postBalance": 1001000000u64, "commission": 7
+    }]));
+    results.insert("getBlockTime", serde_json::json!(1672531200));
+    let rpc_url = mock_solana_rpc(results);
+
+    let path = std::env::temp_dir().join(format!("cryptfolio-solana-rewards-{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let path = path.to_str().unwrap().to_string();
+    let prices = std::collections::HashMap::from([("SOL".to_string(), 100.0)]);
+
+    // Epochs 1 and 2 have passed, so both have a reward.
+    {
+        let app = CryptfolioApp::new(path.as_str()).unwrap();
+        let solana = cryptfolio::platform::blockchain::Solana::with_config(wallet.to_string(), rpc_url, "confirmed", 100).unwrap();
+        let handle = app.add_platform("Staking Wallet", solana);
+        app.sync_platform(handle).await.unwrap();
+        assert_eq!(app.fill_reward_prices(&prices).unwrap(), 2);
+    }
+
+    // The reward epoch and stake account are saved with the connection, so syncing again in
+    // the same epoch fetches no rewards twice.
+    let app = CryptfolioApp::new(path.as_str()).unwrap();
+    let (_nickname, handle) = app.get_connections().into_iter().next().unwrap();
+    app.sync_platform(handle).await.unwrap();
+    assert_eq!(app.fill_reward_prices(&prices).unwrap(), 0);
+    assert!((app.get_balances(None, None)["SOL"] - 0.002).abs() < 1e-9);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn evm_node_sync() {
+    let address = "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266";
+    let recipient = "0x70997970c51812dc3a010c7d01b50e0d17dc79c8";
+    let sender = "0x3c44cdddb6a900fa2b585dd299e03d12fa4293bc";
+    let usdc = "0x833589fcd6edb6e08f4c7c32d4f71b54bda02913";
+    let hash = "0x88df016429689c079f3b2f6ad39fa052532c56795b733da78a91ebe6a713944b";
+    let token_hash = "0xb2f3a1d7c0e1f5a6b7c8d9e0f1a2b3c4d5e6f7a8b9c0d1e2f3a4b5c6d7e8f9a0";
+    let topic = |address: &str| format!("0x{:0>64}", &address[2..]);
+
+    let rpc_url = mock_json_rpc(move |method, params| {
+        match method {
+            "eth_blockNumber" => { serde_json::json!("0x1") },
+            "eth_getBlockByNumber" => {
+                // 1 ETH sent by the address.
+                serde_json::json!({
+                    "number": "0x1",
+                    "timestamp": "0x63b0cd00",
+                    "transactions": [{ "hash": hash, "from": address, "to": recipient, "value": "0xde0b6b3a7640000" }]
+                })
+            },
+            "eth_getTransactionReceipt" => {
+                // 21000 gas at 1 gwei, plus an L1 data fee of 0.000001 ETH.
+                serde_json::json!({
+                    "transactionHash": hash,
+                    "status": "0x1",
+                    "gasUsed": "0x5208",
+                    "effectiveGasPrice": "0x3b9aca00",
+                    "l1Fee": "0xe8d4a51000",
+                    "contractAddress": null
+                })
+            },
+            "eth_getLogs" => {
+                // 250 USDC received by the address, found through the recipient topic only.
+                if params[0]["topics"][1].is_null() {
+                    serde_json::json!([{
+                        "address": usdc,
+                        "topics": [
+                            "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef",
+                            topic(sender),
+                            topic(address)
+                        ],
+                        "data": "0x000000000000000000000000000000000000000000000000000000000ee6b280",
+                        "blockNumber": "0x1",
+                        "transactionHash": token_hash,
+                        "logIndex": "0x0"
+                    }])
+                } else {
+                    serde_json::json!([])
+                }
+            },
+            "eth_call" => {
+                match params[0]["data"].as_str().unwrap() {
+                    // decimals()
+                    "0x313ce567" => { serde_json::json!("0x0000000000000000000000000000000000000000000000000000000000000006") },
+                    // symbol()
+                    _ => {
+                        serde_json::json!(concat!(
+                            "0x0000000000000000000000000000000000000000000000000000000000000020",
+                            "0000000000000000000000000000000000000000000000000000000000000004",
+                            "5553444300000000000000000000000000000000000000000000000000000000"
+                        ))
+                    }
+                }
+            },
+            _ => { panic!("Unexpected RPC method '{}'", method) }
+        }
+    });
+
+    let app = CryptfolioApp::new(":memory:").unwrap();
+    let mut base = cryptfolio::platform::blockchain::Evm::new(address, 8453, rpc_url.as_str()).unwrap();
+    base.set_start_block(1);
+    let handle = app.add_platform("Base Wallet", base);
+    app.sync_platform(handle).await.unwrap();
+
+    let transfers = app.get_transfer_review_queue();
+    assert_eq!(transfers.len(), 2);
+    let eth = transfers.iter().find(|x| x.coin == "ETH").unwrap();
+    assert_eq!(eth.origin, format!("8453:{}", address));
+    assert_eq!(eth.destination, format!("8453:{}", recipient));
+    assert!((eth.unit_size - 1.0).abs() < 1e-12);
+    assert!((eth.fee - 0.000022).abs() < 1e-12);
+
+    let token = transfers.iter().find(|x| x.coin == "USDC").unwrap();
+    assert_eq!(token.origin, format!("8453:{}:{}", sender, usdc));
+    assert_eq!(token.destination, format!("8453:{}:{}", address, usdc));
+    assert!((token.unit_size - 250.0).abs() < 1e-9);
+    assert_eq!(token.fee, 0.0);
+
+    let balances = app.get_balances(Some("Base"), None);
+    assert!((balances["ETH"] + 1.000022).abs() < 1e-9);
+    assert!((balances["USDC"] - 250.0).abs() < 1e-9);
+}
+
+// #[tokio::test]
+// async fn evm_indexer_sync() {
+//     let app = CryptfolioApp::new("local/test.db").unwrap();
+//     for chain_id in [1, 42161, 8453, 137] {
+//         let mut evm = cryptfolio::platform::blockchain::Evm::new("<address>", chain_id, "<rpc url>").unwrap();
+//         evm.set_indexer("https://api.etherscan.io/v2/api", "KEY");
+//         let handle = app.add_platform("Main Wallet", evm);
+//         app.sync_platform(handle).await.unwrap();
+//     }
+// }
+
+#[test]
+fn bitcoin_derive_address() {
+    // BIP84 test vectors (m/84'/0'/0').
+    let wallet = cryptfolio::platform::blockchain::Bitcoin::new(
+        "zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs",
+        cryptfolio::platform::blockchain::BitcoinBackend::Esplora,
+        "https://blockstream.info/api",
+        20
+    ).unwrap();
+    assert_eq!(wallet.derive_address(0, 0).unwrap().to_string(), "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu");
+    assert_eq!(wallet.derive_address(0, 1).unwrap().to_string(), "bc1qnjg0jd8228aq7egyzacy8cys3knf9xvrerkf9g");
+    assert_eq!(wallet.derive_address(1, 0).unwrap().to_string(), "bc1q8c6fshw2dlwun7ekn9qwf37cu2rn755upcp6el");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn bitcoin_esplora_sync() {
+    let receive_0 = "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu";
+    let receive_1 = "bc1qnjg0jd8228aq7egyzacy8cys3knf9xvrerkf9g";
+    let change_0 = "bc1q8c6fshw2dlwun7ekn9qwf37cu2rn755upcp6el";
+    let sender = "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq";
+    let recipient = "bc1qm34lsc65zpw79lxes69zkqmk6ee3ewf0j77s3h";
+    let transaction = |txid: &str, block_time: i64, inputs: Vec<(&str, u64, &str, u64)>, outputs: Vec<(&str, u64)>, fee: u64| serde_json::json!({
+        "txid": txid,
+        "status": { "confirmed": true, "block_time": block_time },
+        "vin": inputs.iter().map(|x| serde_json::json!({
+            "txid": x.0, "vout": x.1, "prevout": { "scriptpubkey_address": x.2, "value": x.3 }
+        })).collect::<Vec<serde_json::Value>>(),
+        "vout": outputs.iter().map(|x| serde_json::json!({
+            "scriptpubkey_address": x.0, "value": x.1
+        })).collect::<Vec<serde_json::Value>>(),
+        "fee": fee
+    });
+
+    // Two outputs received, a send with change, a send without change, then a consolidation
+    // of the change.
+    let received = transaction("tx1", 1672531200, vec![("tx0", 0, sender, 1501000)], vec![(receive_0, 1000000), (receive_1, 500000)], 1000);
+    let with_change = transaction("tx2", 1672617600, vec![("tx1", 0, receive_0, 1000000)], vec![(recipient, 600000), (change_0, 399000)], 1000);
+    let without_change = transaction("tx3", 1672704000, vec![("tx1", 1, receive_1, 500000)], vec![(recipient, 499000)], 1000);
+    let consolidation = transaction("tx4", 1672790400, vec![("tx2", 1, change_0, 399000)], vec![(receive_0, 398000)], 1000);
+    let histories = std::collections::HashMap::from([
+        (receive_0, vec![received.clone(), with_change.clone(), consolidation.clone()]),
+        (receive_1, vec![received, without_change]),
+        (change_0, vec![with_change, consolidation]),
+    ]);
+    let endpoint = mock_http(move |path, _body| {
+        let address = path.strip_prefix("/address/").and_then(|x| x.strip_suffix("/txs/chain")).unwrap();
+        serde_json::json!(histories.get(address).cloned().unwrap_or_default())
+    });
+
+    let app = CryptfolioApp::new(":memory:").unwrap();
+    let wallet = cryptfolio::platform::blockchain::Bitcoin::new(
+        "zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs",
+        cryptfolio::platform::blockchain::BitcoinBackend::Esplora,
+        endpoint.as_str(),
+        2
+    ).unwrap();
+    let handle = app.add_platform("Cold Storage", wallet);
+    app.sync_platform(handle).await.unwrap();
+
+    let mut transfers: Vec<(String, f64, f64)> = app.get_transfer_review_queue()
+        .into_iter()
+        .map(|x| (x.id, x.unit_size, x.fee))
+        .collect();
+    transfers.sort_by(|a, b| a.0.cmp(&b.0));
+    let expected = [
+        ("tx1:0", 0.01, 0.0),
+        ("tx1:1", 0.005, 0.0),
+        ("tx2", 0.006, 0.00001),
+        ("tx2:change", 0.00399, 0.0),
+        ("tx3", 0.00499, 0.00001),
+        ("tx4", 0.00398, 0.00001),
+    ];
+    assert_eq!(transfers.len(), expected.len());
+    for (transfer, expected) in transfers.iter().zip(expected) {
+        assert_eq!(transfer.0, expected.0);
+        assert!((transfer.1 - expected.1).abs() < 1e-12);
+        assert!((transfer.2 - expected.2).abs() < 1e-12);
+    }
+    assert!((app.get_balances(Some("Bitcoin"), None)["BTC"] - 0.00398).abs() < 1e-9);
+}
+
+#[test]
+fn kraken_fixtures() {
+    let read = |name: &str| -> serde_json::Value {
+        serde_json::from_str(&std::fs::read_to_string(format!("tests/fixtures/kraken/{}.json", name)).unwrap()).unwrap()
+    };
+    let kraken = cryptfolio::platform::exchange::Kraken::new("KEY", "SECRET").unwrap();
+    let entries = kraken.process_history(&read("asset_pairs"), &read("balance"), &read("ledgers"), &read("trades")).unwrap();
+
+    // deposit, instant buy, staking reward, 2 withdrawals, 2 trades, and an account and balance for each of USD, BTC, ETH, DOT
+    assert_eq!(entries.len(), 15);
+    assert_eq!(cryptfolio::platform::exchange::Kraken::normalize_asset("XXBT"), "BTC");
+    assert_eq!(cryptfolio::platform::exchange::Kraken::normalize_asset("DOT.S"), "DOT");
+
+    // A delisted pair is split from the trade's ledger legs rather than failing the sync.
+    let mut asset_pairs = read("asset_pairs");
+    asset_pairs.as_object_mut().unwrap().remove("DOTXBT");
+    let entries = kraken.process_history(&asset_pairs, &read("balance"), &read("ledgers"), &read("trades")).unwrap();
+    assert_eq!(entries.len(), 15);
+}
+
+#[tokio::test]
+async fn binance_fixtures() {
+    use cryptfolio::platform::exchange::{Binance, BinanceHistory, BinanceRegion};
+    use cryptfolio::recording::DiagnosticType;
+
+    let read = |name: &str| -> serde_json::Value {
+        serde_json::from_str(&std::fs::read_to_string(format!("tests/fixtures/binance/{}.json", name)).unwrap()).unwrap()
+    };
+    let history = || BinanceHistory {
+        account: read("account"),
+        exchange_info: read("exchange_info"),
+        deposits: serde_json::from_value(read("deposits")).unwrap(),
+        converts: serde_json::from_value(read("converts")).unwrap(),
+        trades: serde_json::from_value(read("trades")).unwrap(),
+        klines: serde_json::from_value(read("klines")).unwrap(),
+        ..BinanceHistory::default()
+    };
+    let binance = Binance::new("KEY", "SECRET", BinanceRegion::Global).unwrap();
+
+    // deposit, convert (the failed one is skipped), 2 trades, the BNB fee of the first, and an
+    // account and balance for each of BTC, BNB and USDT
+    let entries = binance.process_history(&history()).unwrap();
+    assert_eq!(entries.len(), 11);
+
+    // The BNB bought by the convert is partly disposed of as the fee, at the day's close.
+    let app = CryptfolioApp::new(":memory:").unwrap();
+    app.set_price_source(std::rc::Rc::new(std::collections::HashMap::from([("USDT".to_string(), 1.0)])));
+    seed(&app, "Binance", entries).await;
+    let bnb = app.get_coin_record("BNB".to_string(), None);
+    assert!((bnb.get_shares() - 0.5999).abs() < 1e-9);
+    assert_eq!(bnb.get_tax_records().len(), 1);
+    assert!((bnb.get_tax_records()[0].buy_price - 250.0).abs() < 1e-9);
+    assert!((bnb.get_tax_records()[0].sell_price - 246.5).abs() < 1e-9);
+    assert!((bnb.get_tax_records()[0].unit_size - 0.0001).abs() < 1e-12);
+    let lots = app.get_lots("BTC".to_string(), None);
+    assert_eq!(lots.len(), 1);
+    assert!((lots[0].shares - 0.005).abs() < 1e-12);
+
+    // Without the day's close, the disposal is left without a price for the diagnostics.
+    let mut unpriced = history();
+    unpriced.klines.clear();
+    let app = CryptfolioApp::new(":memory:").unwrap();
+    seed(&app, "Binance", binance.process_history(&unpriced).unwrap()).await;
+    let diagnostics = app.get_diagnostics();
+    let missing = diagnostics.get_diagnostics_of_type(DiagnosticType::MissingPrice);
+    assert!(missing.iter().any(|x| x.id == "BTCUSDT:1:fee"));
+}
+
+#[tokio::test]
+async fn coinbase_advanced_fills() {
+    let fill = |id: &str, fee: f64, description: &str| -> Box<dyn DatabaseEntry + Send> {
+        let mut fill = cryptfolio::database::entry::CoinOrder::new(
+            id.to_string(), "2023-01-02T00:00:00Z".to_string(), "BTC-USD".to_string(), 20000.0, 0.1, fee, "buy".to_string(), "Coinbase".to_string()
+        );
+        fill.set_description(description.to_string());
+        fill.set_order_id("b9a7c5e3-1d2f-4a6b-8c0e-2f4a6b8c0e1d".to_string());
+        Box::new(fill)
+    };
+
+    // The Advanced Trade fill already imported by Coinbase is replaced, not duplicated.
+    let app = CryptfolioApp::new(":memory:").unwrap();
+    seed(&app, "Coinbase", vec![fill("cb-1", 12.0, "Advanced Trade fill")]).await;
+    seed(&app, "Coinbase Advanced", vec![fill("adv-1", 10.0, "Advanced Trade: Default")]).await;
+    let lots = app.get_lots("BTC".to_string(), None);
+    assert_eq!(lots.len(), 1);
+    assert_eq!(lots[0].fee, 10.0);
+    assert!((app.get_balances(Some("Coinbase"), None)["BTC"] - 0.1).abs() < 1e-12);
+}